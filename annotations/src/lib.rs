@@ -1106,28 +1106,28 @@ macro_rules! assume_unreachable {
 macro_rules! verify_unreachable {
     () => {
         if cfg!(mirai) {
-            panic!("statement is reachable");
+            $crate::mirai_verify_unreachable("statement is reachable")
         } else {
             unreachable!()
         }
     };
     ($message:literal) => {
         if cfg!(mirai) {
-            panic!($message);
+            $crate::mirai_verify_unreachable($message)
         } else {
             unreachable!($message)
         }
     };
     ($msg:expr) => ({
         if cfg!(mirai) {
-            panic!($message)
+            $crate::mirai_verify_unreachable($msg)
         } else {
             unreachable!($msg)
         }
     });
     ($fmt:expr, $($arg:tt)*) => {
         if cfg!(mirai) {
-            panic!($fmt, $($arg)*);
+            $crate::mirai_verify_unreachable($fmt)
         } else {
             unreachable!($fmt, $($arg)*)
         }
@@ -1180,6 +1180,12 @@ pub fn mirai_precondition(_condition: bool, _message: &str) {}
 #[doc(hidden)]
 pub fn mirai_verify(_condition: bool, _message: &str) {}
 
+// Helper function for MIRAI. Should only be called via the verify_unreachable macro.
+#[doc(hidden)]
+pub fn mirai_verify_unreachable(message: &str) -> ! {
+    panic!("{}", message)
+}
+
 // Helper function for MIRAI. Should only be called via the get_model_field macro.
 #[doc(hidden)]
 pub fn mirai_get_model_field<T, V>(_target: T, _field_name: &str, default_value: V) -> V {
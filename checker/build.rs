@@ -4,8 +4,66 @@
 // LICENSE file in the root directory of this source tree.
 //
 
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
     if cfg!(windows) {
         println!("cargo:rustc-link-search=binaries");
     }
+
+    // Record the compiler and the standard contracts this build is based on. The summary store
+    // header includes them, so that summaries computed by a different build are not reused.
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!(
+        "cargo:rustc-env=MIRAI_RUSTC_VERSION={}",
+        rustc_version.trim()
+    );
+
+    let contracts = Path::new("../standard_contracts/src");
+    let mut hash = FNV_OFFSET_BASIS;
+    if contracts.is_dir() {
+        hash_directory(contracts, &mut hash);
+    }
+    println!("cargo:rustc-env=MIRAI_STANDARD_CONTRACTS_HASH={hash:016x}");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../standard_contracts/src");
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Adds the names and contents of the files in the given directory to an FNV-1a hash,
+/// in an order that does not depend on the file system.
+fn hash_directory(directory: &Path, hash: &mut u64) {
+    let mut entries: Vec<_> = fs::read_dir(directory)
+        .expect("readable directory")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            hash_directory(&path, hash);
+        } else {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let contents = fs::read(&path).unwrap_or_default();
+            // Carriage returns are skipped, so that the hash does not depend on line endings.
+            for byte in name
+                .as_bytes()
+                .iter()
+                .chain(contents.iter())
+                .filter(|b| **b != b'\r')
+            {
+                *hash ^= u64::from(*byte);
+                *hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+    }
 }
@@ -8,7 +8,7 @@
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::HashSet;
-use std::fmt::{Debug, Formatter, Result};
+use std::fmt::{Debug, Display, Formatter, Result};
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::rc::Rc;
@@ -22,7 +22,7 @@ use crate::bool_domain::BoolDomain;
 use crate::constant_domain::ConstantDomain;
 use crate::environment::Environment;
 use crate::expression::Expression::{ConditionalExpression, Join};
-use crate::expression::{Expression, ExpressionType};
+use crate::expression::{Expression, ExpressionType, WithParameterNames};
 use crate::interval_domain::{self, IntervalDomain};
 use crate::k_limits;
 use crate::known_names::KnownNames;
@@ -67,6 +67,19 @@ impl Debug for AbstractValue {
     }
 }
 
+/// See WithParameterNames.
+impl Display for AbstractValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Display::fmt(&self.expression, f)
+    }
+}
+
+impl Display for WithParameterNames<'_, AbstractValue> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        WithParameterNames::new(&self.value.expression, self.parameter_names).fmt(f)
+    }
+}
+
 /// Make a new value from the given expression, using defaults for all other fields.
 const fn make_value(e: Expression) -> AbstractValue {
     AbstractValue {
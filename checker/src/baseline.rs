@@ -0,0 +1,221 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use rustc_errors::Diag;
+
+use crate::sarif;
+use crate::utils;
+
+/// Identifies a diagnostic independently of where exactly in a source file it appears, so that
+/// diagnostics recorded in a baseline are still recognized after unrelated edits move code around.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Fingerprint {
+    /// The summary key of the function in which the diagnostic was found.
+    pub summary_key: String,
+    /// The code of the rule the diagnostic belongs to.
+    pub rule: String,
+    /// The normalized text of the diagnostic message, without the rule tag.
+    pub message: String,
+}
+
+impl Fingerprint {
+    pub fn new(summary_key: &str, diag: &Diag<'_, ()>) -> Fingerprint {
+        let (rule, text) = sarif::split_rule_id(sarif::message_text(&diag.messages[0].0));
+        Fingerprint {
+            summary_key: summary_key.to_string(),
+            rule: rule.to_string(),
+            message: normalize_message(text),
+        }
+    }
+}
+
+/// Collapses runs of white space and replaces runs of digits with #, so that messages that
+/// mention source positions or generated names still match after the code has been edited.
+fn normalize_message(message: &str) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut in_number = false;
+    for word in message.split_whitespace() {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        for ch in word.chars() {
+            if ch.is_ascii_digit() {
+                if !in_number {
+                    result.push('#');
+                    in_number = true;
+                }
+            } else {
+                result.push(ch);
+                in_number = false;
+            }
+        }
+        in_number = false;
+    }
+    result
+}
+
+/// A record of known diagnostics, grouped by the crate in which they were found.
+/// Each crate is analyzed by a separate MIRAI invocation, so a single baseline file can serve
+/// a whole workspace and updating it for one crate leaves the entries of other crates alone.
+/// A fingerprint occurs once for every diagnostic that has it.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Baseline {
+    crates: BTreeMap<String, Vec<Fingerprint>>,
+}
+
+impl Baseline {
+    /// Reads a baseline from the JSON file at the given path.
+    pub fn read(path: &Path) -> Result<Baseline, String> {
+        let baseline_str = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_json(&baseline_str)
+    }
+
+    fn from_json(json: &str) -> Result<Baseline, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Returns, for each of the given fingerprints of diagnostics found in the given crate,
+    /// whether it is recorded in the baseline. A fingerprint that is recorded n times matches at
+    /// most n diagnostics.
+    pub fn known_findings(&self, crate_name: &str, fingerprints: &[Fingerprint]) -> Vec<bool> {
+        let mut counts = HashMap::new();
+        for fingerprint in self.crates.get(crate_name).into_iter().flatten() {
+            *counts.entry(fingerprint).or_insert(0usize) += 1;
+        }
+        fingerprints
+            .iter()
+            .map(|fingerprint| match counts.get_mut(fingerprint) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Replaces the recorded fingerprints of the given crate.
+    pub fn set_findings(&mut self, crate_name: String, mut fingerprints: Vec<Fingerprint>) {
+        if fingerprints.is_empty() {
+            self.crates.remove(&crate_name);
+        } else {
+            // Sorted so that the file does not change if the diagnostics do not.
+            fingerprints.sort();
+            self.crates.insert(crate_name, fingerprints);
+        }
+    }
+
+    /// Returns the known findings of the given crate in the baseline file at the given path, like
+    /// known_findings, and then replaces the fingerprints of the crate in the file with the given
+    /// ones. The file is created if it does not exist. An exclusive lock is held on the file in
+    /// the meantime, since the crates of a workspace can be analyzed in parallel.
+    pub fn update_file(
+        path: &Path,
+        crate_name: &str,
+        fingerprints: &[Fingerprint],
+    ) -> Result<Vec<bool>, String> {
+        let mut known_findings = vec![];
+        utils::update_locked_file(path, |json| {
+            let mut baseline = match json {
+                Some(json) => Self::from_json(json)?,
+                None => Baseline::default(),
+            };
+            known_findings = baseline.known_findings(crate_name, fingerprints);
+            baseline.set_findings(crate_name.to_string(), fingerprints.to_vec());
+            baseline.to_json()
+        })?;
+        Ok(known_findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_normalized() {
+        assert_eq!(
+            normalize_message("possible  attempt to\tadd with overflow "),
+            "possible attempt to add with overflow"
+        );
+        assert_eq!(
+            normalize_message("precondition at src/lib.rs:120:5"),
+            "precondition at src/lib.rs:#:#"
+        );
+    }
+
+    fn fingerprint(summary_key: &str) -> Fingerprint {
+        Fingerprint {
+            summary_key: summary_key.to_string(),
+            rule: "MIRAI0001".to_string(),
+            message: "possible attempt to add with overflow".to_string(),
+        }
+    }
+
+    #[test]
+    fn known_findings_are_matched_as_often_as_they_are_recorded() {
+        let mut baseline = Baseline::default();
+        baseline.set_findings("c".to_string(), vec![fingerprint("c.f")]);
+        let findings = [fingerprint("c.f"), fingerprint("c.f"), fingerprint("c.g")];
+        assert_eq!(
+            baseline.known_findings("c", &findings),
+            vec![true, false, false]
+        );
+        assert_eq!(
+            baseline.known_findings("d", &findings),
+            vec![false, false, false]
+        );
+    }
+
+    #[test]
+    fn updating_a_file_suppresses_known_findings_and_keeps_other_crates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let mut baseline = Baseline::default();
+        baseline.set_findings("c".to_string(), vec![fingerprint("c.f")]);
+        baseline.set_findings("c (bin)".to_string(), vec![fingerprint("main")]);
+        fs::write(&path, baseline.to_json().unwrap()).unwrap();
+
+        let findings = [fingerprint("c.g"), fingerprint("c.f")];
+        assert_eq!(
+            Baseline::update_file(&path, "c", &findings).unwrap(),
+            vec![false, true]
+        );
+
+        let updated = Baseline::read(&path).unwrap();
+        assert_eq!(
+            updated.crates["c"],
+            vec![fingerprint("c.f"), fingerprint("c.g")]
+        );
+        assert_eq!(updated.crates["c (bin)"], vec![fingerprint("main")]);
+    }
+
+    #[test]
+    fn updating_a_missing_file_creates_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let findings = [fingerprint("c.f")];
+        assert_eq!(
+            Baseline::update_file(&path, "c", &findings).unwrap(),
+            vec![false]
+        );
+        assert_eq!(
+            Baseline::read(&path)
+                .unwrap()
+                .known_findings("c", &findings),
+            vec![true]
+        );
+    }
+}
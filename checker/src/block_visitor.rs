@@ -29,8 +29,10 @@ use rustc_trait_selection::infer::TyCtxtInferExt;
 
 use crate::abstract_value::{self, AbstractValue, AbstractValueTrait, BOTTOM};
 use crate::body_visitor::BodyVisitor;
+use crate::call_graph::CallResolution;
 use crate::call_visitor::CallVisitor;
 use crate::constant_domain::{ConstantDomain, FunctionReference};
+use crate::diagnostic_rules::DiagnosticRule;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType};
 use crate::k_limits;
@@ -38,10 +40,12 @@ use crate::known_names::KnownNames;
 use crate::options::DiagLevel;
 use crate::path::{Path, PathEnum, PathSelector};
 use crate::path::{PathOrFunction, PathRefinement, PathRoot};
+use crate::sarif;
 use crate::smt_solver::{SmtResult, SmtSolver};
 use crate::summaries::Precondition;
 use crate::tag_domain::Tag;
 use crate::type_visitor::TypeVisitor;
+use crate::unresolved_calls::{UnresolvedCall, UnresolvedReason};
 use crate::utils;
 
 /// Holds the state for the basic block visitor
@@ -376,7 +380,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                                     "[MIRAI] unknown tag type for constant-time verification: {tag_name}",
                                 ),
                             );
-                            self.bv.emit_diagnostic(warning);
+                            self.bv
+                                .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
                         }
                     }
                     Some(tag) => self.check_tag_existence_on_value(
@@ -623,13 +628,20 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         let func_ref_to_call = if let Some(fr) = func_ref {
             fr
         } else {
-            if self.might_be_reachable().unwrap_or(true)
-                && self
+            if self.might_be_reachable().unwrap_or(true) {
+                let callee_ty = self.get_operand_rustc_type(func);
+                self.record_unresolved_call(
+                    callee_ty.to_string(),
+                    None,
+                    UnresolvedReason::UnknownCallee,
+                );
+                if self
                     .bv
                     .already_reported_errors_for_call_to
                     .insert(func_to_call)
-            {
-                self.report_missing_summary();
+                {
+                    self.report_missing_summary();
+                }
             }
             return;
         };
@@ -737,6 +749,10 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         call_visitor.callee_fun_val = func_to_call;
         call_visitor.function_constant_args = func_const_args;
         call_visitor.initial_type_cache = adt_map;
+        if !matches!(func, mir::Operand::Constant(..)) {
+            // The callee is the value of a local, which has been resolved to a function constant.
+            call_visitor.call_resolution = CallResolution::FunctionPointer;
+        }
         trace!("calling func {:?}", call_visitor.callee_func_ref);
         if call_visitor.handled_as_special_function_call() {
             return;
@@ -744,6 +760,30 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         let function_summary = call_visitor.get_function_summary().unwrap_or_default();
 
         if !function_summary.is_computed {
+            if known_name != KnownNames::StdCloneClone || !self_ty_is_fn_ptr {
+                let tcx = call_visitor.block_visitor.bv.tcx;
+                let callee_def_id = call_visitor.callee_def_id;
+                let reason = if call_visitor.call_resolution == CallResolution::Virtual {
+                    Some(UnresolvedReason::Virtual)
+                } else if !tcx.is_mir_available(callee_def_id) {
+                    Some(UnresolvedReason::MissingSummary)
+                } else {
+                    // The summary of a function with a body is not yet computed if the call is
+                    // recursive.
+                    None
+                };
+                if let Some(reason) = reason {
+                    let summary_key = call_visitor
+                        .callee_func_ref
+                        .as_ref()
+                        .map(|func_ref| func_ref.summary_cache_key.clone());
+                    call_visitor.block_visitor.record_unresolved_call(
+                        utils::def_id_as_qualified_name_str(tcx, callee_def_id).to_string(),
+                        summary_key.as_deref(),
+                        reason,
+                    );
+                }
+            }
             if (known_name != KnownNames::StdCloneClone || !self_ty_is_fn_ptr)
                 && call_visitor
                     .block_visitor
@@ -952,6 +992,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     let precondition = Precondition {
                         condition: promotable_entry_condition.logical_not(),
                         message: Rc::from("incomplete analysis of call because of a nested call to a function without a MIR body"),
+                        rule: DiagnosticRule::IncompleteAnalysis,
                         provenance: None,
                         spans: vec![self.bv.current_span.source_callsite()],
                     };
@@ -972,12 +1013,53 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                         self.bv.current_span,
                         "[MIRAI] the called function did not resolve to an implementation with a MIR body",
                     );
-                    self.bv.emit_diagnostic(warning);
+                    self.bv
+                        .emit_diagnostic(warning, DiagnosticRule::IncompleteAnalysis);
                 }
             }
         }
     }
 
+    /// Adds the call at the current location to the unresolved calls report, if one is requested.
+    /// The summary key is that of the callee, if it is known.
+    pub fn record_unresolved_call(
+        &mut self,
+        callee: String,
+        summary_key: Option<&str>,
+        reason: UnresolvedReason,
+    ) {
+        if self.bv.cv.options.unresolved_calls.is_none() {
+            return;
+        }
+        let tcx = self.bv.tcx;
+        let location = tcx
+            .sess
+            .source_map()
+            .lookup_char_pos(self.bv.current_span.source_callsite().lo());
+        let caller = self
+            .bv
+            .cv
+            .summary_cache
+            .get_summary_key_for(self.bv.def_id, tcx)
+            .to_string();
+        let has_foreign_contract = summary_key
+            .is_some_and(|key| self.bv.cv.summary_cache.has_persistent_summaries_for(key));
+        self.bv.cv.unresolved_calls.push(UnresolvedCall {
+            file: location
+                .file
+                .name
+                .prefer_remapped_unconditionaly()
+                .to_string(),
+            line: location.line,
+            column: location.col.0 + 1,
+            caller,
+            callee,
+            summary_key: summary_key.map(|key| key.to_string()),
+            reason,
+            has_foreign_contract,
+        });
+    }
+
     /// Returns the function reference part of the value, if there is one.
     #[logfn_inputs(TRACE)]
     pub fn get_func_ref(&mut self, val: &Rc<AbstractValue>) -> Option<Rc<FunctionReference>> {
@@ -1147,12 +1229,14 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         } else {
             precondition.message.clone()
         };
+        let mut provenance_arg = None;
         if precondition.spans.is_empty() {
             if let Some(provenance) = &precondition.provenance {
                 let mut buffer = diagnostic.to_string();
                 buffer.push_str(", defined in ");
                 buffer.push_str(provenance.as_ref());
                 diagnostic = Rc::from(buffer.as_str());
+                provenance_arg = Some(provenance.to_string());
             }
         }
         let span = self.bv.current_span;
@@ -1162,6 +1246,12 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             .session
             .dcx()
             .struct_span_warn(span, "[MIRAI] ".to_string() + diagnostic.as_ref());
+        if let Some(provenance) = provenance_arg {
+            // Not part of the message, but lets machine readable output point at the definition.
+            warning.arg(sarif::PROVENANCE_ARG, provenance);
+        }
+        self.bv
+            .explain_possible_failure(&mut warning, condition, &precondition.message);
         for pc_span in precondition.spans.iter() {
             let snippet = self.bv.tcx.sess.source_map().span_to_snippet(*pc_span);
             if snippet.is_ok() {
@@ -1176,7 +1266,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 warning.span_note(*pc_span, format!("related location {span_str}"));
             }
         }
-        self.bv.emit_diagnostic(warning);
+        self.bv.emit_diagnostic(warning, precondition.rule);
     }
 
     /// Extend the current post condition by the given `cond`. If none was set before,
@@ -1202,7 +1292,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                             span,
                             "[MIRAI] multiple post conditions must be on the same execution path",
                         );
-                        self.bv.emit_diagnostic(warning);
+                        self.bv
+                            .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
                     }
                 }
                 (_, _) => {
@@ -1238,7 +1329,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             let message =
                 "[MIRAI] this is unreachable, mark it as such by using the verify_unreachable! macro";
             let warning = self.bv.cv.session.dcx().struct_span_warn(span, message);
-            self.bv.emit_diagnostic(warning);
+            self.bv
+                .emit_diagnostic(warning, DiagnosticRule::Unreachable);
             return None;
         }
 
@@ -1255,7 +1347,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 "[MIRAI] possible unsatisfied postcondition"
             };
             let warning = self.bv.cv.session.dcx().struct_span_warn(span, msg);
-            self.bv.emit_diagnostic(warning);
+            self.bv
+                .emit_diagnostic(warning, DiagnosticRule::UnsatisfiedPostcondition);
             // Don't add the post condition to the summary
             return None;
         }
@@ -1276,7 +1369,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 .session
                 .dcx()
                 .struct_span_warn(span, "[MIRAI] provably false verification condition");
-            self.bv.emit_diagnostic(warning);
+            self.bv
+                .emit_diagnostic(warning, DiagnosticRule::FalseVerificationCondition);
             if entry_cond_as_bool.is_none()
                 && self.bv.preconditions.len() < k_limits::MAX_INFERRED_PRECONDITIONS
             {
@@ -1288,6 +1382,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     let precondition = Precondition {
                         condition,
                         message,
+                        rule: DiagnosticRule::FalseVerificationCondition,
                         provenance: None,
                         spans: vec![span],
                     };
@@ -1298,6 +1393,13 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
         }
 
         let warning = format!("possible {message}");
+        // The conditions of special functions other than verify!, such as intrinsics that must
+        // not be called with zero, are preconditions of those functions.
+        let rule = if function_name == KnownNames::MiraiVerify {
+            DiagnosticRule::FalseVerificationCondition
+        } else {
+            DiagnosticRule::UnsatisfiedPrecondition
+        };
 
         // We might get here, or not, and the condition might be false, or not.
         // Give a warning if we don't know all of the callers, or if we run into a k-limit
@@ -1321,13 +1423,15 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 )
             {
                 let span = self.bv.current_span.source_callsite();
-                let warning = self
+                let mut diagnostic = self
                     .bv
                     .cv
                     .session
                     .dcx()
                     .struct_span_warn(span, "[MIRAI] ".to_string() + warning.clone().as_ref());
-                self.bv.emit_diagnostic(warning);
+                self.bv
+                    .explain_possible_failure(&mut diagnostic, cond, &warning);
+                self.bv.emit_diagnostic(diagnostic, rule);
             }
         }
 
@@ -1416,7 +1520,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                                 tag_name
                             ),
                         );
-                        self.bv.emit_diagnostic(warning);
+                        self.bv.emit_diagnostic(warning, DiagnosticRule::TagCheck);
                     } else if promotable_entry_condition.is_none()
                         || tag_check.extract_promotable_disjuncts(false).is_none()
                     {
@@ -1429,7 +1533,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                                 because it contains local variables",
                             ),
                         );
-                        self.bv.emit_diagnostic(warning);
+                        self.bv.emit_diagnostic(warning, DiagnosticRule::TagCheck);
                     }
                 }
 
@@ -1441,7 +1545,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                         span,
                         format!("[MIRAI] the {value_name} has a {tag_name} tag"),
                     );
-                    self.bv.emit_diagnostic(warning);
+                    self.bv.emit_diagnostic(warning, DiagnosticRule::TagCheck);
                 }
 
                 _ => {}
@@ -1466,6 +1570,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                             if checking_presence { "may not" } else { "may" },
                             tag_name
                         )),
+                        rule: DiagnosticRule::TagCheck,
                         provenance: None,
                         spans: vec![self.bv.current_span.source_callsite()],
                     };
@@ -1552,7 +1657,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                                 .session
                                 .dcx()
                                 .struct_span_warn(span, "[MIRAI] ".to_string() + error);
-                            self.bv.emit_diagnostic(warning);
+                            self.bv.emit_diagnostic(warning, get_assert_rule(msg));
                             // No need to push a precondition, the caller can never satisfy it.
                             return;
                         }
@@ -1567,8 +1672,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     // leading to false positives. When this arises in practice, it would be because
                     // some weakness in the analysis of the current function has lead to an imprecise
                     // value for cond_val.
-                    let promotable_cond_val = (if expected { cond_val } else { not_cond_val })
-                        .extract_promotable_disjuncts(false);
+                    let expected_cond_val = if expected { cond_val } else { not_cond_val };
+                    let promotable_cond_val = expected_cond_val.extract_promotable_disjuncts(false);
                     check_for_early_return!(self.bv);
                     let promotable_entry_cond = self
                         .bv
@@ -1583,11 +1688,20 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                             && self.bv.cv.options.diag_level >= DiagLevel::Library)
                     {
                         // Can't make this the caller's problem.
-                        let warning =
-                            format!("[MIRAI] possible {}", get_assert_msg_description(msg));
+                        let message = format!("possible {}", get_assert_msg_description(msg));
                         let span = self.bv.current_span;
-                        let warning = self.bv.cv.session.dcx().struct_span_warn(span, warning);
-                        self.bv.emit_diagnostic(warning);
+                        let mut warning = self
+                            .bv
+                            .cv
+                            .session
+                            .dcx()
+                            .struct_span_warn(span, format!("[MIRAI] {message}"));
+                        self.bv.explain_possible_failure(
+                            &mut warning,
+                            &expected_cond_val,
+                            &message,
+                        );
+                        self.bv.emit_diagnostic(warning, get_assert_rule(msg));
                         return;
                     }
 
@@ -1608,6 +1722,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                     let precondition = Precondition {
                         condition,
                         message,
+                        rule: get_assert_rule(msg),
                         provenance: None,
                         spans: vec![self.bv.current_span],
                     };
@@ -1616,6 +1731,17 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             }
         }
 
+        fn get_assert_rule(msg: &mir::AssertMessage<'_>) -> DiagnosticRule {
+            use mir::AssertKind::*;
+            match msg {
+                BoundsCheck { .. } => DiagnosticRule::IndexOutOfBounds,
+                MisalignedPointerDereference { .. } => DiagnosticRule::MemorySafety,
+                Overflow(..) | OverflowNeg(_) => DiagnosticRule::Overflow,
+                DivisionByZero(_) | RemainderByZero(_) => DiagnosticRule::DivisionByZero,
+                ResumedAfterReturn(_) | ResumedAfterPanic(_) => DiagnosticRule::Panic,
+            }
+        }
+
         fn get_assert_msg_description<'tcx>(msg: &mir::AssertMessage<'tcx>) -> &'tcx str {
             use mir::AssertKind::*;
             use mir::BinOp;
@@ -1688,7 +1814,7 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
                 self.bv.smt_solver.get_as_smt_predicate(ec)
             };
             self.bv.smt_solver.assert(&smt_expr);
-            if self.bv.smt_solver.solve() == SmtResult::Unsatisfiable {
+            if self.bv.solve_smt_query() == SmtResult::Unsatisfiable {
                 // The solver can prove that the entry condition is always false.
                 entry_cond_as_bool = Some(false);
             }
@@ -1705,7 +1831,8 @@ impl<'block, 'analysis, 'compilation, 'tcx> BlockVisitor<'block, 'analysis, 'com
             span,
             "[MIRAI] Inline assembly code cannot be analyzed by MIRAI.",
         );
-        self.bv.emit_diagnostic(warning);
+        self.bv
+            .emit_diagnostic(warning, DiagnosticRule::IncompleteAnalysis);
         // Don't stop the analysis if we are building a call graph.
         self.bv.analysis_is_incomplete = self.bv.cv.options.call_graph_config.is_none();
         if let Some(target) = targets.first() {
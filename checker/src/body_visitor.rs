@@ -4,7 +4,7 @@
 // LICENSE file in the root directory of this source tree.
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 use std::rc::Rc;
 use std::time::Instant;
@@ -15,6 +15,7 @@ use rpds::HashTrieMap;
 use mirai_annotations::*;
 use rustc_errors::Diag;
 use rustc_hir::def_id::DefId;
+use rustc_hir::Safety;
 use rustc_middle::mir;
 use rustc_middle::ty::{AdtDef, Const, GenericArgsRef, Ty, TyCtxt, TyKind, UintTy};
 
@@ -23,21 +24,20 @@ use crate::block_visitor::BlockVisitor;
 use crate::call_visitor::CallVisitor;
 use crate::constant_domain::ConstantDomain;
 use crate::crate_visitor::CrateVisitor;
+use crate::diagnostic_rules::DiagnosticRule;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType, LayoutSource};
 use crate::fixed_point_visitor::FixedPointVisitor;
 use crate::options::DiagLevel;
 use crate::path::{Path, PathEnum, PathSelector};
 use crate::path::{PathRefinement, PathRoot};
-#[cfg(not(feature = "z3"))]
-use crate::smt_solver::SolverStub;
-use crate::smt_solver::{SmtResult, SmtSolver};
+use crate::smt_queries::{self, QueryLocation};
+use crate::smt_solver::{ConfiguredExpression, ConfiguredSolver, SmtResult, SmtSolver};
 use crate::summaries;
 use crate::summaries::{Precondition, Summary};
 use crate::tag_domain::Tag;
+use crate::test_skeletons::TestSkeleton;
 use crate::type_visitor::{self, TypeCache, TypeVisitor};
-#[cfg(feature = "z3")]
-use crate::z3_solver::Z3Solver;
 use crate::{k_limits, utils};
 
 /// Holds the state for the function body visitor.
@@ -70,10 +70,7 @@ pub struct BodyVisitor<'analysis, 'compilation, 'tcx> {
     pub post_condition_block: Option<mir::BasicBlock>,
     pub preconditions: Vec<Precondition>,
     pub fresh_variable_offset: usize,
-    #[cfg(not(feature = "z3"))]
-    pub smt_solver: SolverStub,
-    #[cfg(feature = "z3")]
-    pub smt_solver: Z3Solver,
+    pub smt_solver: ConfiguredSolver,
     pub block_to_call: HashMap<mir::Location, DefId>,
     pub treat_as_foreign: bool,
     type_visitor: TypeVisitor<'tcx>,
@@ -86,16 +83,6 @@ impl Debug for BodyVisitor<'_, '_, '_> {
 }
 
 impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
-    #[cfg(feature = "z3")]
-    fn get_solver() -> Z3Solver {
-        Z3Solver::new()
-    }
-
-    #[cfg(not(feature = "z3"))]
-    fn get_solver() -> SolverStub {
-        SolverStub::default()
-    }
-
     pub fn new(
         crate_visitor: &'analysis mut CrateVisitor<'compilation, 'tcx>,
         def_id: DefId,
@@ -115,6 +102,10 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             tcx.instance_mir(instance)
         };
         crate_visitor.call_graph.add_root(def_id);
+        if let Some(incremental) = &mut crate_visitor.incremental {
+            incremental.note_body(def_id);
+        }
+        let smt_solver = ConfiguredSolver::new(crate_visitor.options);
         BodyVisitor {
             cv: crate_visitor,
             tcx,
@@ -142,7 +133,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             post_condition_block: None,
             preconditions: Vec::new(),
             fresh_variable_offset: 0,
-            smt_solver: Self::get_solver(),
+            smt_solver,
             block_to_call: HashMap::default(),
             treat_as_foreign: false,
             type_visitor: TypeVisitor::new(def_id, mir, tcx, type_cache),
@@ -309,7 +300,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 self.current_span,
                 "[MIRAI] The analysis of this function timed out",
             );
-            self.emit_diagnostic(warning);
+            self.emit_diagnostic(warning, DiagnosticRule::IncompleteAnalysis);
         }
         warn!(
             "analysis of {} timed out after {} seconds",
@@ -322,13 +313,21 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             self.active_calls_map.remove(&self.def_id);
         }
         self.analysis_is_incomplete = true;
+        if let Some(incremental) = &mut self.cv.incremental {
+            incremental.note_timeout();
+        }
     }
 
-    /// Adds the given diagnostic builder to the buffer.
+    /// Adds the given diagnostic builder, which belongs to the given rule, to the buffer.
     /// Buffering diagnostics gives us the chance to sort them before printing them out,
     /// which is desirable for tools that compare the diagnostics from one run of MIRAI with another.
     #[logfn_inputs(TRACE)]
-    pub fn emit_diagnostic(&mut self, diagnostic_builder: Diag<'compilation, ()>) {
+    pub fn emit_diagnostic(
+        &mut self,
+        mut diagnostic_builder: Diag<'compilation, ()>,
+        rule: DiagnosticRule,
+    ) {
+        rule.attach_to(&mut diagnostic_builder);
         if (self.treat_as_foreign || !self.def_id.is_local())
             && !matches!(self.cv.options.diag_level, DiagLevel::Paranoid)
         {
@@ -350,7 +349,12 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             diagnostic_builder.cancel();
             return;
         }
-        self.buffered_diagnostics.push(diagnostic_builder);
+        if let Some(incremental) = &mut self.cv.incremental {
+            incremental.note_diagnostic(&diagnostic_builder);
+        }
+        if let Some(diagnostic_builder) = self.cv.classify_diagnostic(diagnostic_builder) {
+            self.buffered_diagnostics.push(diagnostic_builder);
+        }
     }
 
     pub fn get_char_const_val(&mut self, val: u128) -> Rc<AbstractValue> {
@@ -794,6 +798,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 Precondition {
                     condition: refined_condition,
                     message: precondition.message.clone(),
+                    rule: precondition.rule,
                     provenance: precondition.provenance.clone(),
                     spans: precondition.spans.clone(),
                 }
@@ -1129,7 +1134,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 let span = self.current_span;
                 let message = "[MIRAI] effective offset is outside allocated range";
                 let warning = self.cv.session.dcx().struct_span_warn(span, message);
-                self.emit_diagnostic(warning);
+                self.emit_diagnostic(warning, DiagnosticRule::IndexOutOfBounds);
             }
         }
     }
@@ -1565,7 +1570,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     self.current_span,
                     "[MIRAI] the pointer points to memory that has already been deallocated",
                 );
-                self.emit_diagnostic(warning);
+                self.emit_diagnostic(warning, DiagnosticRule::MemorySafety);
             }
             let layouts_match = old_length
                 .equals(new_length.clone())
@@ -1592,7 +1597,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     .session
                     .dcx()
                     .struct_span_warn(self.current_span, message);
-                self.emit_diagnostic(warning);
+                self.emit_diagnostic(warning, DiagnosticRule::MemorySafety);
             }
         }
     }
@@ -1643,7 +1648,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                 self.smt_solver.get_as_smt_predicate(ec)
             };
             self.smt_solver.assert(&smt_expr);
-            let smt_result = self.smt_solver.solve();
+            let smt_result = self.solve_smt_query();
             if smt_result == SmtResult::Unsatisfiable {
                 // The solver can prove that the entry condition is always false.
                 entry_cond_as_bool = Some(false);
@@ -1663,7 +1668,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         self.smt_solver.set_backtrack_position();
         let cond_smt_expr = self.smt_solver.get_as_smt_predicate(ce);
         let inv_cond_smt_expr = self.smt_solver.invert_predicate(&cond_smt_expr);
-        let result = match self.smt_solver.solve_expression(&cond_smt_expr) {
+        let result = match self.solve_smt_expression(&cond_smt_expr) {
             SmtResult::Unsatisfiable => {
                 // If we get here, the solver can prove that cond_val is always false.
                 Some(false)
@@ -1671,7 +1676,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
             SmtResult::Satisfiable => {
                 // We could get here with cond_val being true. Or perhaps not.
                 // So lets see if !cond_val is provably false.
-                let smt_result = self.smt_solver.solve_expression(&inv_cond_smt_expr);
+                let smt_result = self.solve_smt_expression(&inv_cond_smt_expr);
                 if smt_result == SmtResult::Unsatisfiable {
                     // The solver can prove that !cond_val is always false.
                     Some(true)
@@ -1685,6 +1690,384 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
         result
     }
 
+    /// If --counterexamples or --test_skeletons is given, asks the solver for parameter values
+    /// for which the condition is false when the current location is reached. If there are such
+    /// values, they are added to the diagnostic as a note and a test that calls the function
+    /// being analyzed with these values is recorded, which is expected to fail with the message.
+    #[logfn_inputs(TRACE)]
+    pub fn explain_possible_failure(
+        &mut self,
+        diagnostic: &mut Diag<'compilation, ()>,
+        cond_val: &Rc<AbstractValue>,
+        message: &str,
+    ) {
+        if !self.cv.options.counterexamples && self.cv.options.test_skeletons.is_none() {
+            return;
+        }
+        let Some(counterexample) = self.get_counterexample(cond_val) else {
+            return;
+        };
+        if self.cv.options.counterexamples {
+            let values: Vec<String> = counterexample
+                .iter()
+                .filter_map(|(path, value)| {
+                    Some(format!("{} = {value}", self.get_rust_name_for(path)?))
+                })
+                .collect();
+            if !values.is_empty() {
+                diagnostic.note(format!("counterexample: {}", values.join(", ")));
+            }
+        }
+        if self.cv.options.test_skeletons.is_some() {
+            self.record_test_skeleton(&counterexample, message);
+        }
+    }
+
+    /// Returns values for the variables of the condition that are rooted by parameters, as
+    /// Rust literals, from a model of the entry condition and the negation of the condition,
+    /// or None if the solver finds no such model.
+    #[logfn_inputs(TRACE)]
+    fn get_counterexample(
+        &mut self,
+        cond_val: &Rc<AbstractValue>,
+    ) -> Option<Vec<(Rc<Path>, String)>> {
+        self.smt_solver.set_backtrack_position();
+        let entry_condition = &self.current_environment.entry_condition;
+        if entry_condition.as_bool_if_known().is_none() {
+            let ec = self
+                .smt_solver
+                .get_as_smt_predicate(&entry_condition.expression);
+            self.smt_solver.assert(&ec);
+        }
+        let cond_smt_expr = self.smt_solver.get_as_smt_predicate(&cond_val.expression);
+        let inv_cond_smt_expr = self.smt_solver.invert_predicate(&cond_smt_expr);
+        self.smt_solver.assert(&inv_cond_smt_expr);
+        let mut result = None;
+        // This query only serves to get a model, so unlike the queries that decide whether
+        // to report a problem, it is not counted if the result is undefined, nor dumped.
+        if self.smt_solver.solve() == SmtResult::Satisfiable {
+            // The model has to be read before the context changes.
+            let mut variables = BTreeMap::new();
+            cond_val
+                .expression
+                .record_parameter_variables(&mut variables);
+            let values = variables
+                .into_iter()
+                .filter_map(|(path, var_type)| {
+                    let value = self.smt_solver.get_model_value_for(&path, var_type)?;
+                    if var_type == ExpressionType::Char {
+                        let c = char::from_u32(value.parse().ok()?)?;
+                        return Some((path, format!("{c:?}")));
+                    }
+                    Some((path, value))
+                })
+                .collect();
+            result = Some(values);
+        }
+        self.smt_solver.backtrack();
+        result
+    }
+
+    /// Records a test that calls the function being analyzed with the values of the
+    /// counterexample, if the function can be called from outside of the crate.
+    #[logfn_inputs(TRACE)]
+    fn record_test_skeleton(&mut self, counterexample: &[(Rc<Path>, String)], message: &str) {
+        if self.treat_as_foreign
+            || !self.def_id.is_local()
+            || !self.function_being_analyzed_is_root()
+            || !utils::is_public(self.def_id, self.tcx)
+            // Constants and statics have bodies too, but no signature and cannot be called.
+            || !matches!(
+                self.tcx.def_kind(self.def_id),
+                rustc_hir::def::DefKind::Fn | rustc_hir::def::DefKind::AssocFn
+            )
+            || self.tcx.asyncness(self.def_id).is_async()
+        {
+            return;
+        }
+        let function = self.get_test_path_for(self.def_id);
+        // Closures, and methods of trait implementations, cannot be called by name.
+        if function.contains(['<', '{']) {
+            return;
+        }
+        let values: HashMap<String, String> = counterexample
+            .iter()
+            .filter_map(|(path, value)| Some((self.get_rust_name_for(path)?, value.clone())))
+            .collect();
+        let arguments = (1..=self.mir.arg_count)
+            .map(|ordinal| {
+                let ty = self.mir.local_decls[mir::Local::from(ordinal)].ty;
+                let name = self
+                    .get_rust_name_for(&Path::new_parameter(ordinal))
+                    .unwrap_or_default();
+                self.get_test_argument(ty, &name, &values, 0)
+            })
+            .collect();
+        let (file, line, column) = self.get_source_location(self.current_span);
+        let name = self.tcx.item_name(self.def_id).to_string();
+        self.cv.test_skeletons.push(TestSkeleton {
+            name: format!("{}_line_{line}", name.trim_start_matches("r#")),
+            location: format!("{file}:{line}:{column}"),
+            message: message.to_string(),
+            function,
+            arguments,
+            is_unsafe: self.tcx.fn_sig(self.def_id).skip_binder().safety() == Safety::Unsafe,
+        });
+    }
+
+    /// Returns an expression of the given type for a test argument, with the values that the
+    /// counterexample gives to the path with the given name and the paths rooted by it. Paths
+    /// without a value get zero, false or an empty string, since any value will do for them.
+    /// Types that are not primitive types, references, arrays, tuples or structs with public
+    /// fields become placeholders.
+    fn get_test_argument(
+        &self,
+        ty: Ty<'tcx>,
+        name: &str,
+        values: &HashMap<String, String>,
+        depth: usize,
+    ) -> String {
+        let placeholder = format!("todo!(\"a value of type {ty}\")");
+        if depth > k_limits::MAX_TEST_ARGUMENT_DEPTH {
+            return placeholder;
+        }
+        let value_or = |default: &str| {
+            values
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| default.to_string())
+        };
+        match ty.kind() {
+            TyKind::Bool => value_or("false"),
+            TyKind::Char => value_or("'\\0'"),
+            TyKind::Int(..) | TyKind::Uint(..) => value_or("0"),
+            TyKind::Float(..) => "0.0".to_string(),
+            TyKind::Str => "\"\"".to_string(),
+            TyKind::Ref(_, target, mutability) => {
+                // Fields and elements are selected through references implicitly.
+                let target_name = if target.is_primitive() {
+                    format!("*{name}")
+                } else {
+                    name.to_string()
+                };
+                let argument = self.get_test_argument(*target, &target_name, values, depth + 1);
+                if target.is_str() {
+                    argument
+                } else if mutability.is_mut() {
+                    format!("&mut {argument}")
+                } else {
+                    format!("&{argument}")
+                }
+            }
+            TyKind::Array(element_type, length) => match length.try_to_target_usize(self.tcx) {
+                Some(length) if length <= k_limits::MAX_TEST_ARGUMENT_ELEMENTS => {
+                    let elements: Vec<String> = (0..length)
+                        .map(|i| {
+                            let element_name = format!("{name}[{i}]");
+                            self.get_test_argument(*element_type, &element_name, values, depth + 1)
+                        })
+                        .collect();
+                    format!("[{}]", elements.join(", "))
+                }
+                _ => placeholder,
+            },
+            TyKind::Tuple(types) => {
+                let elements: Vec<String> = types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, element_type)| {
+                        let element_name = format!("{name}.{i}");
+                        self.get_test_argument(element_type, &element_name, values, depth + 1)
+                    })
+                    .collect();
+                if elements.len() == 1 {
+                    format!("({},)", elements[0])
+                } else {
+                    format!("({})", elements.join(", "))
+                }
+            }
+            TyKind::Adt(def, args)
+                if def.is_struct()
+                    && def
+                        .non_enum_variant()
+                        .fields
+                        .iter()
+                        .all(|f| f.vis.is_public()) =>
+            {
+                let fields: Vec<String> = def
+                    .non_enum_variant()
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let field_name = format!("{name}.{}", field.name);
+                        let field_type = field.ty(self.tcx, args);
+                        let value =
+                            self.get_test_argument(field_type, &field_name, values, depth + 1);
+                        format!("{}: {value}", field.name)
+                    })
+                    .collect();
+                let struct_path = self.get_test_path_for(def.did());
+                if fields.is_empty() {
+                    format!("{struct_path} {{}}")
+                } else {
+                    format!("{struct_path} {{ {} }}", fields.join(", "))
+                }
+            }
+            _ => placeholder,
+        }
+    }
+
+    /// Returns the path by which a test outside of the crate can refer to the item.
+    fn get_test_path_for(&self, def_id: DefId) -> String {
+        let path = self.tcx.def_path_str(def_id);
+        if def_id.is_local() {
+            format!("{}::{path}", self.tcx.crate_name(def_id.krate))
+        } else {
+            path
+        }
+    }
+
+    /// Returns the file, line and column of the start of the span, or of the macro call that
+    /// it was expanded from.
+    fn get_source_location(&self, span: rustc_span::Span) -> (String, usize, usize) {
+        let source_location = self
+            .tcx
+            .sess
+            .source_map()
+            .lookup_char_pos(span.source_callsite().lo());
+        (
+            source_location
+                .file
+                .name
+                .prefer_remapped_unconditionaly()
+                .to_string(),
+            source_location.line,
+            source_location.col.0 + 1,
+        )
+    }
+
+    /// Returns the Rust expression for a path that is rooted by a parameter, if there is one.
+    #[logfn_inputs(TRACE)]
+    pub fn get_rust_name_for(&self, path: &Rc<Path>) -> Option<String> {
+        match &path.value {
+            PathEnum::Parameter { ordinal } => {
+                self.mir
+                    .var_debug_info
+                    .iter()
+                    .find_map(|info| match &info.value {
+                        mir::VarDebugInfoContents::Place(place)
+                            if place.local.as_usize() == *ordinal
+                                && place.projection.is_empty() =>
+                        {
+                            Some(info.name.to_string())
+                        }
+                        _ => None,
+                    })
+            }
+            PathEnum::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } => {
+                if **selector == PathSelector::Deref {
+                    return Some(format!("*{}", self.get_rust_name_for(qualifier)?));
+                }
+                // Fields and elements are selected through references implicitly.
+                let base = match &qualifier.value {
+                    PathEnum::QualifiedPath {
+                        qualifier,
+                        selector,
+                        ..
+                    } if **selector == PathSelector::Deref => self.get_rust_name_for(qualifier)?,
+                    _ => self.get_rust_name_for(qualifier)?,
+                };
+                match selector.as_ref() {
+                    PathSelector::Field(ordinal) => {
+                        let qualifier_type = self
+                            .type_visitor()
+                            .get_path_rustc_type(qualifier, self.current_span);
+                        match self
+                            .type_visitor()
+                            .get_dereferenced_type(qualifier_type)
+                            .kind()
+                        {
+                            TyKind::Adt(def, _) if def.is_struct() => {
+                                let field = def.non_enum_variant().fields.get((*ordinal).into())?;
+                                Some(format!("{base}.{}", field.name))
+                            }
+                            TyKind::Tuple(..) => Some(format!("{base}.{ordinal}")),
+                            _ => None,
+                        }
+                    }
+                    PathSelector::Index(index) => {
+                        if let Expression::CompileTimeConstant(ConstantDomain::U128(i)) =
+                            &index.expression
+                        {
+                            Some(format!("{base}[{i}]"))
+                        } else {
+                            None
+                        }
+                    }
+                    PathSelector::ConstantIndex {
+                        offset,
+                        from_end: false,
+                        ..
+                    } => Some(format!("{base}[{offset}]")),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Solves the assertions in the current context of the solver and, if --dump_smt_queries
+    /// is given, writes the query to a file.
+    #[logfn_inputs(TRACE)]
+    pub fn solve_smt_query(&mut self) -> SmtResult {
+        let start = Instant::now();
+        let result = self.smt_solver.solve();
+        let time_taken = start.elapsed();
+        if result == SmtResult::Undefined && self.cv.options.statistics {
+            *self
+                .cv
+                .undefined_solver_results
+                .entry(self.function_name.to_string())
+                .or_default() += 1;
+        }
+        let Some(dir) = &self.cv.options.dump_smt_queries else {
+            return result;
+        };
+        let Some(script) = self.smt_solver.get_query_as_smtlib() else {
+            return result;
+        };
+        let (file, line, column) = self.get_source_location(self.current_span);
+        let location = QueryLocation {
+            function: &self.function_name,
+            file,
+            line,
+            column,
+        };
+        let dir = std::path::Path::new(dir);
+        if let Err(e) = smt_queries::dump_query(dir, &location, &script, &result, time_taken) {
+            let message = format!(
+                "[MIRAI] could not write solver query to {}: {e}",
+                dir.display()
+            );
+            self.cv.session.dcx().warn(message);
+        }
+        result
+    }
+
+    /// Like solve_expression, but writes the query to a file if --dump_smt_queries is given.
+    #[logfn_inputs(TRACE)]
+    fn solve_smt_expression(&mut self, expression: &ConfiguredExpression) -> SmtResult {
+        self.smt_solver.set_backtrack_position();
+        self.smt_solver.assert(expression);
+        let result = self.solve_smt_query();
+        self.smt_solver.backtrack();
+        result
+    }
+
     /// Copies/moves all paths rooted in source_path to corresponding paths rooted in target_path.
     /// source_path and/or target_path may be pattern paths and will be expanded as needed.
     #[logfn_inputs(TRACE)]
@@ -2014,7 +2397,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                     self.current_span,
                     "[MIRAI] The union is not fully initialized by this assignment",
                 );
-                self.emit_diagnostic(warning);
+                self.emit_diagnostic(warning, DiagnosticRule::MemorySafety);
                 break;
             }
             let (source_path, source_type) = &source_fields[source_field_index];
@@ -2114,7 +2497,7 @@ impl<'analysis, 'compilation, 'tcx> BodyVisitor<'analysis, 'compilation, 'tcx> {
                             self.current_span,
                             "[MIRAI] The union is not fully initialized by this assignment",
                         );
-                        self.emit_diagnostic(warning);
+                        self.emit_diagnostic(warning, DiagnosticRule::MemorySafety);
                         break;
                     }
                     let (source_path, source_type) = &source_fields[source_field_index];
@@ -5,22 +5,29 @@
 
 use core::fmt;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::Path;
 
+use petgraph::algo::{condensation, tarjan_scc};
 use petgraph::dot::{Config, Dot};
 use petgraph::graph::{DefaultIx, NodeIndex};
-use petgraph::visit::Bfs;
+use petgraph::visit::{Bfs, Reversed};
 use petgraph::{Direction, Graph};
 use regex::Regex;
 use serde::ser::{SerializeMap, Serializer};
 use serde::{Deserialize, Serialize};
 
 use mirai_annotations::*;
-use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
-use rustc_span::Span;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_hir::Safety;
+use rustc_middle::ty::{Ty, TyCtxt, TyKind};
+use rustc_session::config::CrateType;
+use rustc_span::{sym, Span};
+
+use crate::k_limits;
+use crate::utils;
 
 // An unique identifier for a Rust type string.
 type TypeId = u32;
@@ -40,6 +47,30 @@ pub enum CallGraphReduction {
     /// Only include nodes reachable from the given node.
     /// See `CallGraph::filter_reachable`.
     Slice(Box<str>),
+    /// Only include nodes from which the given node is reachable,
+    /// that is, all of its direct and indirect callers.
+    /// See `CallGraph::filter_reaching`.
+    ReverseSlice(Box<str>),
+    /// Only include nodes that are on a path from the first given node
+    /// to the second given node.
+    /// See `CallGraph::filter_chop`.
+    Chop(Box<str>, Box<str>),
+    /// Remove nodes whose names match the given regular expression.
+    /// If `fold` is true, the removed nodes are folded in the same way as by `Fold`.
+    /// See `CallGraph::filter_by_pattern`.
+    Exclude {
+        pattern: Box<str>,
+        #[serde(default)]
+        fold: bool,
+    },
+    /// Remove nodes whose names do not match the given regular expression.
+    /// If `fold` is true, the removed nodes are folded in the same way as by `Fold`.
+    /// See `CallGraph::filter_by_pattern`.
+    Include {
+        pattern: Box<str>,
+        #[serde(default)]
+        fold: bool,
+    },
     /// Remove nodes in the graph that belong to crates other than
     /// `CallGraphConfig.included_crates`. The outgoing edges of these
     /// removed node are connected to the node's parents.
@@ -66,6 +97,13 @@ pub struct DatalogConfig {
     /// Datalog output backend to use.
     /// Currently, Differential Datalog and Soufflé are supported.
     datalog_backend: DatalogBackend,
+    /// If true, the output also has relations with the names and crates of the nodes,
+    /// the source locations of the calls and which nodes are crate roots or unsafe functions.
+    #[serde(default)]
+    include_node_and_call_site_relations: bool,
+    /// If true, type relations are inferred from the Rust types of the edges.
+    #[serde(default)]
+    infer_type_relations: bool,
 }
 
 impl DatalogConfig {
@@ -74,15 +112,25 @@ impl DatalogConfig {
         type_map_output_path: Box<str>,
         type_relations_path: Option<Box<str>>,
         datalog_backend: DatalogBackend,
+        infer_type_relations: bool,
     ) -> DatalogConfig {
         DatalogConfig {
             ddlog_output_path,
             type_map_output_path,
             type_relations_path,
             datalog_backend,
+            include_node_and_call_site_relations: false,
+            infer_type_relations,
         }
     }
 
+    pub fn set_include_node_and_call_site_relations(
+        &mut self,
+        include_node_and_call_site_relations: bool,
+    ) {
+        self.include_node_and_call_site_relations = include_node_and_call_site_relations;
+    }
+
     pub fn get_ddlog_path(&self) -> &str {
         self.ddlog_output_path.as_ref()
     }
@@ -98,6 +146,14 @@ impl DatalogConfig {
     pub fn get_datalog_backend(&self) -> DatalogBackend {
         self.datalog_backend
     }
+
+    pub fn includes_node_and_call_site_relations(&self) -> bool {
+        self.include_node_and_call_site_relations
+    }
+
+    pub fn infers_type_relations(&self) -> bool {
+        self.infer_type_relations
+    }
 }
 
 /// Configuration options for call graph generation.
@@ -109,6 +165,23 @@ pub struct CallGraphConfig {
     /// Optionally specifies location for graph to be output in dot format
     /// (for Graphviz).
     dot_output_path: Option<Box<str>>,
+    /// Optionally specifies location for graph to be output in GraphML format.
+    graphml_output_path: Option<Box<str>>,
+    /// Optionally specifies location for graph to be output in the node-link JSON format
+    /// that is read by networkx.
+    json_graph_output_path: Option<Box<str>>,
+    /// Optionally specifies location for a report of the recursive groups of functions
+    /// (strongly connected components) of the graph, in JSON format.
+    scc_output_path: Option<Box<str>>,
+    /// Optionally specifies location of a node-link JSON file with the call graph of a whole
+    /// workspace. The graph of each crate is merged into the graph already in the file.
+    workspace_graph_output_path: Option<Box<str>>,
+    /// If true, each strongly connected component is shown as a single node in the dot output.
+    #[serde(default)]
+    dot_collapse_sccs: bool,
+    /// If true, the call site and dot outputs say how the callee of each call was resolved.
+    #[serde(default)]
+    include_call_resolution: bool,
     /// A list of call graph reductions to apply sequentially
     /// to the call graph.
     reductions: Vec<CallGraphReduction>,
@@ -132,6 +205,12 @@ impl CallGraphConfig {
         CallGraphConfig {
             call_sites_output_path,
             dot_output_path,
+            graphml_output_path: None,
+            json_graph_output_path: None,
+            scc_output_path: None,
+            workspace_graph_output_path: None,
+            dot_collapse_sccs: false,
+            include_call_resolution: false,
             reductions,
             included_crates,
             datalog_config,
@@ -139,6 +218,18 @@ impl CallGraphConfig {
         }
     }
 
+    pub fn set_scc_path(&mut self, scc_output_path: Option<Box<str>>) {
+        self.scc_output_path = scc_output_path;
+    }
+
+    pub fn set_dot_collapse_sccs(&mut self, dot_collapse_sccs: bool) {
+        self.dot_collapse_sccs = dot_collapse_sccs;
+    }
+
+    pub fn set_include_call_resolution(&mut self, include_call_resolution: bool) {
+        self.include_call_resolution = include_call_resolution;
+    }
+
     pub fn get_call_sites_path(&self) -> Option<&str> {
         self.call_sites_output_path.as_deref()
     }
@@ -147,6 +238,22 @@ impl CallGraphConfig {
         self.dot_output_path.as_deref()
     }
 
+    pub fn get_graphml_path(&self) -> Option<&str> {
+        self.graphml_output_path.as_deref()
+    }
+
+    pub fn get_json_graph_path(&self) -> Option<&str> {
+        self.json_graph_output_path.as_deref()
+    }
+
+    pub fn get_scc_path(&self) -> Option<&str> {
+        self.scc_output_path.as_deref()
+    }
+
+    pub fn get_workspace_graph_path(&self) -> Option<&str> {
+        self.workspace_graph_output_path.as_deref()
+    }
+
     pub fn get_ddlog_path(&self) -> Option<&str> {
         self.datalog_config
             .as_ref()
@@ -166,6 +273,33 @@ impl CallGraphConfig {
     }
 }
 
+/// How the callee of a call was determined by the analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallResolution {
+    /// The call names its callee directly.
+    Static,
+    /// The call is to a trait method and was resolved to the method of an impl.
+    TraitDispatch,
+    /// The callee is a function pointer or closure value that was resolved to a
+    /// constant function.
+    FunctionPointer,
+    /// The call is to a trait method that could not be resolved to an implementation,
+    /// for example because it is called on a trait object.
+    Virtual,
+}
+
+impl fmt::Display for CallResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallResolution::Static => write!(f, "static"),
+            CallResolution::TraitDispatch => write!(f, "trait_dispatch"),
+            CallResolution::FunctionPointer => write!(f, "function_pointer"),
+            CallResolution::Virtual => write!(f, "virtual"),
+        }
+    }
+}
+
 /// The type of a call graph node.
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum NodeType {
@@ -259,6 +393,15 @@ impl TypeRelation {
         }
     }
 
+    /// A relation of the given kind, with the types of an equivalence in lexicographic order.
+    fn new(kind: TypeRelationKind, t1: &str, t2: &str) -> TypeRelation {
+        match kind {
+            TypeRelationKind::Eq if t2 < t1 => TypeRelation::new_eq(t2.into(), t1.into()),
+            TypeRelationKind::Eq => TypeRelation::new_eq(t1.into(), t2.into()),
+            TypeRelationKind::Member => TypeRelation::new_member(t1.into(), t2.into()),
+        }
+    }
+
     fn new_member(t1: Box<str>, t2: Box<str>) -> TypeRelation {
         TypeRelation {
             kind: TypeRelationKind::Member,
@@ -328,12 +471,16 @@ pub struct CallGraph<'tcx> {
     /// (call_site, (caller, callee)). One entry per call that is reachable from
     /// an analysis root.
     call_sites: HashMap<rustc_span::Span, (DefId, DefId)>,
+    /// How the callee of each call site was resolved.
+    call_resolutions: HashMap<rustc_span::Span, CallResolution>,
     /// The graph structure capturing calls between nodes
     graph: Graph<CallGraphNode, CallGraphEdge>,
     /// A map from DefId to node information
     nodes: HashMap<DefId, NodeId>,
     /// A map from type string to an EdgeType instance
     edge_types: HashMap<Box<str>, EdgeType>,
+    /// The Rust types of the edge types that were added with `add_typed_edge`.
+    edge_tys: HashMap<Box<str>, Ty<'tcx>>,
     /// Dominance information
     dominance: HashMap<DefId, HashSet<DefId>>,
 }
@@ -349,9 +496,11 @@ impl<'tcx> CallGraph<'tcx> {
             tcx,
             non_local_defs: HashSet::new(),
             call_sites: HashMap::new(),
+            call_resolutions: HashMap::new(),
             graph: Graph::<CallGraphNode, CallGraphEdge>::new(),
             nodes: HashMap::<DefId, NodeId>::new(),
             edge_types: HashMap::<Box<str>, EdgeType>::new(),
+            edge_tys: HashMap::<Box<str>, Ty<'tcx>>::new(),
             dominance: HashMap::<DefId, HashSet<DefId>>::new(),
         }
     }
@@ -369,7 +518,28 @@ impl<'tcx> CallGraph<'tcx> {
     }
 
     pub fn needs_edges(&self) -> bool {
-        self.config.dot_output_path.is_some() || self.config.datalog_config.is_some()
+        self.config.dot_output_path.is_some()
+            || self.config.datalog_config.is_some()
+            || self.config.graphml_output_path.is_some()
+            || self.config.json_graph_output_path.is_some()
+            || self.config.scc_output_path.is_some()
+            || self.config.workspace_graph_output_path.is_some()
+    }
+
+    /// The call site output and the GraphML and JSON graph outputs, including the workspace
+    /// graph, include the source locations of calls, as can the Datalog output. The dot output
+    /// needs them to label edges with how the calls were resolved.
+    fn needs_call_sites(&self) -> bool {
+        self.config.call_sites_output_path.is_some()
+            || (self.config.include_call_resolution && self.config.dot_output_path.is_some())
+            || self.config.graphml_output_path.is_some()
+            || self.config.json_graph_output_path.is_some()
+            || self.config.workspace_graph_output_path.is_some()
+            || self
+                .config
+                .datalog_config
+                .as_ref()
+                .is_some_and(|config| config.include_node_and_call_site_relations)
     }
 
     /// Produce an updated call graph structure that preserves all the
@@ -381,8 +551,10 @@ impl<'tcx> CallGraph<'tcx> {
             graph,
             non_local_defs: self.non_local_defs.clone(),
             call_sites: self.call_sites.clone(),
+            call_resolutions: self.call_resolutions.clone(),
             nodes: self.nodes.clone(),
             edge_types: self.edge_types.clone(),
+            edge_tys: self.edge_tys.clone(),
             dominance: self.dominance.clone(),
         }
     }
@@ -426,8 +598,9 @@ impl<'tcx> CallGraph<'tcx> {
         }
     }
 
-    // Record a call site in the call graph. Only do so if call_sites_output_path is specified
-    // and omit any calls where the caller is known to be external to the crate being analyzed.
+    // Record a call site in the call graph. Only do so if an output that includes call sites is
+    // specified and omit any calls where the caller is known to be external to the crate being
+    // analyzed.
     //todo: to make this a precise as possible a callee should only be marked as external
     // if no Trait type is reachable from a parameter type.
     pub fn add_call_site(
@@ -435,13 +608,14 @@ impl<'tcx> CallGraph<'tcx> {
         loc: rustc_span::Span,
         caller: DefId,
         callee: DefId,
+        resolution: CallResolution,
         external_callee: bool,
     ) {
         if self.config.include_calls_in_summaries
-            || (self.config.call_sites_output_path.is_some()
-                && !self.non_local_defs.contains(&caller))
+            || (self.needs_call_sites() && !self.non_local_defs.contains(&caller))
         {
             self.call_sites.insert(loc, (caller, callee));
+            self.call_resolutions.insert(loc, resolution);
             if external_callee {
                 self.non_local_defs.insert(callee);
             }
@@ -479,6 +653,14 @@ impl<'tcx> CallGraph<'tcx> {
         }
     }
 
+    /// Add a new edge to the call graph, whose type is the given Rust type.
+    /// The Rust type is kept, so that type relations can be inferred from it.
+    pub fn add_typed_edge(&mut self, caller_id: DefId, callee_id: DefId, ty: Ty<'tcx>) {
+        let edge_type_str = ty.to_string().into_boxed_str();
+        self.edge_tys.entry(edge_type_str.clone()).or_insert(ty);
+        self.add_edge(caller_id, callee_id, edge_type_str);
+    }
+
     /// Find a node in the call graph given a `name` that may appear as
     /// a substring within the node's name. The first such node is returned, if any.
     fn get_node_by_name(&self, name: &str) -> Option<NodeId> {
@@ -552,27 +734,80 @@ impl<'tcx> CallGraph<'tcx> {
         reachable
     }
 
+    /// Returns the set of nodes from which the given end node is reachable,
+    /// including the end node itself. Unlike `reachable_from`, all crate roots
+    /// that can reach the end node are included.
+    fn reaching(&self, end_node: NodeId) -> HashSet<NodeId> {
+        let mut reaching = HashSet::<NodeId>::new();
+        let reversed = Reversed(&self.graph);
+        let mut bfs = Bfs::new(reversed, end_node);
+        while let Some(node_id) = bfs.next(reversed) {
+            reaching.insert(node_id);
+        }
+        reaching
+    }
+
+    /// Find the node identified by `name`, panicking if there is no such node.
+    fn expect_node_by_name(&self, name: &str) -> NodeId {
+        match self.get_node_by_name(name) {
+            Some(node_id) => node_id,
+            None => panic!("Failed to filter graph; could not find node: {name}"),
+        }
+    }
+
+    /// Produce a graph that only includes the given nodes and the edges between them.
+    fn retain_nodes(&self, retained: &HashSet<NodeId>) -> CallGraph<'tcx> {
+        let graph = self.graph.filter_map(
+            |node_id, node| {
+                if retained.contains(&node_id) {
+                    Some(node.to_owned())
+                } else {
+                    None
+                }
+            },
+            |_, edge| Some(edge.to_owned()),
+        );
+        self.update(graph)
+    }
+
     /// Filter out all nodes from the graph that are not reachable
     /// via start node identifiable by `name`.
     fn filter_reachable(&self, name: &str) -> CallGraph<'tcx> {
         if let Some(start_node) = self.get_node_by_name(name) {
-            let reachable = self.reachable_from(start_node);
-            let graph = self.graph.filter_map(
-                |node_id, node| {
-                    if reachable.contains(&node_id) {
-                        Some(node.to_owned())
-                    } else {
-                        None
-                    }
-                },
-                |_, edge| Some(edge.to_owned()),
-            );
-            self.update(graph)
+            self.retain_nodes(&self.reachable_from(start_node))
         } else {
             panic!("Failed to filter graph; could not find start node: {name}");
         }
     }
 
+    /// Filter out all nodes from the graph that cannot reach
+    /// the end node identifiable by `name`.
+    fn filter_reaching(&self, name: &str) -> CallGraph<'tcx> {
+        let end_node = self.expect_node_by_name(name);
+        self.retain_nodes(&self.reaching(end_node))
+    }
+
+    /// Filter out all nodes from the graph that are not on a path
+    /// from the node identifiable by `from` to the node identifiable by `to`.
+    ///
+    /// These are the nodes that are reachable from `from` and that can also reach `to`.
+    /// In contrast to `Slice`, every crate root on such a path is kept.
+    fn filter_chop(&self, from: &str, to: &str) -> CallGraph<'tcx> {
+        let start_node = self.expect_node_by_name(from);
+        let end_node = self.expect_node_by_name(to);
+        let mut reachable = HashSet::<NodeId>::new();
+        let mut bfs = Bfs::new(&self.graph, start_node);
+        while let Some(node_id) = bfs.next(&self.graph) {
+            reachable.insert(node_id);
+        }
+        let on_path = self
+            .reaching(end_node)
+            .intersection(&reachable)
+            .copied()
+            .collect::<HashSet<NodeId>>();
+        self.retain_nodes(&on_path)
+    }
+
     /// Helper function for folding excluded nodes.
     ///
     /// Computes the set of reachable nodes reachable
@@ -629,21 +864,26 @@ impl<'tcx> CallGraph<'tcx> {
     ///
     /// An excluded node satisfies `node.is_excluded()` (it is not one of crates specified
     /// by CallGraphConfig::included_crates).
-    /// An excluded edge is an edge with at least one excluded endpoint.
+    fn fold_excluded(&self) -> CallGraph<'tcx> {
+        let included_crates = self
+            .config
+            .included_crates
+            .iter()
+            .map(|v| &**v)
+            .collect::<Vec<&str>>();
+        self.fold_nodes(|node| node.is_excluded(&included_crates))
+    }
+
+    /// Fold the graph to remove the nodes that satisfy `is_excluded`, along with
+    /// excluded edges. An excluded edge is an edge with at least one excluded endpoint.
     ///
     /// The outgoing edges of an excluded node are joined to the node's non-excluded parents.
-    fn fold_excluded(&self) -> CallGraph<'tcx> {
+    fn fold_nodes(&self, is_excluded: impl Fn(&CallGraphNode) -> bool) -> CallGraph<'tcx> {
         let mut excluded = MidpointExcludedMap::new();
         // 1. Find all excluded nodes
         let mut graph = self.graph.filter_map(
             |node_id, node| {
-                let included_crates = self
-                    .config
-                    .included_crates
-                    .iter()
-                    .map(|v| &**v)
-                    .collect::<Vec<&str>>();
-                if node.is_excluded(&included_crates) {
+                if is_excluded(node) {
                     excluded.insert(
                         node_id,
                         (HashSet::<HalfRawEdge>::new(), HashSet::<HalfRawEdge>::new()),
@@ -689,6 +929,30 @@ impl<'tcx> CallGraph<'tcx> {
         self.update(graph)
     }
 
+    /// Remove the nodes whose names match `pattern` (if `keep_matches` is false) or
+    /// do not match `pattern` (if `keep_matches` is true). The pattern is a regular
+    /// expression that must match the entire name of a node.
+    ///
+    /// If `fold` is true, paths through the removed nodes are preserved as with
+    /// `fold_excluded`; otherwise the edges of the removed nodes are removed as well.
+    fn filter_by_pattern(&self, pattern: &str, keep_matches: bool, fold: bool) -> CallGraph<'tcx> {
+        let regex = match Regex::new(&format!("^(?:{pattern})$")) {
+            Ok(regex) => regex,
+            Err(e) => panic!("Failed to filter graph; invalid pattern {pattern}: {e}"),
+        };
+        let is_excluded = |node: &CallGraphNode| regex.is_match(&node.name) != keep_matches;
+        if fold {
+            self.fold_nodes(is_excluded)
+        } else {
+            let retained = self
+                .graph
+                .node_indices()
+                .filter(|node_id| !is_excluded(&self.graph[*node_id]))
+                .collect::<HashSet<NodeId>>();
+            self.retain_nodes(&retained)
+        }
+    }
+
     /// Filter out nodes from that graph that have no incoming
     /// or outgoing edges (unconnected from the rest of the graph).
     fn filter_no_edges(&self) -> CallGraph<'tcx> {
@@ -733,6 +997,14 @@ impl<'tcx> CallGraph<'tcx> {
             .iter()
             .fold(call_graph, |graph, reduction| match reduction {
                 CallGraphReduction::Slice(crate_name) => graph.filter_reachable(crate_name),
+                CallGraphReduction::ReverseSlice(name) => graph.filter_reaching(name),
+                CallGraphReduction::Chop(from, to) => graph.filter_chop(from, to),
+                CallGraphReduction::Exclude { pattern, fold } => {
+                    graph.filter_by_pattern(pattern, false, *fold)
+                }
+                CallGraphReduction::Include { pattern, fold } => {
+                    graph.filter_by_pattern(pattern, true, *fold)
+                }
                 CallGraphReduction::Fold => graph.fold_excluded(),
                 CallGraphReduction::Deduplicate => graph.deduplicate_edges(),
                 CallGraphReduction::Clean => graph.filter_no_edges(),
@@ -892,6 +1164,7 @@ impl<'tcx> CallGraph<'tcx> {
         &self,
         type_map: &mut HashMap<TypeId, Box<str>>,
         type_relations_path: Option<&Path>,
+        infer_type_relations: bool,
     ) -> HashSet<TypeRelation> {
         let type_to_index: HashMap<Box<str>, TypeId> = type_map
             .iter()
@@ -946,21 +1219,131 @@ impl<'tcx> CallGraph<'tcx> {
                 }
             }
         }
+        if infer_type_relations {
+            self.infer_type_relations(type_map, &mut type_relations);
+        }
         let eq_map = self.get_input_equivalences(&type_relations);
         self.derive_all_relations(type_map, &eq_map, &mut type_relations);
         type_relations
     }
 
+    /// Infer type relations from the Rust types of the edges: a reference, `Box`, `Rc` or `Arc`
+    /// is equivalent to the type it points to, the fields of a struct and the payloads of the
+    /// variants of an enum are members of it and the types that implement a trait are members
+    /// of its trait object type. The types that these relations refer to are added to the type
+    /// map and are related to their own components in turn, up to a limited depth.
+    fn infer_type_relations(
+        &self,
+        type_map: &mut HashMap<TypeId, Box<str>>,
+        relations: &mut HashSet<TypeRelation>,
+    ) {
+        let mut type_to_index: HashMap<Box<str>, TypeId> = type_map
+            .iter()
+            .map(|(type_id, type_str)| (type_str.to_owned(), *type_id))
+            .collect();
+        let mut max_id: u32 = *type_map.keys().max().unwrap_or(&0);
+        let mut edge_types: Vec<(TypeId, Ty<'tcx>)> = type_map
+            .iter()
+            .filter_map(|(type_id, type_str)| self.edge_tys.get(type_str).map(|ty| (*type_id, *ty)))
+            .collect();
+        edge_types.sort_by_key(|(type_id, _)| *type_id);
+        let mut to_visit: VecDeque<(Ty<'tcx>, usize)> =
+            edge_types.into_iter().map(|(_, ty)| (ty, 0)).collect();
+        // The visited types, in the order in which they were visited, for determinism.
+        let mut visited = Vec::<Ty<'tcx>>::new();
+        let mut visited_set = HashSet::<Ty<'tcx>>::new();
+        while let Some((ty, depth)) = to_visit.pop_front() {
+            if !visited_set.insert(ty) {
+                continue;
+            }
+            visited.push(ty);
+            let type_str: Box<str> = ty.to_string().into_boxed_str();
+            for (kind, component) in self.type_components(ty) {
+                let component_str: Box<str> = component.to_string().into_boxed_str();
+                if component_str == type_str {
+                    continue;
+                }
+                relations.insert(TypeRelation::new(kind, &type_str, &component_str));
+                if !type_to_index.contains_key(&component_str) {
+                    max_id += 1;
+                    type_map.insert(max_id, component_str.clone());
+                    type_to_index.insert(component_str, max_id);
+                }
+                if depth + 1 < k_limits::MAX_INFERRED_TYPE_RELATION_DEPTH {
+                    to_visit.push_back((component, depth + 1));
+                }
+            }
+        }
+        for trait_object_ty in visited.iter() {
+            let TyKind::Dynamic(predicates, ..) = trait_object_ty.kind() else {
+                continue;
+            };
+            let Some(trait_def_id) = predicates.principal_def_id() else {
+                continue;
+            };
+            let trait_object_str = trait_object_ty.to_string();
+            for ty in visited.iter() {
+                if (matches!(ty.kind(), TyKind::Adt(..)) || ty.is_primitive())
+                    && self.implements_trait(*ty, trait_def_id)
+                {
+                    relations.insert(TypeRelation::new_member(
+                        trait_object_str.clone().into_boxed_str(),
+                        ty.to_string().into_boxed_str(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Returns the types that the given type is directly related to, along with the kinds
+    /// of the relations. Only the fields of types that are not defined by the standard library
+    /// are considered, since those of standard library types are implementation details.
+    fn type_components(&self, ty: Ty<'tcx>) -> Vec<(TypeRelationKind, Ty<'tcx>)> {
+        let tcx = self.tcx;
+        match ty.kind() {
+            TyKind::Ref(_, pointee, _) => vec![(TypeRelationKind::Eq, *pointee)],
+            TyKind::Adt(def, args)
+                if ty.is_box()
+                    || matches!(tcx.get_diagnostic_name(def.did()), Some(sym::Rc | sym::Arc)) =>
+            {
+                vec![(TypeRelationKind::Eq, args.type_at(0))]
+            }
+            TyKind::Adt(def, args)
+                if (def.is_struct() || def.is_enum())
+                    && !matches!(
+                        tcx.crate_name(def.did().krate),
+                        sym::std | sym::core | sym::alloc
+                    ) =>
+            {
+                def.all_fields()
+                    .map(|field| (TypeRelationKind::Member, field.ty(tcx, args)))
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// True if the trait has an implementation for the type. Generic implementations are
+    /// matched by the type definition they are for, ignoring the type arguments, and blanket
+    /// implementations are ignored.
+    fn implements_trait(&self, ty: Ty<'tcx>, trait_def_id: DefId) -> bool {
+        self.tcx.all_impls(trait_def_id).any(|impl_def_id| {
+            let self_ty = self.tcx.type_of(impl_def_id).instantiate_identity();
+            match (self_ty.kind(), ty.kind()) {
+                (TyKind::Adt(def1, _), TyKind::Adt(def2, _)) => def1.did() == def2.did(),
+                _ => self_ty == ty,
+            }
+        })
+    }
+
     /// Convert the call graph to a Datalog representation.
     ///
     /// Properties of the graph are converted into input relations.
-    fn to_datalog(
-        &self,
-        backend: DatalogBackend,
-        ddlog_path: &Path,
-        type_map_path: &Path,
-        type_relations_path: Option<&Path>,
-    ) {
+    fn to_datalog(&self, datalog_config: &DatalogConfig) {
+        let ddlog_path = Path::new(datalog_config.get_ddlog_path());
+        let type_map_path = Path::new(datalog_config.get_type_map_path());
+        let include_node_and_call_site_relations =
+            datalog_config.includes_node_and_call_site_relations();
         let mut ctr: u32 = 0;
         let mut used_types = HashSet::<TypeId>::new();
         let mut output = DatalogOutput::new();
@@ -980,7 +1363,28 @@ impl<'tcx> CallGraph<'tcx> {
             },
             |_, _| (),
         );
-        // Output edge and edge type relations
+        // Output node name, crate, crate root and unsafe function relations
+        if include_node_and_call_site_relations {
+            for node_id in self.graph.node_indices() {
+                let node = &self.graph[node_id];
+                let id = node_id.index() as u32;
+                output.add_relation(DatalogRelation::new_node_name(id, &node.name));
+                let crate_name = self.tcx.crate_name(node.defid.krate);
+                output.add_relation(DatalogRelation::new_node_crate(id, crate_name.as_str()));
+                if node.is_croot() {
+                    output.add_relation(DatalogRelation::new_is_crate_root(id));
+                }
+                if self.is_unsafe_function(node.defid) {
+                    output.add_relation(DatalogRelation::new_is_unsafe(id));
+                }
+            }
+        }
+        // Output edge, edge type and call site relations
+        let calls = if include_node_and_call_site_relations {
+            self.call_sites_by_endpoints()
+        } else {
+            HashMap::new()
+        };
         self.graph.map(
             |_, _| (),
             |edge_id, edge| {
@@ -992,6 +1396,10 @@ impl<'tcx> CallGraph<'tcx> {
                     ));
                     output.add_relation(DatalogRelation::new_edge_type(ctr, edge.type_id));
                     used_types.insert(edge.type_id);
+                    let endpoints = (self.graph[start_id].defid, self.graph[end_id].defid);
+                    for call_site in calls.get(&endpoints).into_iter().flatten() {
+                        output.add_relation(DatalogRelation::new_call_site(ctr, call_site));
+                    }
                     ctr += 1;
                 }
             },
@@ -1003,7 +1411,11 @@ impl<'tcx> CallGraph<'tcx> {
                 index_to_type.insert(edge_type.id, edge_type.name.to_owned());
             }
         }
-        let type_relations = self.gather_type_relations(&mut index_to_type, type_relations_path);
+        let type_relations = self.gather_type_relations(
+            &mut index_to_type,
+            datalog_config.get_type_relations_path().map(Path::new),
+            datalog_config.infers_type_relations(),
+        );
         let mut type_to_index = HashMap::<Box<str>, TypeId>::new();
         for (type_id, type_str) in index_to_type.iter() {
             type_to_index.insert(type_str.to_owned(), *type_id);
@@ -1024,7 +1436,7 @@ impl<'tcx> CallGraph<'tcx> {
         }
         // Output the Datalog operations in the format of the configured
         // Datalog backend
-        let output_result = match backend {
+        let output_result = match datalog_config.get_datalog_backend() {
             DatalogBackend::DifferentialDatalog => output.to_differential_datalog(ddlog_path),
             DatalogBackend::Souffle => output.to_souffle(ddlog_path),
         };
@@ -1043,19 +1455,116 @@ impl<'tcx> CallGraph<'tcx> {
         };
     }
 
+    /// True if the given function is declared as unsafe.
+    fn is_unsafe_function(&self, defid: DefId) -> bool {
+        matches!(self.tcx.def_kind(defid), DefKind::Fn | DefKind::AssocFn)
+            && self.tcx.fn_sig(defid).skip_binder().safety() == Safety::Unsafe
+    }
+
     /// Produce a dot file representation of the call graph
     /// for displaying with Graphviz.
+    ///
+    /// If `include_call_resolution` is configured, every edge is labeled with the ways
+    /// in which the calls it represents were resolved.
     fn to_dot(&self, dot_path: &Path) {
-        let output = format!(
-            "{:?}",
-            Dot::with_config(&self.shortened_node_names(), &[Config::EdgeNoLabel])
-        );
+        let output = if self.config.include_call_resolution {
+            let resolutions = self.resolutions_by_endpoints();
+            let labeled = self.graph.filter_map(
+                |_, node| Some(&*node.name),
+                |edge_id, _| {
+                    let (start_id, end_id) = self.graph.edge_endpoints(edge_id)?;
+                    let caller = self.graph.node_weight(start_id)?.defid;
+                    let callee = self.graph.node_weight(end_id)?.defid;
+                    Some(
+                        resolutions
+                            .get(&(caller, callee))
+                            .map(|kinds| {
+                                kinds
+                                    .iter()
+                                    .map(|kind| kind.to_string())
+                                    .collect::<Vec<String>>()
+                                    .join(", ")
+                            })
+                            .unwrap_or_default(),
+                    )
+                },
+            );
+            self.dot_string(labeled, &[])
+        } else {
+            self.dot_string(self.shortened_node_names(), &[Config::EdgeNoLabel])
+        };
         match fs::write(dot_path, output) {
             Ok(_) => (),
             Err(e) => panic!("Failed to write dot file output: {e:?}"),
         };
     }
 
+    /// Render a graph whose nodes are labeled with function names in dot format.
+    ///
+    /// If `dot_collapse_sccs` is configured, every strongly connected component is shown
+    /// as a single node, labeled with the names of its members, and the calls within
+    /// a component are omitted.
+    fn dot_string<E: fmt::Debug + Clone>(
+        &self,
+        graph: Graph<&str, E>,
+        config: &[Config],
+    ) -> String {
+        if self.config.dot_collapse_sccs {
+            let condensed = condensation(graph, true).map(
+                |_, names| {
+                    let mut names = names.clone();
+                    names.sort_unstable();
+                    names.join(", ")
+                },
+                |_, edge| edge.clone(),
+            );
+            format!("{:?}", Dot::with_config(&condensed, config))
+        } else {
+            format!("{:?}", Dot::with_config(&graph, config))
+        }
+    }
+
+    /// Returns the ways in which the calls from a caller to a callee were resolved.
+    fn resolutions_by_endpoints(&self) -> HashMap<(DefId, DefId), BTreeSet<CallResolution>> {
+        let mut resolutions = HashMap::<(DefId, DefId), BTreeSet<CallResolution>>::new();
+        for (loc, endpoints) in self.call_sites.iter() {
+            if let Some(resolution) = self.call_resolutions.get(loc) {
+                resolutions
+                    .entry(*endpoints)
+                    .or_default()
+                    .insert(*resolution);
+            }
+        }
+        resolutions
+    }
+
+    /// Returns the source locations of the calls from each caller to each callee, in source order.
+    fn call_sites_by_endpoints(&self) -> HashMap<(DefId, DefId), Vec<ExportedCallSite>> {
+        let source_map = self.tcx.sess.source_map();
+        let mut sites: Vec<(&Span, &(DefId, DefId))> = self.call_sites.iter().collect();
+        sites.sort_by(|a, b| a.0.cmp(b.0));
+        let mut calls = HashMap::<(DefId, DefId), Vec<ExportedCallSite>>::new();
+        for (loc, (caller, callee)) in sites {
+            if let Ok(line_and_file) = source_map.span_to_lines(loc.source_callsite()) {
+                let line = &line_and_file.lines[0];
+                calls
+                    .entry((*caller, *callee))
+                    .or_default()
+                    .push(ExportedCallSite {
+                        file: display_file_name(&line_and_file.file.name),
+                        line: line.line_index + 1,
+                        column: line.start_col.0 + 1,
+                        resolution: self
+                            .call_resolutions
+                            .get(loc)
+                            .copied()
+                            .unwrap_or(CallResolution::Static),
+                    });
+            }
+        }
+        calls
+    }
+
     fn to_call_sites(&self, call_site_path: &Path) {
         let call_site_info = CallSiteOutput::new(self);
         match serde_json::to_string_pretty(&call_site_info)
@@ -1068,26 +1577,127 @@ impl<'tcx> CallGraph<'tcx> {
         };
     }
 
+    /// Produce a JSON report of the groups of functions that are (mutually) recursive.
+    /// These are the strongly connected components of the graph that have more than one
+    /// node, along with the single nodes that call themselves.
+    fn to_scc_report(&self, scc_path: &Path) {
+        let mut components = tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.graph.find_edge(component[0], component[0]).is_some()
+            })
+            .map(|component| {
+                let mut members = component
+                    .iter()
+                    .filter_map(|node_id| self.graph.node_weight(*node_id))
+                    .map(|node| node.name.to_string())
+                    .collect::<Vec<String>>();
+                members.sort();
+                let mut node_ids = component
+                    .iter()
+                    .map(|node_id| node_id.index())
+                    .collect::<Vec<usize>>();
+                node_ids.sort_unstable();
+                StronglyConnectedComponent {
+                    size: component.len(),
+                    has_croot: component
+                        .iter()
+                        .filter_map(|node_id| self.graph.node_weight(*node_id))
+                        .any(|node| node.is_croot()),
+                    members,
+                    node_ids,
+                }
+            })
+            .collect::<Vec<StronglyConnectedComponent>>();
+        components.sort_by(|c1, c2| {
+            c2.size
+                .cmp(&c1.size)
+                .then_with(|| c1.members.cmp(&c2.members))
+        });
+        match serde_json::to_string_pretty(&SccOutput { components })
+            .map_err(|e| e.to_string())
+            .and_then(|scc_output| fs::write(scc_path, scc_output).map_err(|e| e.to_string()))
+        {
+            Ok(_) => (),
+            Err(e) => panic!("Failed to write SCC output: {e}"),
+        };
+    }
+
+    /// Produce a GraphML representation of the call graph, for tools such as Gephi.
+    fn to_graphml(&self, graphml_path: &Path) {
+        let output = GraphExport::new(self).to_graphml();
+        match fs::write(graphml_path, output) {
+            Ok(_) => (),
+            Err(e) => panic!("Failed to write GraphML output: {e:?}"),
+        };
+    }
+
+    /// Produce a node-link JSON representation of the call graph, as read by
+    /// `networkx.node_link_graph`.
+    fn to_json_graph(&self, json_graph_path: &Path) {
+        match serde_json::to_string_pretty(&GraphExport::new(self))
+            .map_err(|e| e.to_string())
+            .and_then(|json_graph_output| {
+                fs::write(json_graph_path, json_graph_output).map_err(|e| e.to_string())
+            }) {
+            Ok(_) => (),
+            Err(e) => panic!("Failed to write JSON graph output: {e}"),
+        };
+    }
+
+    /// Merge the call graph into the workspace graph at the given path, replacing what was
+    /// merged into it by a previous analysis of the same crate.
+    fn to_workspace_graph(&self, workspace_graph_path: &Path) {
+        // A package can have a library and a binary with the same name.
+        let mut crate_name = self.tcx.crate_name(LOCAL_CRATE).to_string();
+        if self.tcx.crate_types().contains(&CrateType::Executable) {
+            crate_name.push_str(" (bin)");
+        }
+        let crate_graph = GraphExport::new(self);
+        let result = utils::update_locked_file(workspace_graph_path, |json| {
+            let mut workspace_graph = match json {
+                Some(json) => {
+                    serde_json::from_str::<WorkspaceGraph>(json).map_err(|e| e.to_string())?
+                }
+                None => WorkspaceGraph::default(),
+            };
+            workspace_graph.merge(&crate_name, &crate_graph);
+            serde_json::to_string_pretty(&workspace_graph).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(_) => (),
+            Err(e) => panic!("Failed to update workspace graph: {e}"),
+        };
+    }
+
     /// Top-level output function.
     ///
     /// First applies a set of reductions to the call graph.
-    /// Then produces Datalog and / or dot file output of the call graph.
+    /// Then produces Datalog, dot, SCC, GraphML, JSON graph, workspace graph and / or call site
+    /// output of the call graph.
     pub fn output(&self) {
         let call_graph = self.reduce_graph(self.clone(), &self.config.reductions);
         if let Some(datalog_config) = &self.config.datalog_config {
-            call_graph.to_datalog(
-                datalog_config.get_datalog_backend(),
-                Path::new(datalog_config.get_ddlog_path()),
-                Path::new(datalog_config.get_type_map_path()),
-                datalog_config.get_type_relations_path().map(Path::new),
-            );
+            call_graph.to_datalog(datalog_config);
         }
         if let Some(dot_path) = &self.config.dot_output_path {
             call_graph.to_dot(Path::new(dot_path.as_ref()));
         }
+        if let Some(scc_path) = &self.config.scc_output_path {
+            call_graph.to_scc_report(Path::new(scc_path.as_ref()));
+        }
+        if let Some(graphml_path) = &self.config.graphml_output_path {
+            call_graph.to_graphml(Path::new(graphml_path.as_ref()));
+        }
+        if let Some(json_graph_path) = &self.config.json_graph_output_path {
+            call_graph.to_json_graph(Path::new(json_graph_path.as_ref()));
+        }
         if let Some(call_path) = &self.config.call_sites_output_path {
             call_graph.to_call_sites(Path::new(call_path.as_ref()));
         }
+        if let Some(workspace_graph_path) = &self.config.workspace_graph_output_path {
+            call_graph.to_workspace_graph(Path::new(workspace_graph_path.as_ref()));
+        }
     }
 
     pub fn get_calls_for_def_ids(&self) -> HashMap<DefId, Vec<(Span, DefId)>> {
@@ -1148,6 +1758,33 @@ enum RelationType {
     EqType,
     /// `Member(type_id1, type_id2)`: The type `type_id2` is a member of `type_id1`.
     Member,
+    /// `NodeName(id, name)`: The node is the function with the given qualified name.
+    NodeName,
+    /// `NodeCrate(id, crate)`: The function of the node is defined in the given crate.
+    NodeCrate,
+    /// `CallSite(edge_id, file, line, column)`: A call that the edge represents is at the
+    /// given source location.
+    CallSite,
+    /// `IsCrateRoot(id)`: The node is a crate root.
+    IsCrateRoot,
+    /// `IsUnsafe(id)`: The function of the node is declared as unsafe.
+    IsUnsafe,
+}
+
+impl RelationType {
+    /// All relation types, in the order in which the Soufflé fact files are written.
+    const ALL: [RelationType; 10] = [
+        RelationType::Dom,
+        RelationType::Edge,
+        RelationType::EdgeType,
+        RelationType::EqType,
+        RelationType::Member,
+        RelationType::NodeName,
+        RelationType::NodeCrate,
+        RelationType::CallSite,
+        RelationType::IsCrateRoot,
+        RelationType::IsUnsafe,
+    ];
 }
 
 impl fmt::Display for RelationType {
@@ -1158,52 +1795,135 @@ impl fmt::Display for RelationType {
             RelationType::EdgeType => write!(f, "EdgeType"),
             RelationType::EqType => write!(f, "EqType"),
             RelationType::Member => write!(f, "Member"),
+            RelationType::NodeName => write!(f, "NodeName"),
+            RelationType::NodeCrate => write!(f, "NodeCrate"),
+            RelationType::CallSite => write!(f, "CallSite"),
+            RelationType::IsCrateRoot => write!(f, "IsCrateRoot"),
+            RelationType::IsUnsafe => write!(f, "IsUnsafe"),
         }
     }
 }
 
+/// An operand of a Datalog relation: an index or a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum DatalogOperand {
+    Number(u32),
+    Symbol(Box<str>),
+}
+
+impl DatalogOperand {
+    /// Format the operand for Differential Datalog, where strings are quoted as in Rust.
+    fn to_differential_datalog(&self) -> String {
+        match self {
+            DatalogOperand::Number(n) => n.to_string(),
+            DatalogOperand::Symbol(s) => format!("{s:?}"),
+        }
+    }
+
+    /// Format the operand for Soufflé, where strings are quoted as in CSV (RFC 4180) files,
+    /// since they can contain commas.
+    fn to_souffle(&self) -> String {
+        match self {
+            DatalogOperand::Number(n) => n.to_string(),
+            DatalogOperand::Symbol(s) => format!("\"{}\"", s.replace('"', "\"\"")),
+        }
+    }
+}
+
+impl From<u32> for DatalogOperand {
+    fn from(n: u32) -> DatalogOperand {
+        DatalogOperand::Number(n)
+    }
+}
+
+impl From<&str> for DatalogOperand {
+    fn from(s: &str) -> DatalogOperand {
+        DatalogOperand::Symbol(s.into())
+    }
+}
+
 /// Represents an atomic Datalog relation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct DatalogRelation {
     /// A relation has a name.
     name: RelationType,
     /// As well as one or more operands.
-    operands: Vec<u32>,
+    operands: Vec<DatalogOperand>,
 }
 
 impl DatalogRelation {
     pub fn new_dom(n1: u32, n2: u32) -> DatalogRelation {
         DatalogRelation {
             name: RelationType::Dom,
-            operands: vec![n1, n2],
+            operands: vec![n1.into(), n2.into()],
         }
     }
 
     pub fn new_edge(id: u32, n1: u32, n2: u32) -> DatalogRelation {
         DatalogRelation {
             name: RelationType::Edge,
-            operands: vec![id, n1, n2],
+            operands: vec![id.into(), n1.into(), n2.into()],
         }
     }
 
     pub fn new_edge_type(id: u32, t: u32) -> DatalogRelation {
         DatalogRelation {
             name: RelationType::EdgeType,
-            operands: vec![id, t],
+            operands: vec![id.into(), t.into()],
         }
     }
 
     pub fn new_eq_type(t1: u32, t2: u32) -> DatalogRelation {
         DatalogRelation {
             name: RelationType::EqType,
-            operands: vec![t1, t2],
+            operands: vec![t1.into(), t2.into()],
         }
     }
 
     pub fn new_member(t1: u32, t2: u32) -> DatalogRelation {
         DatalogRelation {
             name: RelationType::Member,
-            operands: vec![t1, t2],
+            operands: vec![t1.into(), t2.into()],
+        }
+    }
+
+    pub fn new_node_name(id: u32, name: &str) -> DatalogRelation {
+        DatalogRelation {
+            name: RelationType::NodeName,
+            operands: vec![id.into(), name.into()],
+        }
+    }
+
+    pub fn new_node_crate(id: u32, crate_name: &str) -> DatalogRelation {
+        DatalogRelation {
+            name: RelationType::NodeCrate,
+            operands: vec![id.into(), crate_name.into()],
+        }
+    }
+
+    pub fn new_call_site(id: u32, call_site: &ExportedCallSite) -> DatalogRelation {
+        DatalogRelation {
+            name: RelationType::CallSite,
+            operands: vec![
+                id.into(),
+                call_site.file.as_str().into(),
+                (call_site.line as u32).into(),
+                (call_site.column as u32).into(),
+            ],
+        }
+    }
+
+    pub fn new_is_crate_root(id: u32) -> DatalogRelation {
+        DatalogRelation {
+            name: RelationType::IsCrateRoot,
+            operands: vec![id.into()],
+        }
+    }
+
+    pub fn new_is_unsafe(id: u32) -> DatalogRelation {
+        DatalogRelation {
+            name: RelationType::IsUnsafe,
+            operands: vec![id.into()],
         }
     }
 
@@ -1214,7 +1934,7 @@ impl DatalogRelation {
             self.name,
             self.operands
                 .iter()
-                .map(|x| x.to_string())
+                .map(|x| x.to_differential_datalog())
                 .collect::<Vec<String>>()
                 .join(",")
         )
@@ -1224,7 +1944,7 @@ impl DatalogRelation {
     fn to_souffle(&self) -> String {
         self.operands
             .iter()
-            .map(|x| x.to_string())
+            .map(|x| x.to_souffle())
             .collect::<Vec<String>>()
             .join(",")
     }
@@ -1290,46 +2010,17 @@ impl DatalogOutput {
     /// Output the Datalog relations to a set of files (one file per relation type)
     /// in the format expected by Soufflé Datalog
     pub fn to_souffle(&self, path: &Path) -> std::io::Result<()> {
-        fs::write(
-            path.join("Dom.facts"),
-            self.output_relation_set(
-                &self.relations,
-                Some(RelationType::Dom),
-                DatalogBackend::Souffle,
-            ),
-        )?;
-        fs::write(
-            path.join("Edge.facts"),
-            self.output_relation_set(
-                &self.relations,
-                Some(RelationType::Edge),
-                DatalogBackend::Souffle,
-            ),
-        )?;
-        fs::write(
-            path.join("EdgeType.facts"),
-            self.output_relation_set(
-                &self.relations,
-                Some(RelationType::EdgeType),
-                DatalogBackend::Souffle,
-            ),
-        )?;
-        fs::write(
-            path.join("EqType.facts"),
-            self.output_relation_set(
-                &self.relations,
-                Some(RelationType::EqType),
-                DatalogBackend::Souffle,
-            ),
-        )?;
-        fs::write(
-            path.join("Member.facts"),
-            self.output_relation_set(
-                &self.relations,
-                Some(RelationType::Member),
-                DatalogBackend::Souffle,
-            ),
-        )
+        for relation_type in RelationType::ALL {
+            fs::write(
+                path.join(format!("{relation_type}.facts")),
+                self.output_relation_set(
+                    &self.relations,
+                    Some(relation_type),
+                    DatalogBackend::Souffle,
+                ),
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -1349,6 +2040,10 @@ struct CallSiteOutput {
     /// File index, line, column, caller index, callee index.
     /// Line and column numbers are 1 based.
     calls: Vec<(usize, usize, usize, usize, usize)>,
+    /// How the callee of each entry of calls was resolved, in the same order.
+    /// Only present if `CallGraphConfig.include_call_resolution` is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolutions: Option<Vec<CallResolution>>,
 }
 
 /// Metadata for each callable that is mentioned in the calls collection.
@@ -1376,6 +2071,7 @@ impl CallSiteOutput {
         let mut callables = vec![];
         let mut callable_index = HashMap::<DefId, usize>::new();
         let mut calls = vec![];
+        let mut resolutions = vec![];
         let mut sites: Vec<(&rustc_span::Span, &(DefId, DefId))> =
             call_graph.call_sites.iter().collect();
         sites.sort_by(|a, b| a.0.cmp(b.0));
@@ -1408,12 +2104,24 @@ impl CallSiteOutput {
                     caller_index,
                     callee_index,
                 ));
+                resolutions.push(
+                    call_graph
+                        .call_resolutions
+                        .get(*loc)
+                        .copied()
+                        .unwrap_or(CallResolution::Static),
+                );
             }
         }
         CallSiteOutput {
             files,
             callables,
             calls,
+            resolutions: if call_graph.config.include_call_resolution {
+                Some(resolutions)
+            } else {
+                None
+            },
         }
     }
 
@@ -1427,13 +2135,7 @@ impl CallSiteOutput {
             Entry::Vacant(v) => {
                 let index = files.len();
                 v.insert(index);
-                let mut file_name = None;
-                if let rustc_span::FileName::Real(real_fname) = fname {
-                    if let Some(p) = real_fname.remapped_path_if_available().to_str() {
-                        file_name = p.split("/lib/rustlib/src").last().map(|s| s.to_string());
-                    }
-                }
-                files.push(file_name.unwrap_or_else(|| "unknown".into()));
+                files.push(display_file_name(fname));
                 index
             }
         }
@@ -1479,3 +2181,616 @@ impl CallSiteOutput {
         }
     }
 }
+
+/// The recursive groups of functions of the call graph, largest first.
+#[derive(Serialize)]
+struct SccOutput {
+    components: Vec<StronglyConnectedComponent>,
+}
+
+/// A group of functions that call each other, directly or indirectly, or a single function
+/// that calls itself.
+#[derive(Serialize)]
+struct StronglyConnectedComponent {
+    /// The number of functions in the group.
+    size: usize,
+    /// True if any function in the group is a crate root.
+    has_croot: bool,
+    /// The names of the functions, as they appear in the dot output.
+    members: Vec<String>,
+    /// The node indices of the functions, as used by the dot and Datalog outputs.
+    node_ids: Vec<usize>,
+}
+
+/// Returns the path of the given source file as it appears in the call graph outputs.
+/// For the sources of the Rust standard library, this is the part of the path that follows
+/// "/lib/rustlib/src". For other files, it is the (remapped) path as given to the compiler,
+/// which is relative to the crate root directory for the files of the crate being built.
+/// Files that are not real, such as macro expansions, are "unknown".
+fn display_file_name(fname: &rustc_span::FileName) -> String {
+    let mut file_name = None;
+    if let rustc_span::FileName::Real(real_fname) = fname {
+        if let Some(p) = real_fname.remapped_path_if_available().to_str() {
+            file_name = p.split("/lib/rustlib/src").last().map(|s| s.to_string());
+        }
+    }
+    file_name.unwrap_or_else(|| "unknown".into())
+}
+
+/// A self-contained rendering of the (reduced) call graph, from which the GraphML and
+/// node-link JSON outputs are produced. Node and edge identifiers are the indices of the nodes
+/// and edges in the graph, which are also used by the dot and Datalog outputs.
+#[derive(Serialize)]
+struct GraphExport {
+    directed: bool,
+    multigraph: bool,
+    graph: HashMap<String, String>,
+    nodes: Vec<ExportedNode>,
+    links: Vec<ExportedEdge>,
+}
+
+#[derive(Serialize)]
+struct ExportedNode {
+    id: usize,
+    /// The name of the function, as it appears in the dot output.
+    name: String,
+    /// The summary key of the function, which is the same in the graphs of all crates.
+    key: String,
+    /// The name of the crate that defines the function.
+    #[serde(rename = "crate")]
+    crate_name: String,
+    /// "root" or "croot" (crate root).
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct ExportedEdge {
+    source: usize,
+    target: usize,
+    /// Distinguishes parallel edges, which connect the same nodes but have different types.
+    key: usize,
+    /// The index of the edge type in the type map.
+    type_id: TypeId,
+    /// The Rust type associated with the edge. Empty if the call has no arguments.
+    #[serde(rename = "type")]
+    type_name: String,
+    /// The ways in which the calls from the caller to the callee were resolved.
+    resolution: Vec<CallResolution>,
+    /// The source locations of the calls from the caller to the callee.
+    call_sites: Vec<ExportedCallSite>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+struct ExportedCallSite {
+    file: String,
+    /// 1-based line number.
+    line: usize,
+    /// 1-based column number.
+    column: usize,
+    /// How the callee of the call was resolved.
+    resolution: CallResolution,
+}
+
+impl fmt::Display for ExportedCallSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl GraphExport {
+    pub fn new(call_graph: &CallGraph<'_>) -> GraphExport {
+        let tcx = call_graph.tcx;
+        let type_names: HashMap<TypeId, &str> = call_graph
+            .edge_types
+            .values()
+            .map(|edge_type| (edge_type.id, edge_type.name.as_ref()))
+            .collect();
+        let calls = call_graph.call_sites_by_endpoints();
+        let graph = &call_graph.graph;
+        let nodes = graph
+            .node_indices()
+            .filter_map(|node_id| {
+                graph.node_weight(node_id).map(|node| ExportedNode {
+                    id: node_id.index(),
+                    name: node.name.to_string(),
+                    key: utils::summary_key_str(tcx, node.defid).to_string(),
+                    crate_name: tcx.crate_name(node.defid.krate).to_string(),
+                    kind: if node.is_croot() { "croot" } else { "root" },
+                })
+            })
+            .collect();
+        let links = graph
+            .edge_indices()
+            .filter_map(|edge_id| {
+                let (start_id, end_id) = graph.edge_endpoints(edge_id)?;
+                let edge = graph.edge_weight(edge_id)?;
+                let caller = graph.node_weight(start_id)?.defid;
+                let callee = graph.node_weight(end_id)?.defid;
+                let call_sites: Vec<ExportedCallSite> =
+                    calls.get(&(caller, callee)).cloned().unwrap_or_default();
+                let resolution = call_sites
+                    .iter()
+                    .map(|site| site.resolution)
+                    .collect::<BTreeSet<CallResolution>>()
+                    .into_iter()
+                    .collect();
+                Some(ExportedEdge {
+                    source: start_id.index(),
+                    target: end_id.index(),
+                    key: edge_id.index(),
+                    type_id: edge.type_id,
+                    type_name: type_names
+                        .get(&edge.type_id)
+                        .map(|name| name.to_string())
+                        .unwrap_or_default(),
+                    resolution,
+                    call_sites,
+                })
+            })
+            .collect();
+        GraphExport {
+            directed: true,
+            multigraph: true,
+            graph: HashMap::new(),
+            nodes,
+            links,
+        }
+    }
+
+    /// Renders the graph as a GraphML document. Since GraphML attributes are scalars, the call
+    /// sites of an edge are rendered as a single string of file:line:column locations,
+    /// separated by semicolons.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, domain, attr_type) in [
+            ("name", "node", "string"),
+            ("key", "node", "string"),
+            ("crate", "node", "string"),
+            ("kind", "node", "string"),
+            ("type_id", "edge", "int"),
+            ("type", "edge", "string"),
+            ("resolution", "edge", "string"),
+            ("call_sites", "edge", "string"),
+        ] {
+            out.push_str(&format!("  <key id=\"{id}\" for=\"{domain}\" "));
+            out.push_str(&format!("attr.name=\"{id}\" attr.type=\"{attr_type}\"/>\n"));
+        }
+        out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        for node in self.nodes.iter() {
+            out.push_str(&format!(
+                "    <node id=\"n{}\">{}{}{}{}</node>\n",
+                node.id,
+                graphml_data("name", &node.name),
+                graphml_data("key", &node.key),
+                graphml_data("crate", &node.crate_name),
+                graphml_data("kind", node.kind)
+            ));
+        }
+        for edge in self.links.iter() {
+            let call_sites = edge
+                .call_sites
+                .iter()
+                .map(|site| site.to_string())
+                .collect::<Vec<String>>()
+                .join(";");
+            let resolution = edge
+                .resolution
+                .iter()
+                .map(|kind| kind.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            out.push_str(&format!(
+                "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">{}{}{}{}</edge>\n",
+                edge.key,
+                edge.source,
+                edge.target,
+                graphml_data("type_id", &edge.type_id.to_string()),
+                graphml_data("type", &edge.type_name),
+                graphml_data("resolution", &resolution),
+                graphml_data("call_sites", &call_sites)
+            ));
+        }
+        out.push_str("  </graph>\n");
+        out.push_str("</graphml>\n");
+        out
+    }
+}
+
+/// The call graph of a workspace, in the node-link JSON format of the JSON graph output.
+/// Since every crate is analyzed by a separate MIRAI invocation, the graph is built up by
+/// merging in the graph of one crate at a time. Nodes are identified by the summary keys of
+/// their functions, so that a call from one crate links up with the node of the callee in the
+/// graph of the crate that defines it.
+#[derive(Default, Deserialize, Serialize)]
+struct WorkspaceGraph {
+    directed: bool,
+    multigraph: bool,
+    graph: HashMap<String, String>,
+    nodes: Vec<WorkspaceNode>,
+    links: Vec<WorkspaceEdge>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct WorkspaceNode {
+    id: usize,
+    name: String,
+    key: String,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    /// "croot" if the function is a crate root in the graph of any crate, "root" otherwise.
+    kind: String,
+    /// The crates whose graphs include the function.
+    analyzed_in: BTreeSet<String>,
+    /// The crates in whose graphs the function is a crate root.
+    croot_in: BTreeSet<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct WorkspaceEdge {
+    source: usize,
+    target: usize,
+    key: usize,
+    /// The Rust type associated with the edge. Type ids are not given, since they differ
+    /// between crates.
+    #[serde(rename = "type")]
+    type_name: String,
+    resolution: Vec<CallResolution>,
+    call_sites: Vec<ExportedCallSite>,
+    /// The crate whose graph includes the edge.
+    analyzed_in: String,
+}
+
+impl WorkspaceGraph {
+    /// Replaces the nodes and edges that were merged into the graph by a previous analysis
+    /// of the given crate with those of its current graph.
+    fn merge(&mut self, crate_name: &str, crate_graph: &GraphExport) {
+        let old_keys: Vec<String> = self.nodes.iter().map(|node| node.key.clone()).collect();
+        let mut nodes: BTreeMap<String, WorkspaceNode> = BTreeMap::new();
+        for mut node in self.nodes.drain(..) {
+            node.analyzed_in.remove(crate_name);
+            node.croot_in.remove(crate_name);
+            if !node.analyzed_in.is_empty() {
+                nodes.insert(node.key.clone(), node);
+            }
+        }
+        for node in crate_graph.nodes.iter() {
+            let merged_node = nodes
+                .entry(node.key.clone())
+                .or_insert_with(|| WorkspaceNode {
+                    id: 0,
+                    name: node.name.clone(),
+                    key: node.key.clone(),
+                    crate_name: node.crate_name.clone(),
+                    kind: node.kind.to_string(),
+                    analyzed_in: BTreeSet::new(),
+                    croot_in: BTreeSet::new(),
+                });
+            if node.kind == "croot" {
+                merged_node.croot_in.insert(crate_name.to_string());
+            }
+            merged_node.analyzed_in.insert(crate_name.to_string());
+        }
+        for node in nodes.values_mut() {
+            node.kind = if node.croot_in.is_empty() {
+                "root"
+            } else {
+                "croot"
+            }
+            .to_string();
+        }
+        // Edges are given as (source key, target key, type, ...) until the nodes are renumbered.
+        let mut edges: Vec<(String, String, WorkspaceEdge)> = self
+            .links
+            .drain(..)
+            .filter(|edge| edge.analyzed_in != crate_name)
+            .map(|edge| {
+                let source = old_keys[edge.source].clone();
+                let target = old_keys[edge.target].clone();
+                (source, target, edge)
+            })
+            .collect();
+        let crate_keys: HashMap<usize, &str> = crate_graph
+            .nodes
+            .iter()
+            .map(|node| (node.id, node.key.as_str()))
+            .collect();
+        for edge in crate_graph.links.iter() {
+            edges.push((
+                crate_keys[&edge.source].to_string(),
+                crate_keys[&edge.target].to_string(),
+                WorkspaceEdge {
+                    source: 0,
+                    target: 0,
+                    key: 0,
+                    type_name: edge.type_name.clone(),
+                    resolution: edge.resolution.clone(),
+                    call_sites: edge.call_sites.clone(),
+                    analyzed_in: crate_name.to_string(),
+                },
+            ));
+        }
+        // Number the nodes in the order of their keys and the edges in the order of their
+        // endpoints, so that the file does not depend on the order in which crates are merged.
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        self.nodes = nodes
+            .into_values()
+            .enumerate()
+            .map(|(id, mut node)| {
+                ids.insert(node.key.clone(), id);
+                node.id = id;
+                node
+            })
+            .collect();
+        edges.sort_by(|(s1, t1, e1), (s2, t2, e2)| {
+            (s1, t1, &e1.type_name, &e1.analyzed_in).cmp(&(s2, t2, &e2.type_name, &e2.analyzed_in))
+        });
+        self.links = edges
+            .into_iter()
+            .enumerate()
+            .map(|(key, (source, target, mut edge))| {
+                edge.source = ids[&source];
+                edge.target = ids[&target];
+                edge.key = key;
+                edge
+            })
+            .collect();
+        self.directed = true;
+        self.multigraph = true;
+    }
+}
+
+/// Renders a GraphML attribute value of a node or edge.
+fn graphml_data(key: &str, value: &str) -> String {
+    format!("<data key=\"{key}\">{}</data>", escape_xml(value))
+}
+
+/// Escapes the characters that may not appear literally in XML character data or attributes.
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: usize, key: &str, kind: &'static str) -> ExportedNode {
+        ExportedNode {
+            id,
+            name: key.to_string(),
+            key: key.to_string(),
+            crate_name: key.split("::").next().unwrap_or_default().to_string(),
+            kind,
+        }
+    }
+
+    fn edge(key: usize, source: usize, target: usize) -> ExportedEdge {
+        ExportedEdge {
+            source,
+            target,
+            key,
+            type_id: 0,
+            type_name: String::new(),
+            resolution: vec![CallResolution::Static],
+            call_sites: vec![ExportedCallSite {
+                file: "src/lib.rs".to_string(),
+                line: key + 1,
+                column: 5,
+                resolution: CallResolution::Static,
+            }],
+        }
+    }
+
+    fn graph(nodes: Vec<ExportedNode>, links: Vec<ExportedEdge>) -> GraphExport {
+        GraphExport {
+            directed: true,
+            multigraph: true,
+            graph: HashMap::new(),
+            nodes,
+            links,
+        }
+    }
+
+    fn edge_keys(workspace_graph: &WorkspaceGraph) -> Vec<(String, String, String)> {
+        workspace_graph
+            .links
+            .iter()
+            .map(|edge| {
+                (
+                    workspace_graph.nodes[edge.source].key.clone(),
+                    workspace_graph.nodes[edge.target].key.clone(),
+                    edge.analyzed_in.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn remerging_a_crate_replaces_its_nodes_and_edges() {
+        let mut workspace_graph = WorkspaceGraph::default();
+        workspace_graph.merge(
+            "b",
+            &graph(
+                vec![node(0, "b::h", "croot"), node(1, "b::k", "root")],
+                vec![edge(0, 0, 1)],
+            ),
+        );
+        workspace_graph.merge(
+            "a",
+            &graph(
+                vec![
+                    node(0, "a::f", "croot"),
+                    node(1, "a::g", "root"),
+                    node(2, "b::h", "root"),
+                    node(3, "b::k", "croot"),
+                ],
+                vec![edge(0, 0, 1), edge(1, 1, 2)],
+            ),
+        );
+        assert_eq!(workspace_graph.nodes.len(), 4);
+        assert_eq!(workspace_graph.nodes[3].kind, "croot");
+        assert_eq!(workspace_graph.links.len(), 3);
+
+        // a::g is gone and a::f now calls b::h directly.
+        workspace_graph.merge(
+            "a",
+            &graph(
+                vec![node(0, "b::h", "root"), node(1, "a::f", "croot")],
+                vec![edge(0, 1, 0)],
+            ),
+        );
+        let keys: Vec<&str> = workspace_graph
+            .nodes
+            .iter()
+            .map(|node| node.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["a::f", "b::h", "b::k"]);
+        for (id, node) in workspace_graph.nodes.iter().enumerate() {
+            assert_eq!(node.id, id);
+        }
+        let b_h = &workspace_graph.nodes[1];
+        assert_eq!(b_h.kind, "croot");
+        assert_eq!(b_h.analyzed_in.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+        // b::k is no longer a crate root in the graph of a.
+        assert_eq!(workspace_graph.nodes[2].kind, "root");
+        assert_eq!(
+            edge_keys(&workspace_graph),
+            vec![
+                ("a::f".to_string(), "b::h".to_string(), "a".to_string()),
+                ("b::h".to_string(), "b::k".to_string(), "b".to_string()),
+            ]
+        );
+        for (key, edge) in workspace_graph.links.iter().enumerate() {
+            assert_eq!(edge.key, key);
+        }
+
+        // Merging an empty graph of a crate removes everything that only it contributed.
+        workspace_graph.merge("a", &graph(vec![], vec![]));
+        let keys: Vec<&str> = workspace_graph
+            .nodes
+            .iter()
+            .map(|node| node.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["b::h", "b::k"]);
+        assert_eq!(
+            edge_keys(&workspace_graph),
+            vec![("b::h".to_string(), "b::k".to_string(), "b".to_string())]
+        );
+    }
+
+    #[test]
+    fn souffle_symbols_are_quoted_as_in_csv_files() {
+        let relation = DatalogRelation::new_node_name(3, "<Vec<T, A> as From<&\"str\">>::from");
+        assert_eq!(
+            relation.to_souffle(),
+            r#"3,"<Vec<T, A> as From<&""str"">>::from""#
+        );
+        assert_eq!(
+            relation.to_differential_datalog(),
+            r#"NodeName(3,"<Vec<T, A> as From<&\"str\">>::from")"#
+        );
+        let call_site = ExportedCallSite {
+            file: "src/lib.rs".to_string(),
+            line: 12,
+            column: 5,
+            resolution: CallResolution::Static,
+        };
+        assert_eq!(
+            DatalogRelation::new_call_site(0, &call_site).to_souffle(),
+            r#"0,"src/lib.rs",12,5"#
+        );
+        assert_eq!(DatalogRelation::new_is_unsafe(7).to_souffle(), "7");
+    }
+
+    #[test]
+    fn graphml_output_has_escaped_nodes_and_edges() {
+        let graphml = graph(
+            vec![
+                node(0, "a::<&str as T>::f", "croot"),
+                node(1, "a::g", "root"),
+            ],
+            vec![edge(0, 0, 1)],
+        )
+        .to_graphml();
+        let lines: Vec<&str> = graphml.lines().collect();
+        assert_eq!(lines[0], "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("  <key "))
+                .count(),
+            8
+        );
+        assert!(lines.contains(
+            &"  <key id=\"call_sites\" for=\"edge\" attr.name=\"call_sites\" attr.type=\"string\"/>"
+        ));
+        assert!(lines.contains(&concat!(
+            "    <node id=\"n0\">",
+            "<data key=\"name\">a::&lt;&amp;str as T&gt;::f</data>",
+            "<data key=\"key\">a::&lt;&amp;str as T&gt;::f</data>",
+            "<data key=\"crate\">a</data><data key=\"kind\">croot</data></node>"
+        )));
+        assert!(lines.contains(&concat!(
+            "    <edge id=\"e0\" source=\"n0\" target=\"n1\">",
+            "<data key=\"type_id\">0</data><data key=\"type\"></data>",
+            "<data key=\"resolution\">static</data>",
+            "<data key=\"call_sites\">src/lib.rs:1:5</data></edge>"
+        )));
+        assert_eq!(lines[lines.len() - 1], "</graphml>");
+    }
+
+    #[test]
+    fn json_graph_output_is_in_the_node_link_format() {
+        let json_graph = serde_json::to_value(graph(
+            vec![node(0, "a::f", "croot"), node(1, "a::g", "root")],
+            vec![edge(0, 0, 1)],
+        ))
+        .unwrap();
+        assert_eq!(
+            json_graph,
+            serde_json::json!({
+                "directed": true,
+                "multigraph": true,
+                "graph": {},
+                "nodes": [
+                    { "id": 0, "name": "a::f", "key": "a::f", "crate": "a", "kind": "croot" },
+                    { "id": 1, "name": "a::g", "key": "a::g", "crate": "a", "kind": "root" }
+                ],
+                "links": [
+                    {
+                        "source": 0,
+                        "target": 1,
+                        "key": 0,
+                        "type_id": 0,
+                        "type": "",
+                        "resolution": ["static"],
+                        "call_sites": [
+                            { "file": "src/lib.rs", "line": 1, "column": 5, "resolution": "static" }
+                        ]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn xml_special_characters_are_escaped() {
+        assert_eq!(
+            escape_xml("<a href='x'>\"&\"</a>"),
+            "&lt;a href=&apos;x&apos;&gt;&quot;&amp;&quot;&lt;/a&gt;"
+        );
+        assert_eq!(escape_xml("a::f"), "a::f");
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// This implements the "cargo mirai callgraph-diff" subcommand, which compares two call graphs
+// written by the call graph generator, for instance those of the base and the head of a pull
+// request. Like "cargo mirai store", it is forwarded to the mirai binary, as
+// "mirai callgraph-diff <arguments>".
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const CALLGRAPH_DIFF_HELP: &str = r#"Compare two MIRAI call graphs
+
+Usage:
+    cargo mirai callgraph-diff [options] <old graph> <new graph>
+
+The graphs are the JSON files written by the call graph generator when "json_graph_output_path"
+or "workspace_graph_output_path" is configured. Functions are matched by their summary keys.
+
+Options:
+    --format text|json          print the differences as text (the default) or as JSON
+    --sensitive_crate <name>    also list the functions of this crate that become reachable
+                                from a crate root; can be given more than once
+    --help                      print this message
+"#;
+
+/// Runs the callgraph-diff command and returns the exit code of the process.
+pub fn run(args: &[String]) -> i32 {
+    let mut json = false;
+    let mut sensitive_crates = vec![];
+    let mut paths = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                println!("{CALLGRAPH_DIFF_HELP}");
+                return 0;
+            }
+            "--format" => match args.next().map(String::as_str) {
+                Some("text") => json = false,
+                Some("json") => json = true,
+                _ => {
+                    eprintln!("{CALLGRAPH_DIFF_HELP}");
+                    return 2;
+                }
+            },
+            "--sensitive_crate" => match args.next() {
+                Some(crate_name) => sensitive_crates.push(crate_name.clone()),
+                None => {
+                    eprintln!("{CALLGRAPH_DIFF_HELP}");
+                    return 2;
+                }
+            },
+            _ => paths.push(arg),
+        }
+    }
+    let [old_path, new_path] = paths.as_slice() else {
+        eprintln!("{CALLGRAPH_DIFF_HELP}");
+        return 2;
+    };
+    let diff = match (
+        ComparedGraph::read(Path::new(old_path)),
+        ComparedGraph::read(Path::new(new_path)),
+    ) {
+        (Ok(old), Ok(new)) => CallGraphDiff::new(&old, &new, &sensitive_crates),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if json {
+        match serde_json::to_string_pretty(&diff) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        }
+    } else {
+        print!("{diff}");
+    }
+    0
+}
+
+/// The parts of the node-link JSON call graph output that are needed for the comparison.
+#[derive(Deserialize)]
+struct GraphFile {
+    nodes: Vec<NodeEntry>,
+    links: Vec<LinkEntry>,
+}
+
+#[derive(Deserialize)]
+struct NodeEntry {
+    id: usize,
+    name: String,
+    key: String,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct LinkEntry {
+    source: usize,
+    target: usize,
+}
+
+/// A call graph in which functions are identified by their summary keys, since the node
+/// indices of two graphs are unrelated.
+pub struct ComparedGraph {
+    functions: BTreeMap<String, DiffFunction>,
+    crate_roots: BTreeSet<String>,
+    /// (caller key, callee key). Edges that differ only in their types are not distinguished.
+    calls: BTreeSet<(String, String)>,
+}
+
+impl ComparedGraph {
+    /// Reads a graph from a JSON graph or workspace graph file.
+    pub fn read(path: &Path) -> Result<ComparedGraph, String> {
+        let graph_str = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {e}", path.display()))?;
+        let graph_file: GraphFile = serde_json::from_str(&graph_str)
+            .map_err(|e| format!("could not parse {}: {e}", path.display()))?;
+        let mut keys = BTreeMap::new();
+        let mut functions = BTreeMap::new();
+        let mut crate_roots = BTreeSet::new();
+        for node in graph_file.nodes {
+            keys.insert(node.id, node.key.clone());
+            if node.kind == "croot" {
+                crate_roots.insert(node.key.clone());
+            }
+            functions.insert(
+                node.key.clone(),
+                DiffFunction {
+                    name: node.name,
+                    key: node.key,
+                    crate_name: node.crate_name,
+                },
+            );
+        }
+        let mut calls = BTreeSet::new();
+        for link in graph_file.links {
+            match (keys.get(&link.source), keys.get(&link.target)) {
+                (Some(caller), Some(callee)) => {
+                    calls.insert((caller.clone(), callee.clone()));
+                }
+                _ => return Err(format!("{} has a link to a missing node", path.display())),
+            }
+        }
+        Ok(ComparedGraph {
+            functions,
+            crate_roots,
+            calls,
+        })
+    }
+
+    /// Returns the keys of the functions that can be reached from a crate root, including the
+    /// crate roots themselves.
+    fn reachable_from_crate_roots(&self) -> BTreeSet<&str> {
+        let mut callees: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (caller, callee) in self.calls.iter() {
+            callees
+                .entry(caller.as_str())
+                .or_default()
+                .push(callee.as_str());
+        }
+        let mut reachable: BTreeSet<&str> = BTreeSet::new();
+        let mut to_visit: VecDeque<&str> = self.crate_roots.iter().map(String::as_str).collect();
+        while let Some(key) = to_visit.pop_front() {
+            if reachable.insert(key) {
+                to_visit.extend(callees.get(key).into_iter().flatten());
+            }
+        }
+        reachable
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct DiffFunction {
+    /// The name of the function, as it appears in the dot output.
+    pub name: String,
+    pub key: String,
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct DiffCall {
+    /// The name of the calling function.
+    pub caller: String,
+    /// The name of the called function.
+    pub callee: String,
+}
+
+/// The differences between an old and a new call graph.
+#[derive(Debug, Serialize)]
+pub struct CallGraphDiff {
+    pub added_functions: Vec<DiffFunction>,
+    pub removed_functions: Vec<DiffFunction>,
+    pub added_calls: Vec<DiffCall>,
+    pub removed_calls: Vec<DiffCall>,
+    /// The functions that can be reached from a crate root in the new graph, but not in the
+    /// old graph.
+    pub newly_reachable: Vec<DiffFunction>,
+    /// The newly reachable functions that belong to one of the sensitive crates.
+    pub newly_reachable_in_sensitive_crates: Vec<DiffFunction>,
+}
+
+impl CallGraphDiff {
+    pub fn new(
+        old: &ComparedGraph,
+        new: &ComparedGraph,
+        sensitive_crates: &[String],
+    ) -> CallGraphDiff {
+        let functions_only_in = |graph: &ComparedGraph, other: &ComparedGraph| {
+            graph
+                .functions
+                .iter()
+                .filter(|(key, _)| !other.functions.contains_key(*key))
+                .map(|(_, function)| function.clone())
+                .collect::<Vec<DiffFunction>>()
+        };
+        let calls_only_in = |graph: &ComparedGraph, other: &ComparedGraph| {
+            graph
+                .calls
+                .difference(&other.calls)
+                .map(|(caller, callee)| DiffCall {
+                    caller: graph.functions[caller].name.clone(),
+                    callee: graph.functions[callee].name.clone(),
+                })
+                .collect::<Vec<DiffCall>>()
+        };
+        let old_reachable = old.reachable_from_crate_roots();
+        let newly_reachable: Vec<DiffFunction> = new
+            .reachable_from_crate_roots()
+            .difference(&old_reachable)
+            .map(|key| new.functions[*key].clone())
+            .collect();
+        let newly_reachable_in_sensitive_crates = newly_reachable
+            .iter()
+            .filter(|function| sensitive_crates.contains(&function.crate_name))
+            .cloned()
+            .collect();
+        CallGraphDiff {
+            added_functions: functions_only_in(new, old),
+            removed_functions: functions_only_in(old, new),
+            added_calls: calls_only_in(new, old),
+            removed_calls: calls_only_in(old, new),
+            newly_reachable,
+            newly_reachable_in_sensitive_crates,
+        }
+    }
+}
+
+impl fmt::Display for CallGraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let function_sections = [
+            ("added functions", "+", &self.added_functions),
+            ("removed functions", "-", &self.removed_functions),
+        ];
+        for (title, marker, functions) in function_sections {
+            writeln!(f, "{title}: {}", functions.len())?;
+            for function in functions {
+                writeln!(f, "  {marker} {} ({})", function.name, function.crate_name)?;
+            }
+        }
+        let call_sections = [
+            ("added calls", "+", &self.added_calls),
+            ("removed calls", "-", &self.removed_calls),
+        ];
+        for (title, marker, calls) in call_sections {
+            writeln!(f, "{title}: {}", calls.len())?;
+            for call in calls {
+                writeln!(f, "  {marker} {} -> {}", call.caller, call.callee)?;
+            }
+        }
+        let reachability_sections = [
+            ("newly reachable from crate roots", &self.newly_reachable),
+            (
+                "newly reachable in sensitive crates",
+                &self.newly_reachable_in_sensitive_crates,
+            ),
+        ];
+        for (title, functions) in reachability_sections {
+            writeln!(f, "{title}: {}", functions.len())?;
+            for function in functions {
+                writeln!(f, "  {} ({})", function.name, function.crate_name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(functions: &[(&str, &str, bool)], calls: &[(&str, &str)]) -> ComparedGraph {
+        ComparedGraph {
+            functions: functions
+                .iter()
+                .map(|(key, crate_name, _)| {
+                    let function = DiffFunction {
+                        name: key.replace('.', "::"),
+                        key: key.to_string(),
+                        crate_name: crate_name.to_string(),
+                    };
+                    (key.to_string(), function)
+                })
+                .collect(),
+            crate_roots: functions
+                .iter()
+                .filter(|(_, _, is_crate_root)| *is_crate_root)
+                .map(|(key, _, _)| key.to_string())
+                .collect(),
+            calls: calls
+                .iter()
+                .map(|(caller, callee)| (caller.to_string(), callee.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn new_calls_make_functions_reachable() {
+        let old = graph(
+            &[
+                ("app.main", "app", true),
+                ("app.helper", "app", false),
+                ("db.query", "db", false),
+            ],
+            &[("app.main", "app.helper")],
+        );
+        let new = graph(
+            &[
+                ("app.main", "app", true),
+                ("app.helper", "app", false),
+                ("db.query", "db", false),
+                ("db.connect", "db", false),
+            ],
+            &[
+                ("app.main", "app.helper"),
+                ("app.helper", "db.query"),
+                ("db.query", "db.connect"),
+            ],
+        );
+        let diff = CallGraphDiff::new(&old, &new, &["db".to_string()]);
+        let keys = |functions: &[DiffFunction]| {
+            functions
+                .iter()
+                .map(|function| function.key.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(keys(&diff.added_functions), ["db.connect"]);
+        assert!(diff.removed_functions.is_empty());
+        assert_eq!(diff.added_calls.len(), 2);
+        assert!(diff.removed_calls.is_empty());
+        assert_eq!(keys(&diff.newly_reachable), ["db.connect", "db.query"]);
+        assert_eq!(
+            keys(&diff.newly_reachable_in_sensitive_crates),
+            ["db.connect", "db.query"]
+        );
+    }
+}
@@ -21,7 +21,9 @@ use rustc_target::abi::VariantIdx;
 use crate::abstract_value::{AbstractValue, AbstractValueTrait};
 use crate::block_visitor::BlockVisitor;
 use crate::body_visitor::BodyVisitor;
+use crate::call_graph::CallResolution;
 use crate::constant_domain::{ConstantDomain, FunctionReference};
+use crate::diagnostic_rules::DiagnosticRule;
 use crate::environment::Environment;
 use crate::expression::{Expression, ExpressionType, LayoutSource};
 use crate::k_limits;
@@ -31,6 +33,7 @@ use crate::path::{Path, PathEnum, PathRefinement, PathRoot, PathSelector};
 use crate::summaries::{Precondition, Summary};
 use crate::tag_domain::Tag;
 use crate::type_visitor::TypeVisitor;
+use crate::unresolved_calls::UnresolvedReason;
 use crate::{abstract_value, utils};
 
 pub struct CallVisitor<'call, 'block, 'analysis, 'compilation, 'tcx> {
@@ -43,6 +46,8 @@ pub struct CallVisitor<'call, 'block, 'analysis, 'compilation, 'tcx> {
     pub callee_generic_arguments: Option<GenericArgsRef<'tcx>>,
     pub callee_known_name: KnownNames,
     pub callee_generic_argument_map: Option<HashMap<rustc_span::Symbol, GenericArg<'tcx>>>,
+    /// How the callee was determined, as recorded in the call graph.
+    pub call_resolution: CallResolution,
     pub unwind: mir::UnwindAction,
     pub destination: mir::Place<'tcx>,
     pub target: Option<mir::BasicBlock>,
@@ -78,6 +83,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 callee_generic_arguments,
                 callee_known_name,
                 callee_generic_argument_map,
+                call_resolution: CallResolution::Static,
                 actual_args: vec![],
                 actual_argument_types: vec![],
                 unwind: mir::UnwindAction::Continue,
@@ -182,7 +188,8 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
 
     /// If self.callee_def_id is a trait (virtual) then this tries to get the def_id of the
     /// concrete method that implements the given virtual method and returns the summary of that,
-    /// computing it if necessary.
+    /// computing it if necessary. The call is marked as virtual if the method is called on a
+    /// trait object, or if no implementation can be selected for the generic arguments.
     #[logfn_inputs(TRACE)]
     fn try_to_devirtualize(&mut self) {
         let tcx = self.block_visitor.bv.tcx;
@@ -210,6 +217,11 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     // Instance::resolve panics if it can't find a vtable entry for the given def_id
                     // It is hard to figure out exactly when this will be the case, but it does
                     // happen in a case where the first generic argument type is Dynamic.
+                    if utils::is_trait_method(self.callee_def_id, tcx) {
+                        // The Self type of the trait method is a trait object, so the callee
+                        // is only known at runtime.
+                        self.call_resolution = CallResolution::Virtual;
+                    }
                     return;
                 }
             }
@@ -284,6 +296,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     }
                 }
             } else {
+                if resolved_instance.is_some() && utils::is_trait_method(self.callee_def_id, tcx) {
+                    self.call_resolution = CallResolution::Virtual;
+                }
                 debug!(
                     "could not resolve function {:?}, {:?}, {:?}",
                     self.callee_def_id, typing_env, gen_args,
@@ -314,7 +329,16 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
     /// Returns a summary of the function to call, obtained from the summary cache.
     #[logfn_inputs(TRACE)]
     pub fn get_function_summary(&mut self) -> Option<Summary> {
+        let def_id_before_devirtualization = self.callee_def_id;
         self.try_to_devirtualize();
+        let tcx = self.block_visitor.bv.tcx;
+        if self.callee_def_id != def_id_before_devirtualization {
+            self.call_resolution = CallResolution::TraitDispatch;
+        }
+        let caller_def_id = self.block_visitor.bv.def_id;
+        if let Some(incremental) = &mut self.block_visitor.bv.cv.incremental {
+            incremental.note_call(caller_def_id, self.callee_def_id);
+        }
         if self.block_visitor.bv.cv.call_graph.needs_edges() {
             if self.actual_argument_types.is_empty() {
                 self.block_visitor.bv.cv.call_graph.add_edge(
@@ -324,10 +348,10 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 );
             } else {
                 for ty in self.actual_argument_types.iter() {
-                    self.block_visitor.bv.cv.call_graph.add_edge(
+                    self.block_visitor.bv.cv.call_graph.add_typed_edge(
                         self.block_visitor.bv.def_id,
                         self.callee_def_id,
-                        ty.to_string().into_boxed_str(),
+                        *ty,
                     );
                 }
             }
@@ -339,12 +363,12 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             // predefined summaries.
 
             let func_args = self.get_function_constant_signature(self.function_constant_args);
-            let tcx = self.block_visitor.bv.tcx;
             let callee_def_id = func_ref.def_id.unwrap_or(self.callee_def_id);
             self.block_visitor.bv.cv.call_graph.add_call_site(
                 self.block_visitor.bv.current_span,
                 self.block_visitor.bv.def_id,
                 callee_def_id,
+                self.call_resolution,
                 !tcx.is_mir_available(callee_def_id)
                     || (!callee_def_id.is_local()
                         && (self.callee_generic_arguments.is_none()
@@ -627,7 +651,8 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.handle_swap_non_overlapping();
                 return true;
             }
-            KnownNames::StdPanickingAssertFailed
+            KnownNames::MiraiVerifyUnreachable
+            | KnownNames::StdPanickingAssertFailed
             | KnownNames::StdPanickingBeginPanic
             | KnownNames::StdPanickingBeginPanicFmt => {
                 if self.block_visitor.bv.check_for_errors {
@@ -723,6 +748,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                     let precondition = Precondition {
                                         condition: promotable_is_zero,
                                         message: Rc::from("incomplete analysis of call because of failure to resolve std::Clone::clone method"),
+                                        rule: DiagnosticRule::IncompleteAnalysis,
                                         provenance: None,
                                         spans: vec![self.block_visitor.bv.current_span.source_callsite()],
                                     };
@@ -796,7 +822,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                         .session
                         .dcx()
                         .struct_span_warn(span, message);
-                    self.block_visitor.bv.emit_diagnostic(warning);
+                    self.block_visitor
+                        .bv
+                        .emit_diagnostic(warning, DiagnosticRule::Unreachable);
                     return;
                 }
 
@@ -817,7 +845,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     .session
                     .dcx()
                     .struct_span_warn(span, message);
-                self.block_visitor.bv.emit_diagnostic(warning);
+                self.block_visitor
+                    .bv
+                    .emit_diagnostic(warning, DiagnosticRule::Assumption);
             }
             KnownNames::MiraiPostcondition => {
                 let actual_args = self.actual_args.clone();
@@ -854,7 +884,8 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     KnownNames::MiraiVerify,
                 );
             }
-            KnownNames::StdPanickingAssertFailed
+            KnownNames::MiraiVerifyUnreachable
+            | KnownNames::StdPanickingAssertFailed
             | KnownNames::StdPanickingBeginPanic
             | KnownNames::StdPanickingBeginPanicFmt => {
                 assume!(!self.actual_args.is_empty()); // The type checker ensures this.
@@ -865,7 +896,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 }
                 let msg = match self.callee_known_name {
                     KnownNames::StdPanickingAssertFailed => Rc::from("assertion failed"),
-                    KnownNames::StdPanickingBeginPanic => {
+                    KnownNames::MiraiVerifyUnreachable | KnownNames::StdPanickingBeginPanic => {
                         self.coerce_to_string(&self.actual_args[0].0.clone())
                     }
                     _ => {
@@ -916,7 +947,15 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                         Rc::from(msg)
                     }
                 };
-                if msg.contains("entered unreachable code")
+                let is_verify_unreachable =
+                    self.callee_known_name == KnownNames::MiraiVerifyUnreachable;
+                if is_verify_unreachable {
+                    // verify_unreachable should always complain if possibly reachable
+                    // and the current function is public or root.
+                    if path_cond.is_none() {
+                        path_cond = Some(true);
+                    }
+                } else if msg.contains("entered unreachable code")
                     || msg.contains("not yet implemented")
                     || msg.contains("not implemented")
                     || msg.starts_with("unrecoverable: ")
@@ -927,13 +966,14 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     // unimplemented!() is unlikely to be a programmer mistake, so need to fixate on that either.
                     // unrecoverable! is way for the programmer to indicate that termination is not a mistake.
                     return;
-                } else if path_cond.is_none() && msg.as_ref() == "statement is reachable" {
-                    // verify_unreachable should always complain if possibly reachable
-                    // and the current function is public or root.
-                    path_cond = Some(true);
-                };
+                }
 
                 let span = self.block_visitor.bv.current_span.source_callsite();
+                let rule = if is_verify_unreachable {
+                    DiagnosticRule::Unreachable
+                } else {
+                    DiagnosticRule::Panic
+                };
 
                 if path_cond.unwrap_or(false)
                     && self.block_visitor.bv.function_being_analyzed_is_root()
@@ -949,13 +989,14 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                             .session
                             .dcx()
                             .struct_span_warn(span, "[MIRAI] ".to_string() + msg.as_ref());
-                        self.block_visitor.bv.emit_diagnostic(warning);
+                        self.block_visitor.bv.emit_diagnostic(warning, rule);
                     } else {
                         // If we see an unconditional panic inside a standard contract summary,
                         // make it into an unsatisfiable precondition.
                         let precondition = Precondition {
                             condition: Rc::new(abstract_value::FALSE),
                             message: msg,
+                            rule,
                             provenance: None,
                             spans: vec![],
                         };
@@ -973,7 +1014,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                     span,
                                     "[MIRAI] ".to_string() + &msg.to_string(),
                                 );
-                            self.block_visitor.bv.emit_diagnostic(warning);
+                            self.block_visitor
+                                .bv
+                                .emit_diagnostic(warning, DiagnosticRule::UnsatisfiedPostcondition);
                         }
                         return;
                     }
@@ -1001,6 +1044,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                         let precondition = Precondition {
                             condition,
                             message: msg,
+                            rule,
                             provenance: None,
                             spans: if self.block_visitor.bv.def_id.is_local() {
                                 vec![span]
@@ -1022,7 +1066,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                 .session
                                 .dcx()
                                 .struct_span_warn(span, "[MIRAI] ".to_string() + msg.as_ref());
-                            self.block_visitor.bv.emit_diagnostic(warning);
+                            self.block_visitor.bv.emit_diagnostic(warning, rule);
                         } else {
                             // Since the assertion occurs in code that is being used rather than
                             // analyzed, we'll assume that the code is correct and the analyzer
@@ -1088,6 +1132,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                 let precondition = Precondition {
                                     condition,
                                     message: warning,
+                                    rule: DiagnosticRule::UnsatisfiedPrecondition,
                                     provenance: None,
                                     spans: vec![self.block_visitor.bv.current_span],
                                 };
@@ -1099,7 +1144,10 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                         self.block_visitor.bv.current_span,
                                         "[MIRAI] ".to_string() + warning.as_ref(),
                                     );
-                                self.block_visitor.bv.emit_diagnostic(warning);
+                                self.block_visitor.bv.emit_diagnostic(
+                                    warning,
+                                    DiagnosticRule::UnsatisfiedPrecondition,
+                                );
                             }
                         }
                     }
@@ -1371,6 +1419,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             indirect_call_visitor.function_constant_args = &function_constant_args;
             indirect_call_visitor.callee_fun_val = callee.clone();
             indirect_call_visitor.callee_known_name = KnownNames::None;
+            indirect_call_visitor.call_resolution = CallResolution::FunctionPointer;
             indirect_call_visitor.destination = self.destination;
             indirect_call_visitor.target = self.target;
             let summary = indirect_call_visitor.get_function_summary();
@@ -1393,6 +1442,12 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 return;
             }
         }
+        let callee_ty = self.actual_argument_types[0];
+        self.block_visitor.record_unresolved_call(
+            callee_ty.to_string(),
+            None,
+            UnresolvedReason::UnknownCallee,
+        );
         if self
             .block_visitor
             .bv
@@ -1448,7 +1503,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     self.block_visitor.bv.current_span,
                     "[MIRAI] the macro add_tag! expects its argument to be a reference to a non-reference value",
                 );
-                self.block_visitor.bv.emit_diagnostic(warning);
+                self.block_visitor
+                    .bv
+                    .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
             }
 
             // Augment the tags associated at the source with a new tag.
@@ -1569,7 +1626,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                         if checking_presence { "has_tag! " } else { "does_not_have_tag!" },
                     ),
                 );
-                self.block_visitor.bv.emit_diagnostic(warning);
+                self.block_visitor
+                    .bv
+                    .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
             }
 
             // Get the value to check for the presence or absence of the tag
@@ -1910,7 +1969,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 span,
                 "[MIRAI] preconditions should be reached unconditionally",
             );
-            self.block_visitor.bv.emit_diagnostic(warning);
+            self.block_visitor
+                .bv
+                .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
             self.block_visitor.bv.check_for_unconditional_precondition = false;
         }
         let exit_condition = self
@@ -1942,6 +2003,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
             let precondition = Precondition {
                 condition,
                 message,
+                rule: DiagnosticRule::UnsatisfiedPrecondition,
                 provenance: None,
                 spans: vec![self.block_visitor.bv.current_span],
             };
@@ -2649,6 +2711,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     let precondition = Precondition {
                         condition: promotable_entry_condition.logical_not(),
                         message: Rc::from("incomplete analysis of call because of failure to resolve a nested call"),
+                        rule: DiagnosticRule::IncompleteAnalysis,
                         provenance: None,
                         spans: vec![self.block_visitor.bv.current_span.source_callsite()],
                     };
@@ -2667,7 +2730,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     self.block_visitor.bv.current_span,
                     "[MIRAI] the called function could not be completely analyzed",
                 );
-                self.block_visitor.bv.emit_diagnostic(warning);
+                self.block_visitor
+                    .bv
+                    .emit_diagnostic(warning, DiagnosticRule::IncompleteAnalysis);
             }
             let argument_type_hint = if let Some(func) = &self.callee_func_ref {
                 format!(" (foreign fn argument key: {})", func.argument_type_key)
@@ -2914,6 +2979,7 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                     let promoted_precondition = Precondition {
                         condition: promoted_condition,
                         message: precondition.message.clone(),
+                        rule: precondition.rule,
                         provenance: precondition.provenance.clone(),
                         spans: stacked_spans,
                     };
@@ -3170,7 +3236,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                 self.block_visitor.bv.current_span,
                 "[MIRAI] this argument should be a string literal, do not call this function directly",
             );
-            self.block_visitor.bv.emit_diagnostic(warning);
+            self.block_visitor
+                .bv
+                .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
         }
         Rc::from("dummy argument")
     }
@@ -3220,7 +3288,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                 self.block_visitor.bv.current_span,
                                 "[MIRAI] the tag type should be a generic type whose first parameter is a constant of type TagPropagationSet",
                             );
-                            self.block_visitor.bv.emit_diagnostic(warning);
+                            self.block_visitor
+                                .bv
+                                .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
                         }
                         return None;
                     }
@@ -3245,7 +3315,9 @@ impl<'call, 'block, 'analysis, 'compilation, 'tcx>
                                 self.block_visitor.bv.current_span,
                                 "[MIRAI] the first parameter of the tag type should have type TagPropagationSet",
                             );
-                            self.block_visitor.bv.emit_diagnostic(warning);
+                            self.block_visitor
+                                .bv
+                                .emit_diagnostic(warning, DiagnosticRule::AnnotationMisuse);
                         }
                         return None;
                     }
@@ -7,21 +7,28 @@
 use crate::call_graph::CallGraph;
 use crate::constant_domain::ConstantValueCache;
 use crate::crate_visitor::CrateVisitor;
+use crate::diagnostic_rules::RuleConfig;
+use crate::incremental::IncrementalAnalysis;
 use crate::known_names::KnownNamesCache;
 use crate::options::Options;
 use crate::summaries::SummaryCache;
+use crate::suppressions::Suppressions;
 
 use crate::type_visitor::TypeCache;
 use crate::utils;
 use log::info;
 use log_derive::*;
+use rustc_ast::ast;
+use rustc_ast::attr::AttributeExt;
 use rustc_driver::Compilation;
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_interface::interface;
 use rustc_middle::ty::TyCtxt;
+use rustc_span::symbol::{sym, Symbol};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use tempfile::TempDir;
 
@@ -85,6 +92,12 @@ impl rustc_driver::Callbacks for MiraiCallbacks {
             self.options.test_only = true;
         }
         config.crate_cfg.push("mirai".to_string());
+        if self.options.jobs > config.opts.unstable_opts.threads {
+            // Functions are analyzed on the thread pool of the compiler, which only has more than
+            // one thread if the compiler runs in parallel mode. This is the same as passing
+            // -Z threads=<jobs> to the compiler, so a larger number given that way is kept.
+            config.opts.unstable_opts.threads = self.options.jobs;
+        }
         match &config.output_dir {
             None => {
                 self.output_directory = std::env::temp_dir();
@@ -94,6 +107,30 @@ impl rustc_driver::Callbacks for MiraiCallbacks {
         };
     }
 
+    /// Called after the crate root has been parsed.
+    /// Registers mirai as a tool, so that attributes such as #[mirai::allow(overflow)] are
+    /// accepted. Code that is also compiled without MIRAI should put them inside
+    /// #[cfg_attr(mirai, ...)]. A crate that already does this itself would get an error if the
+    /// feature were enabled, or the tool registered, a second time, so the attributes of the
+    /// crate are checked first, after expanding the ones inside cfg_attr.
+    #[logfn(TRACE)]
+    fn after_crate_root_parsing(
+        &mut self,
+        compiler: &interface::Compiler,
+        krate: &mut ast::Crate,
+    ) -> Compilation {
+        let attrs = rustc_expand::config::pre_configure_attrs(&compiler.sess, &krate.attrs);
+        let mut missing_attrs = vec![];
+        if !has_crate_attr(&attrs, sym::feature, sym::register_tool) {
+            missing_attrs.push("feature(register_tool)".to_string());
+        }
+        if !has_crate_attr(&attrs, sym::register_tool, Symbol::intern("mirai")) {
+            missing_attrs.push("register_tool(mirai)".to_string());
+        }
+        rustc_builtin_macros::cmdline_attrs::inject(krate, &compiler.sess.psess, &missing_attrs);
+        Compilation::Continue
+    }
+
     /// Called after the compiler has completed all analysis passes and before it lowers MIR to LLVM IR.
     /// At this point the compiler is ready to tell us all it knows and we can proceed to do abstract
     /// interpretation of all functions that will end up in the compiler output.
@@ -150,6 +187,46 @@ impl MiraiCallbacks {
             "storing summaries for {} at {}/.summary_store.sled",
             self.file_name, summary_store_path
         );
+        let mut summary_cache = SummaryCache::new(summary_store_path);
+        if let Some(path) = &self.options.shared_summary_store {
+            let crate_name = tcx.crate_name(LOCAL_CRATE);
+            if let Err(e) = summary_cache.use_shared_store(
+                Path::new(path),
+                &self.output_directory,
+                crate_name.as_str(),
+            ) {
+                compiler.sess.dcx().warn(format!(
+                    "[MIRAI] not using shared summary store {path}: {e}"
+                ));
+            }
+        }
+        let rule_config = RuleConfig::new(self.options.rule_config.as_deref());
+        let suppressions = Suppressions::collect(tcx);
+        let mut incremental = None;
+        if let Some(directory) = &self.options.incremental {
+            // Call graph output, printed summaries, the unresolved calls report and generated
+            // tests need every function to be analyzed.
+            if !self.test_run
+                && !self.options.print_summaries
+                && self.options.call_graph_config.is_none()
+                && self.options.unresolved_calls.is_none()
+                && self.options.test_skeletons.is_none()
+            {
+                match IncrementalAnalysis::new(
+                    directory,
+                    tcx,
+                    &self.file_name,
+                    &self.options,
+                    &rule_config,
+                    &suppressions,
+                ) {
+                    Ok(analysis) => incremental = Some(analysis),
+                    Err(e) => compiler.sess.dcx().warn(format!(
+                        "[MIRAI] not using incremental analysis directory {directory}: {e}"
+                    )),
+                }
+            }
+        }
         let call_graph_config = self.options.call_graph_config.to_owned();
         let mut crate_visitor = CrateVisitor {
             buffered_diagnostics: Vec::new(),
@@ -160,12 +237,19 @@ impl MiraiCallbacks {
             file_name: self.file_name.as_str(),
             known_names_cache: KnownNamesCache::create_cache(),
             options: &std::mem::take(&mut self.options),
+            rule_config,
             session: &compiler.sess,
             generic_args_cache: HashMap::new(),
-            summary_cache: SummaryCache::new(summary_store_path),
+            incremental,
+            is_worker: false,
+            summary_cache,
+            suppressions,
             tcx,
             test_run: self.test_run,
             type_cache: Rc::new(RefCell::new(TypeCache::new())),
+            unresolved_calls: Vec::new(),
+            undefined_solver_results: BTreeMap::new(),
+            test_skeletons: Vec::new(),
             call_graph: CallGraph::new(call_graph_config, tcx),
         };
         if crate_visitor.options.print_summaries {
@@ -176,3 +260,13 @@ impl MiraiCallbacks {
         crate_visitor.print_summaries();
     }
 }
+
+/// Returns true if one of the given crate attributes has the given name and has the argument in
+/// its list, as in `#![feature(register_tool)]`.
+fn has_crate_attr(attrs: &[ast::Attribute], name: Symbol, argument: Symbol) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.has_name(name))
+        .flat_map(|attr| attr.meta_item_list().unwrap_or_default())
+        .any(|item| item.has_name(argument))
+}
@@ -11,7 +11,7 @@
 
 use std::ffi::OsString;
 use std::ops::Index;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use cargo_metadata::{Package, Target, TargetKind};
@@ -20,14 +20,31 @@ const CARGO_MIRAI_HELP: &str = r#"Static analysis tool for Rust programs
 
 Usage:
     cargo mirai
+    cargo mirai store --help
+    cargo mirai callgraph-diff --help
 "#;
 
 pub fn main() {
-    if std::env::args().any(|a| a == "--help" || a == "-h") {
+    let is_store_command = std::env::args().nth(2).as_deref() == Some("store");
+    let is_callgraph_diff_command = std::env::args().nth(2).as_deref() == Some("callgraph-diff");
+    if !is_store_command
+        && !is_callgraph_diff_command
+        && std::env::args().any(|a| a == "--help" || a == "-h")
+    {
         println!("{CARGO_MIRAI_HELP}");
         return;
     }
     match std::env::args().nth(1).as_ref().map(AsRef::<str>::as_ref) {
+        Some(s) if (s.ends_with("mirai") || s.ends_with("mirai.exe")) && is_store_command => {
+            // Get here for "cargo mirai store ...".
+            call_store();
+        }
+        Some(s)
+            if (s.ends_with("mirai") || s.ends_with("mirai.exe")) && is_callgraph_diff_command =>
+        {
+            // Get here for "cargo mirai callgraph-diff ...".
+            call_callgraph_diff();
+        }
         Some(s) if s.ends_with("mirai") || s.ends_with("mirai.exe") => {
             // Get here for the top level cargo execution, i.e. "cargo mirai".
             if std::env::args().any(|a| a == "--version" || a == "-V") {
@@ -209,7 +226,8 @@ fn call_rustc_or_mirai() {
     }
 }
 
-fn call_mirai() {
+/// Returns the path of the mirai binary, which is installed next to this binary.
+fn mirai_path() -> PathBuf {
     let mut path = std::env::current_exe().expect("current executable path invalid");
     let extension = path.extension().map(|e| e.to_owned());
     path.pop(); // remove the cargo_mirai bit
@@ -217,7 +235,11 @@ fn call_mirai() {
     if let Some(ext) = extension {
         path.set_extension(ext);
     }
-    let mut cmd = Command::new(path);
+    path
+}
+
+fn call_mirai() {
+    let mut cmd = Command::new(mirai_path());
     cmd.args(std::env::args().skip(2));
     let exit_status = cmd
         .spawn()
@@ -230,6 +252,62 @@ fn call_mirai() {
     }
 }
 
+/// Forwards "cargo mirai store [--store <path>] <command> [<argument>]" to the mirai binary,
+/// which can decode the summaries in the store.
+fn call_store() {
+    let mut store_path = None;
+    let mut store_args = vec![];
+    let mut args = std::env::args().skip(3);
+    while let Some(arg) = args.next() {
+        if arg == "--store" {
+            store_path = args.next();
+        } else if let Some(path) = arg.strip_prefix("--store=") {
+            store_path = Some(path.to_owned());
+        } else if arg == "--help" || arg == "-h" {
+            store_args = vec!["help".to_owned()];
+            break;
+        } else {
+            store_args.push(arg);
+        }
+    }
+    let store_path = store_path.unwrap_or_else(|| {
+        // This is where MIRAI puts the store when MIRAI_SHARE_PERSISTENT_STORE is set.
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .no_deps()
+            .exec()
+            .unwrap_or_else(|_| {
+                eprintln!("Could not obtain Cargo metadata; use --store to specify the store");
+                std::process::exit(1);
+            });
+        metadata
+            .target_directory
+            .join("debug")
+            .join("deps")
+            .to_string()
+    });
+    let exit_status = Command::new(mirai_path())
+        .arg("store")
+        .arg(store_path)
+        .args(store_args)
+        .spawn()
+        .expect("could not run mirai")
+        .wait()
+        .expect("failed to wait for mirai");
+    std::process::exit(exit_status.code().unwrap_or(-1))
+}
+
+/// Forwards "cargo mirai callgraph-diff <arguments>" to the mirai binary.
+fn call_callgraph_diff() {
+    let exit_status = Command::new(mirai_path())
+        .arg("callgraph-diff")
+        .args(std::env::args().skip(3))
+        .spawn()
+        .expect("could not run mirai")
+        .wait()
+        .expect("failed to wait for mirai");
+    std::process::exit(exit_status.code().unwrap_or(-1))
+}
+
 fn call_rustc() {
     let mut args = std::env::args_os().skip(1);
     // The rustc to use is passed by Cargo as the first argument to RUSTC_WRAPPER
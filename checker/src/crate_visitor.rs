@@ -9,8 +9,9 @@
 
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter, Result};
+use std::path::Path;
 use std::rc::Rc;
 use std::time::Instant;
 
@@ -18,21 +19,31 @@ use log::*;
 use log_derive::{logfn, logfn_inputs};
 
 use mirai_annotations::*;
+use rustc_data_structures::sync::{par_map, IntoDynSyncSend};
 use rustc_errors::Diag;
-use rustc_hir::def_id::{DefId, DefIndex};
+use rustc_hir::def_id::{DefId, DefIndex, LOCAL_CRATE};
 use rustc_middle::mir;
 use rustc_middle::ty::{GenericArgsRef, TyCtxt};
+use rustc_session::config::CrateType;
 use rustc_session::Session;
 
+use crate::baseline::{Baseline, Fingerprint};
 use crate::body_visitor::BodyVisitor;
 use crate::call_graph::CallGraph;
 use crate::constant_domain::ConstantValueCache;
+use crate::diagnostic_rules::{DiagnosticRule, RuleConfig};
 use crate::expected_errors;
+use crate::incremental::IncrementalAnalysis;
 use crate::known_names::KnownNamesCache;
-use crate::options::Options;
+use crate::options::{DiagnosticsOutput, Options};
+use crate::parallel::{self, PartitionResult};
+use crate::sarif::SarifLog;
 use crate::summaries::SummaryCache;
+use crate::suppressions::Suppressions;
 use crate::tag_domain::Tag;
+use crate::test_skeletons::{self, TestSkeleton};
 use crate::type_visitor::TypeCache;
+use crate::unresolved_calls::{UnresolvedCall, UnresolvedCallsReport};
 use crate::utils;
 
 /// A visitor that takes information gathered by the Rust compiler when compiling a particular
@@ -49,13 +60,27 @@ pub struct CrateVisitor<'compilation, 'tcx> {
     pub diagnostics_for: HashMap<DefId, Vec<Diag<'compilation, ()>>>,
     pub file_name: &'compilation str,
     pub generic_args_cache: HashMap<DefId, GenericArgsRef<'tcx>>,
+    pub incremental: Option<IncrementalAnalysis>,
+    /// True if this visitor analyzes part of the crate on a worker thread.
+    pub is_worker: bool,
     pub known_names_cache: KnownNamesCache,
     pub options: &'compilation Options,
+    pub rule_config: RuleConfig,
     pub session: &'compilation Session,
     pub summary_cache: SummaryCache<'tcx>,
+    pub suppressions: Suppressions,
     pub tcx: TyCtxt<'tcx>,
     pub type_cache: Rc<RefCell<TypeCache<'tcx>>>,
     pub test_run: bool,
+    /// The calls that could not be analyzed with a summary of the callee, if these are to be
+    /// reported.
+    pub unresolved_calls: Vec<UnresolvedCall>,
+    /// The number of solver queries with an undefined result, by the summary key of the
+    /// function that made them, if statistics are to be printed.
+    pub undefined_solver_results: BTreeMap<String, usize>,
+    /// The tests for the possible failures for which the solver found parameter values, if
+    /// these are to be written.
+    pub test_skeletons: Vec<TestSkeleton>,
     pub call_graph: CallGraph<'tcx>,
 }
 
@@ -82,6 +107,7 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
 
         // Analyze all functions that are whitelisted or public
         let building_standard_summaries = std::env::var("MIRAI_START_FRESH").is_ok();
+        let mut roots = vec![];
         for local_def_id in self.tcx.hir().body_owners() {
             let def_id = local_def_id.to_def_id();
             let name = utils::summary_key_str(self.tcx, def_id);
@@ -122,14 +148,203 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             } else {
                 info!("analyzing function {}", name);
             }
+            roots.push(def_id);
+        }
+        if self.options.jobs > 1 && !self.test_run {
+            let sequential_option = if self.incremental.is_some() {
+                Some("--incremental")
+            } else if self.options.print_summaries {
+                Some("--print_summaries")
+            } else if self.options.call_graph_config.is_some() {
+                Some("--call_graph_config")
+            } else {
+                None
+            };
+            if let Some(option) = sequential_option {
+                self.session
+                    .dcx()
+                    .warn(format!("[MIRAI] --jobs is ignored together with {option}"));
+            }
+        }
+        if self.incremental.is_some() {
+            self.analyze_changed_bodies(roots, start_instant);
+        } else if self.options.jobs > 1
+            && !self.test_run
+            && !self.options.print_summaries
+            && self.options.call_graph_config.is_none()
+        {
+            self.analyze_in_parallel(roots, start_instant);
+        } else {
+            self.analyze_roots(roots, start_instant);
+        }
+        self.report_suppression_problems();
+        self.emit_or_check_diagnostics();
+        if let Some(path) = &self.options.unresolved_calls {
+            self.write_unresolved_calls(path);
+        }
+        if let Some(dir) = &self.options.test_skeletons {
+            self.write_test_skeletons(dir);
+        }
+    }
+
+    /// Analyzes the given roots, one after the other, until the time allowed for the crate runs out.
+    /// Returns false if that happens.
+    pub fn analyze_roots(&mut self, roots: Vec<DefId>, start_instant: Instant) -> bool {
+        for def_id in roots {
             self.call_graph.add_croot(def_id);
             self.analyze_body(def_id);
             if start_instant.elapsed().as_secs() > self.options.max_analysis_time_for_crate {
                 info!("exceeded total time allowed for crate analysis");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Partitions the given roots into groups that do not call the same local functions and
+    /// analyzes the groups on the threads of the compiler's thread pool, each with its own caches
+    /// and solver instances. The diagnostics of the groups are then merged in the order of the
+    /// roots. The summaries that are persisted belong to the roots, each of which is analyzed
+    /// by exactly one thread, so the summary store does not depend on the order of the threads.
+    fn analyze_in_parallel(&mut self, roots: Vec<DefId>, start_instant: Instant) {
+        let tcx = self.tcx;
+        let session = self.session;
+        let options = self.options;
+        let file_name = self.file_name;
+        let partitions = parallel::partition_roots(tcx, &roots, options.jobs);
+        info!(
+            "analyzing {} functions in {} partitions",
+            roots.len(),
+            partitions.len()
+        );
+        let stores = IntoDynSyncSend(self.summary_cache.stores());
+        let results: Vec<PartitionResult> = par_map(partitions, |partition| {
+            parallel::analyze_partition(
+                tcx,
+                session,
+                options,
+                file_name,
+                stores.0.clone(),
+                partition,
+                start_instant,
+            )
+        });
+        let dcx = session.dcx();
+        for result in results.into_iter() {
+            for (def_id, detached_diagnostics) in result.diagnostics.into_iter() {
+                let diagnostics = detached_diagnostics
+                    .iter()
+                    .filter_map(|detached| self.classify_diagnostic(detached.to_diag(dcx)))
+                    .collect();
+                self.diagnostics_for.insert(def_id, diagnostics);
+            }
+            self.unresolved_calls.extend(result.unresolved_calls);
+            for (function, count) in result.undefined_solver_results {
+                *self.undefined_solver_results.entry(function).or_default() += count;
+            }
+            self.test_skeletons.extend(result.test_skeletons);
+        }
+    }
+
+    /// Analyzes the given roots, except those whose previous results are still valid, in which case
+    /// the previous diagnostics are reused. Roots that are affected only by changes to the bodies
+    /// of other roots are analyzed last, once it is known whether the summaries of those roots
+    /// have changed. The results of this run are recorded for the next one.
+    fn analyze_changed_bodies(&mut self, roots: Vec<DefId>, start_instant: Instant) {
+        let tcx = self.tcx;
+        let incremental = self.incremental.as_mut().expect("incremental analysis");
+        incremental.select_roots(tcx, &roots);
+        let mut deferred = vec![];
+        let mut timed_out = false;
+        for def_id in roots {
+            let incremental = self.incremental.as_mut().expect("incremental analysis");
+            if incremental.can_reuse(tcx, def_id, true) {
+                deferred.push(def_id);
+                continue;
+            }
+            self.call_graph.add_croot(def_id);
+            self.analyze_body(def_id);
+            if start_instant.elapsed().as_secs() > self.options.max_analysis_time_for_crate {
+                info!("exceeded total time allowed for crate analysis");
+                timed_out = true;
                 break;
             }
         }
-        self.emit_or_check_diagnostics();
+        // Analyzing a root can change its summary, which can in turn invalidate the results
+        // of other roots, so iterate until no more roots need to be analyzed.
+        let mut analyzed_some = true;
+        while analyzed_some && !timed_out {
+            analyzed_some = false;
+            let mut still_deferred = vec![];
+            for def_id in deferred {
+                let incremental = self.incremental.as_mut().expect("incremental analysis");
+                if timed_out || incremental.can_reuse(tcx, def_id, false) {
+                    still_deferred.push(def_id);
+                    continue;
+                }
+                self.call_graph.add_croot(def_id);
+                self.analyze_body(def_id);
+                analyzed_some = true;
+                if start_instant.elapsed().as_secs() > self.options.max_analysis_time_for_crate {
+                    info!("exceeded total time allowed for crate analysis");
+                    timed_out = true;
+                }
+            }
+            deferred = still_deferred;
+        }
+        let mut incremental = self.incremental.take().expect("incremental analysis");
+        for def_id in deferred {
+            // After a time out, a root that was deferred is only reusable if none of the roots
+            // that it depends on has been left unanalyzed or has had its summary changed.
+            if timed_out && !incremental.can_reuse(tcx, def_id, false) {
+                continue;
+            }
+            let diagnostics = incremental
+                .cached_diagnostics(tcx, def_id, self.session.dcx())
+                .into_iter()
+                .filter_map(|diag| self.classify_diagnostic(diag))
+                .collect();
+            self.diagnostics_for.insert(def_id, diagnostics);
+        }
+        if let Err(e) = incremental.write(tcx) {
+            self.session.dcx().warn(format!(
+                "[MIRAI] could not write incremental analysis index: {e}"
+            ));
+        }
+    }
+
+    /// Applies the suppressions and the configured rule severities to a diagnostic that has
+    /// been found while analyzing a function body, returning None if the diagnostic is dropped.
+    /// Otherwise the generic [MIRAI] tag is replaced with the code of the rule the diagnostic
+    /// belongs to.
+    /// A worker leaves this to the visitor that merges its diagnostics.
+    pub fn classify_diagnostic(
+        &mut self,
+        diag: Diag<'compilation, ()>,
+    ) -> Option<Diag<'compilation, ()>> {
+        if self.is_worker {
+            return Some(diag);
+        }
+        let rule = DiagnosticRule::for_diagnostic(&diag);
+        if self.suppressions.suppresses(rule, &diag.span) {
+            diag.cancel();
+            return None;
+        }
+        self.rule_config.apply(rule, diag, self.session.dcx())
+    }
+
+    /// Adds diagnostics for suppression attributes and comments that name unknown rules, or
+    /// that did not suppress any diagnostic of the functions that were analyzed.
+    fn report_suppression_problems(&mut self) {
+        let analyzed_functions: Vec<DefId> = self.diagnostics_for.keys().copied().collect();
+        for (span, owner, message) in self.suppressions.problems(&analyzed_functions) {
+            let mut warning = self.session.dcx().struct_span_warn(span, message);
+            let rule = DiagnosticRule::AnnotationMisuse;
+            rule.attach_to(&mut warning);
+            if let Some(warning) = self.rule_config.apply(rule, warning, self.session.dcx()) {
+                self.diagnostics_for.entry(owner).or_default().push(warning);
+            }
+        }
     }
 
     /// Use compilation options to determine a list of functions to analyze.
@@ -182,6 +397,9 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
         );
         // Analysis local foreign contracts are not summarized and cached on demand, so we need to do it here.
         let summary = body_visitor.visit_body(&[]);
+        if let Some(incremental) = &mut self.incremental {
+            incremental.root_analyzed(self.tcx, def_id, &summary);
+        }
         let kind = self.tcx.def_kind(def_id);
         if matches!(kind, rustc_hir::def::DefKind::Static { .. })
             || utils::is_foreign_contract(self.tcx, def_id)
@@ -253,6 +471,12 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
                 }
             }
             print!("{}, analyzed, {}", self.file_name, num_diags);
+            for (function, count) in self.undefined_solver_results.iter() {
+                print!(
+                    "\n{}, undefined solver results, {}, {}",
+                    self.file_name, function, count
+                );
+            }
         } else if self.test_run {
             let mut expected_errors = expected_errors::ExpectedErrors::new(self.file_name);
             let mut diags = vec![];
@@ -271,12 +495,15 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
             }
         } else {
             let mut diagnostics = vec![];
-            for (_, dbs) in self.diagnostics_for.drain() {
+            for (def_id, dbs) in self.diagnostics_for.drain() {
                 for db in dbs.into_iter() {
-                    diagnostics.push(db);
+                    diagnostics.push((def_id, db));
                 }
             }
-            fn compare_diagnostics<'a>(x: &Diag<'a, ()>, y: &Diag<'a, ()>) -> Ordering {
+            fn compare_diagnostics<'a>(
+                (_, x): &(DefId, Diag<'a, ()>),
+                (_, y): &(DefId, Diag<'a, ()>),
+            ) -> Ordering {
                 if x.span.primary_spans().lt(y.span.primary_spans()) {
                     Ordering::Less
                 } else if x.span.primary_spans().gt(y.span.primary_spans()) {
@@ -286,12 +513,142 @@ impl<'compilation> CrateVisitor<'compilation, '_> {
                 }
             }
             diagnostics.sort_by(compare_diagnostics);
-            for d in diagnostics.into_iter() {
+            if let Some(path) = &self.options.baseline {
+                diagnostics = self.apply_baseline(path, diagnostics);
+            }
+            if let Some(DiagnosticsOutput::Sarif(path)) = &self.options.diagnostics_output {
+                self.write_sarif_log(path, &diagnostics);
+            }
+            for (_, d) in diagnostics.into_iter() {
                 d.emit()
             }
         }
     }
 
+    /// Cancels the diagnostics that are recorded in the baseline file at the given path and
+    /// returns the others. If so requested, the file is then updated to record exactly the
+    /// given diagnostics for the current crate.
+    fn apply_baseline(
+        &mut self,
+        path: &str,
+        diagnostics: Vec<(DefId, Diag<'compilation, ()>)>,
+    ) -> Vec<(DefId, Diag<'compilation, ()>)> {
+        let session = self.session;
+        let path = Path::new(path);
+        if !path.exists() && !self.options.update_baseline {
+            session.dcx().warn(format!(
+                "[MIRAI] baseline {} does not exist, use --update_baseline to create it",
+                path.display()
+            ));
+            return diagnostics;
+        }
+        let crate_name = self.output_crate_name();
+        let mut fingerprints = Vec::with_capacity(diagnostics.len());
+        for (def_id, diag) in diagnostics.iter() {
+            let summary_key = self.summary_cache.get_summary_key_for(*def_id, self.tcx);
+            fingerprints.push(Fingerprint::new(summary_key, diag));
+        }
+        let known_findings = if self.options.update_baseline {
+            Baseline::update_file(path, &crate_name, &fingerprints).unwrap_or_else(|e| {
+                session.dcx().warn(format!(
+                    "[MIRAI] could not update baseline {}: {e}",
+                    path.display()
+                ));
+                vec![]
+            })
+        } else {
+            match Baseline::read(path) {
+                Ok(baseline) => baseline.known_findings(&crate_name, &fingerprints),
+                Err(e) => {
+                    session.dcx().warn(format!(
+                        "[MIRAI] could not read baseline {}: {e}",
+                        path.display()
+                    ));
+                    vec![]
+                }
+            }
+        };
+        let mut new_diagnostics = vec![];
+        for (i, (def_id, diag)) in diagnostics.into_iter().enumerate() {
+            if known_findings.get(i).copied().unwrap_or(false) {
+                diag.cancel();
+            } else {
+                new_diagnostics.push((def_id, diag));
+            }
+        }
+        new_diagnostics
+    }
+
+    /// Returns the name under which the results of the current crate are recorded in files
+    /// that are shared by the crates of a workspace.
+    fn output_crate_name(&self) -> String {
+        // A package can have a library and a binary with the same name.
+        let mut crate_name = self.tcx.crate_name(LOCAL_CRATE).to_string();
+        if self.tcx.crate_types().contains(&CrateType::Executable) {
+            crate_name.push_str(" (bin)");
+        }
+        crate_name
+    }
+
+    /// Writes the given diagnostics, along with the summary keys of the functions in which
+    /// they were found, to the run of the current crate in the SARIF log at the given path,
+    /// keeping the runs of other crates.
+    fn write_sarif_log(&mut self, path: &str, diagnostics: &[(DefId, Diag<'compilation, ()>)]) {
+        let session = self.session;
+        let crate_name = self.output_crate_name();
+        let mut log = SarifLog::new(&crate_name);
+        for (def_id, diag) in diagnostics.iter() {
+            let summary_key = self.summary_cache.get_summary_key_for(*def_id, self.tcx);
+            log.add_result(diag, summary_key, session.source_map());
+        }
+        if let Err(e) =
+            utils::update_locked_file(Path::new(path), |previous_log| log.to_json(previous_log))
+        {
+            session.dcx().warn(format!(
+                "[MIRAI] could not write SARIF output to {path}: {e}"
+            ));
+        }
+    }
+
+    /// Records the unresolved calls of the current crate in the report at the given path,
+    /// keeping the entries of other crates.
+    fn write_unresolved_calls(&mut self, path: &str) {
+        let session = self.session;
+        let crate_name = self.output_crate_name();
+        let calls = std::mem::take(&mut self.unresolved_calls);
+        let result = utils::update_locked_file(Path::new(path), |json| {
+            let mut report = match json {
+                Some(json) => UnresolvedCallsReport::from_json(json).unwrap_or_else(|e| {
+                    session.dcx().warn(format!(
+                        "[MIRAI] could not read unresolved calls report {path}: {e}"
+                    ));
+                    UnresolvedCallsReport::default()
+                }),
+                None => UnresolvedCallsReport::default(),
+            };
+            report.set_calls(crate_name, calls);
+            report.to_json()
+        });
+        if let Err(e) = result {
+            session.dcx().warn(format!(
+                "[MIRAI] could not write unresolved calls report {path}: {e}"
+            ));
+        }
+    }
+
+    /// Writes the tests for the possible failures found in the current crate to a file in the
+    /// given directory.
+    fn write_test_skeletons(&mut self, dir: &str) {
+        let crate_name = self.tcx.crate_name(LOCAL_CRATE).to_string();
+        let skeletons = std::mem::take(&mut self.test_skeletons);
+        if let Err(e) = test_skeletons::write_test_skeletons(Path::new(dir), &crate_name, skeletons)
+        {
+            self.session
+                .dcx()
+                .warn(format!("[MIRAI] could not write tests to {dir}: {e}"));
+        }
+    }
+
     pub fn print_summaries(&mut self) {
         if !self.options.print_summaries {
             return;
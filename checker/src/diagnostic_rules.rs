@@ -0,0 +1,246 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use mirai_annotations::*;
+use rustc_errors::{Diag, DiagArgValue, DiagCtxtHandle, Level};
+
+use crate::sarif;
+
+/// The categories into which MIRAI diagnostics are classified. Each category has a stable
+/// code, such as MIRAI0001, that appears in the output instead of the generic [MIRAI] tag,
+/// and a name that can be used to refer to the category in configuration files and attributes.
+/// Codes must never be reused or renumbered, since users record them in configuration files
+/// and baselines.
+/// The rule of a diagnostic is decided where the diagnostic is created, by the kind of check that
+/// fails, and is carried along in the diagnostic as an argument named RULE_ARG. Preconditions
+/// carry the rule of the check they were inferred from, so that a promoted overflow check is
+/// still reported as an overflow at the call site.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum DiagnosticRule {
+    /// An arithmetic operation or shift might overflow.
+    Overflow,
+    /// A division or remainder operation might have a zero divisor.
+    DivisionByZero,
+    /// An index, slice range or pointer offset might be outside of the bounds of a collection.
+    IndexOutOfBounds,
+    /// A condition given to verify! might be false.
+    FalseVerificationCondition,
+    /// The precondition of a called function might not be satisfied.
+    UnsatisfiedPrecondition,
+    /// A post condition or invariant might not be satisfied.
+    UnsatisfiedPostcondition,
+    /// Something prevented the analysis of a function from completing.
+    IncompleteAnalysis,
+    /// A value might (not) have a tag that it must not (must) have.
+    TagCheck,
+    /// Code that should be marked as unreachable, or code that is reachable but should not be.
+    Unreachable,
+    /// Memory is used in a way that might be unsafe.
+    MemorySafety,
+    /// An assumption is provably true or provably false.
+    Assumption,
+    /// A MIRAI annotation is used incorrectly.
+    AnnotationMisuse,
+    /// Any other condition that might cause a panic, such as a failed assertion or unwrap.
+    Panic,
+}
+
+/// The name of the diagnostic argument that holds the code of the rule a diagnostic belongs to.
+/// It is not part of the message.
+pub const RULE_ARG: &str = "mirai_rule";
+
+const ALL_RULES: &[DiagnosticRule] = &[
+    DiagnosticRule::Overflow,
+    DiagnosticRule::DivisionByZero,
+    DiagnosticRule::IndexOutOfBounds,
+    DiagnosticRule::FalseVerificationCondition,
+    DiagnosticRule::UnsatisfiedPrecondition,
+    DiagnosticRule::UnsatisfiedPostcondition,
+    DiagnosticRule::IncompleteAnalysis,
+    DiagnosticRule::TagCheck,
+    DiagnosticRule::Unreachable,
+    DiagnosticRule::MemorySafety,
+    DiagnosticRule::Assumption,
+    DiagnosticRule::AnnotationMisuse,
+    DiagnosticRule::Panic,
+];
+
+impl DiagnosticRule {
+    /// The stable code of this rule.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DiagnosticRule::Overflow => "MIRAI0001",
+            DiagnosticRule::DivisionByZero => "MIRAI0002",
+            DiagnosticRule::IndexOutOfBounds => "MIRAI0003",
+            DiagnosticRule::FalseVerificationCondition => "MIRAI0004",
+            DiagnosticRule::UnsatisfiedPrecondition => "MIRAI0005",
+            DiagnosticRule::UnsatisfiedPostcondition => "MIRAI0006",
+            DiagnosticRule::IncompleteAnalysis => "MIRAI0007",
+            DiagnosticRule::TagCheck => "MIRAI0008",
+            DiagnosticRule::Unreachable => "MIRAI0009",
+            DiagnosticRule::MemorySafety => "MIRAI0010",
+            DiagnosticRule::Assumption => "MIRAI0011",
+            DiagnosticRule::AnnotationMisuse => "MIRAI0012",
+            DiagnosticRule::Panic => "MIRAI0013",
+        }
+    }
+
+    /// The name of this rule, for use in configuration files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagnosticRule::Overflow => "overflow",
+            DiagnosticRule::DivisionByZero => "division_by_zero",
+            DiagnosticRule::IndexOutOfBounds => "index_out_of_bounds",
+            DiagnosticRule::FalseVerificationCondition => "false_verification_condition",
+            DiagnosticRule::UnsatisfiedPrecondition => "unsatisfied_precondition",
+            DiagnosticRule::UnsatisfiedPostcondition => "unsatisfied_postcondition",
+            DiagnosticRule::IncompleteAnalysis => "incomplete_analysis",
+            DiagnosticRule::TagCheck => "tag_check",
+            DiagnosticRule::Unreachable => "unreachable",
+            DiagnosticRule::MemorySafety => "memory_safety",
+            DiagnosticRule::Assumption => "assumption",
+            DiagnosticRule::AnnotationMisuse => "annotation_misuse",
+            DiagnosticRule::Panic => "panic",
+        }
+    }
+
+    /// Looks up a rule by its code or by its name.
+    pub fn from_code_or_name(s: &str) -> Option<DiagnosticRule> {
+        ALL_RULES
+            .iter()
+            .find(|rule| rule.code() == s || rule.name() == s)
+            .copied()
+    }
+
+    /// Returns the rule that was attached to the given diagnostic when it was emitted.
+    pub fn for_diagnostic(diag: &Diag<'_, ()>) -> DiagnosticRule {
+        let rule = match diag.args.get(RULE_ARG) {
+            Some(DiagArgValue::Str(code)) => DiagnosticRule::from_code_or_name(code),
+            _ => None,
+        };
+        rule.unwrap_or_else(|| {
+            assume_unreachable!(
+                "diagnostic without a rule: {}",
+                sarif::message_text(&diag.messages[0].0)
+            )
+        })
+    }
+
+    /// Records in the given diagnostic that it belongs to this rule.
+    pub fn attach_to(self, diag: &mut Diag<'_, ()>) {
+        diag.arg(RULE_ARG, self.code());
+    }
+}
+
+/// How diagnostics that belong to a rule are reported.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The diagnostics are silently dropped.
+    Allow,
+    /// The diagnostics are reported as warnings.
+    #[default]
+    Warn,
+    /// The diagnostics are reported as errors, which causes the compilation to fail.
+    Deny,
+}
+
+/// Maps rules to severities. Rules that are not mentioned are reported as warnings.
+#[derive(Debug, Default)]
+pub struct RuleConfig {
+    severities: HashMap<DiagnosticRule, Severity>,
+}
+
+impl RuleConfig {
+    /// Reads a rule configuration from a JSON file that maps rule codes or names to
+    /// "allow", "warn" or "deny". For example: {"MIRAI0001": "deny", "incomplete_analysis": "allow"}.
+    pub fn new(path_to_config: Option<&str>) -> RuleConfig {
+        match path_to_config {
+            Some(path) => match Self::parse_config(Path::new(path)) {
+                Ok(config) => config,
+                Err(e) => unrecoverable!("Failed to read rule config: {:?}", e),
+            },
+            None => RuleConfig::default(),
+        }
+    }
+
+    fn parse_config(path_to_config: &Path) -> Result<RuleConfig, String> {
+        let config_str = fs::read_to_string(path_to_config).map_err(|e| e.to_string())?;
+        let raw: HashMap<String, Severity> =
+            serde_json::from_str(&config_str).map_err(|e| e.to_string())?;
+        let mut severities = HashMap::new();
+        for (key, severity) in raw.into_iter() {
+            match DiagnosticRule::from_code_or_name(&key) {
+                Some(rule) => {
+                    severities.insert(rule, severity);
+                }
+                None => return Err(format!("unknown rule {key}")),
+            }
+        }
+        Ok(RuleConfig { severities })
+    }
+
+    pub fn severity_for(&self, rule: DiagnosticRule) -> Severity {
+        self.severities.get(&rule).copied().unwrap_or_default()
+    }
+
+    /// Replaces the generic [MIRAI] tag of the given diagnostic, which belongs to the given rule,
+    /// with the code of the rule and applies the severity configured for the rule.
+    /// Returns None if the diagnostic has been cancelled because the rule is allowed.
+    pub fn apply<'a>(
+        &self,
+        rule: DiagnosticRule,
+        mut diag: Diag<'a, ()>,
+        dcx: DiagCtxtHandle<'a>,
+    ) -> Option<Diag<'a, ()>> {
+        let message = sarif::message_text(&diag.messages[0].0).to_string();
+        let text = message.strip_prefix("[MIRAI] ").unwrap_or(&message);
+        let tagged_message = format!("[{}] {text}", rule.code());
+        match self.severity_for(rule) {
+            Severity::Allow => {
+                diag.cancel();
+                None
+            }
+            Severity::Warn => {
+                diag.primary_message(tagged_message);
+                Some(diag)
+            }
+            Severity::Deny => Some(upgrade_to_error(diag, tagged_message, dcx)),
+        }
+    }
+}
+
+impl Hash for RuleConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut severities: Vec<(&DiagnosticRule, &Severity)> = self.severities.iter().collect();
+        severities.sort_by_key(|(rule, _)| **rule);
+        severities.hash(state);
+    }
+}
+
+/// Returns an error with the given message and with the same spans, notes and arguments as the
+/// given warning, which is cancelled.
+fn upgrade_to_error<'a>(
+    warning: Diag<'a, ()>,
+    message: String,
+    dcx: DiagCtxtHandle<'a>,
+) -> Diag<'a, ()> {
+    let mut error = Diag::<()>::new(dcx, Level::Error, message);
+    error.span(warning.span.clone());
+    // The children keep their own levels, so notes stay notes and help stays help.
+    error.children = warning.children.clone();
+    // The arguments include RULE_ARG and PROVENANCE_ARG, which SARIF output and
+    // DiagnosticRule::for_diagnostic rely on.
+    error.args = warning.args.clone();
+    warning.cancel();
+    error
+}
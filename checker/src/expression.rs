@@ -7,8 +7,8 @@ use log_derive::*;
 use mirai_annotations::*;
 use rustc_middle::ty::{FloatTy, IntTy, Ty, TyCtxt, TyKind, UintTy};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::fmt::{Debug, Formatter, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Debug, Display, Formatter, Result};
 use std::rc::Rc;
 
 use crate::abstract_value::{AbstractValue, AbstractValueTrait};
@@ -624,7 +624,336 @@ impl Debug for Expression {
     }
 }
 
+/// Renders an expression, a path or an abstract value as Rust like source text, for people to
+/// read. Operators are infix and operands are only parenthesized where the precedence of the
+/// operators requires it, the types of variables are left out and parameters are named by
+/// parameter_names, where parameter_names[0] is the name of the first parameter. Parameters
+/// without a usable name are rendered as param_1, param_2 and so on, which is also what the
+/// Display implementations of these types do.
+pub struct WithParameterNames<'a, T: ?Sized> {
+    pub value: &'a T,
+    pub parameter_names: &'a [String],
+}
+
+impl<'a, T: ?Sized> WithParameterNames<'a, T> {
+    pub fn new(value: &'a T, parameter_names: &'a [String]) -> Self {
+        WithParameterNames {
+            value,
+            parameter_names,
+        }
+    }
+
+    /// Renders the given value, which is part of this one, with the same parameter names.
+    pub fn part(&self, value: &'a AbstractValue) -> WithParameterNames<'a, AbstractValue> {
+        WithParameterNames::new(value, self.parameter_names)
+    }
+
+    /// Renders the given path, which is part of this value, with the same parameter names.
+    pub fn path(&self, path: &'a Path) -> WithParameterNames<'a, Path> {
+        WithParameterNames::new(path, self.parameter_names)
+    }
+
+    /// Renders the given value, which is part of this one, and parenthesizes it if the operator at
+    /// its root binds less tightly than the given precedence.
+    pub fn fmt_operand(
+        &self,
+        f: &mut Formatter<'_>,
+        operand: &'a AbstractValue,
+        precedence: u8,
+    ) -> Result {
+        if operand.expression.precedence() < precedence {
+            f.write_fmt(format_args!("({})", self.part(operand)))
+        } else {
+            self.part(operand).fmt(f)
+        }
+    }
+
+    /// Renders the binary operation with the given operands, which groups from left to right,
+    /// unless it is a comparison, which does not group at all.
+    fn fmt_binary(
+        &self,
+        f: &mut Formatter<'_>,
+        left: &'a AbstractValue,
+        operator: &str,
+        right: &'a AbstractValue,
+        precedence: u8,
+    ) -> Result {
+        let left_precedence = if precedence == COMPARISON_PRECEDENCE {
+            precedence + 1
+        } else {
+            precedence
+        };
+        self.fmt_operand(f, left, left_precedence)?;
+        f.write_fmt(format_args!(" {operator} "))?;
+        self.fmt_operand(f, right, precedence + 1)
+    }
+}
+
+/// The precedence of an operand that never needs parentheses, such as a variable or a call.
+pub const ATOM_PRECEDENCE: u8 = 15;
+/// The precedence of the prefix operators, such as ! and unary -.
+pub const UNARY_PRECEDENCE: u8 = 14;
+/// The precedence of a cast with as.
+pub const CAST_PRECEDENCE: u8 = 13;
+const COMPARISON_PRECEDENCE: u8 = 6;
+
+impl Display for WithParameterNames<'_, Expression> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.value {
+            Expression::Add { left, right } => self.fmt_binary(f, left, "+", right, 11),
+            Expression::AddOverflows { left, right, .. } => {
+                f.write_str("overflows(")?;
+                self.fmt_binary(f, left, "+", right, 11)?;
+                f.write_str(")")
+            }
+            Expression::And { left, right } => self.fmt_binary(f, left, "&&", right, 5),
+            Expression::BitAnd { left, right } => self.fmt_binary(f, left, "&", right, 9),
+            Expression::BitNot { operand, .. } | Expression::LogicalNot { operand } => {
+                f.write_str("!")?;
+                self.fmt_operand(f, operand, UNARY_PRECEDENCE)
+            }
+            Expression::BitOr { left, right } => self.fmt_binary(f, left, "|", right, 7),
+            Expression::BitXor { left, right } => self.fmt_binary(f, left, "^", right, 8),
+            Expression::Cast {
+                operand,
+                target_type,
+            } => {
+                self.fmt_operand(f, operand, CAST_PRECEDENCE)?;
+                f.write_fmt(format_args!(" as {}", target_type.as_rust_str()))
+            }
+            Expression::CompileTimeConstant(c) => match c {
+                ConstantDomain::Function(func_ref) => f.write_str(&func_ref.summary_cache_key),
+                ConstantDomain::I128(val) => f.write_fmt(format_args!("{val}")),
+                ConstantDomain::U128(val) => f.write_fmt(format_args!("{val}")),
+                _ => c.fmt(f),
+            },
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => f.write_fmt(format_args!(
+                "if {} {{ {} }} else {{ {} }}",
+                self.part(condition),
+                self.part(consequent),
+                self.part(alternate),
+            )),
+            Expression::Div { left, right } => self.fmt_binary(f, left, "/", right, 12),
+            Expression::Equals { left, right } => {
+                self.fmt_binary(f, left, "==", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::GreaterOrEqual { left, right } => {
+                self.fmt_binary(f, left, ">=", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::GreaterThan { left, right } => {
+                self.fmt_binary(f, left, ">", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::HeapBlockLayout {
+                length,
+                alignment,
+                source,
+            } => f.write_fmt(format_args!(
+                "layout(length: {}; alignment: {}; source: {source:?})",
+                self.part(length),
+                self.part(alignment),
+            )),
+            Expression::IntrinsicBinary { left, right, name } => {
+                self.fmt_operand(f, left, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!(".{name:?}({})", self.part(right)))
+            }
+            Expression::IntrinsicBitVectorUnary {
+                operand,
+                bit_length,
+                name,
+            } => {
+                f.write_str("(")?;
+                self.fmt_operand(f, operand, CAST_PRECEDENCE)?;
+                f.write_fmt(format_args!(" as (i|u){bit_length}).{name:?}()"))
+            }
+            Expression::IntrinsicFloatingPointUnary { operand, name } => {
+                self.fmt_operand(f, operand, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!(".{name:?}()"))
+            }
+            Expression::Join { left, right } => f.write_fmt(format_args!(
+                "join({}, {})",
+                self.part(left),
+                self.part(right)
+            )),
+            Expression::LessOrEqual { left, right } => {
+                self.fmt_binary(f, left, "<=", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::LessThan { left, right } => {
+                self.fmt_binary(f, left, "<", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::Memcmp {
+                left,
+                right,
+                length,
+            } => f.write_fmt(format_args!(
+                "memcmp({}, {}, {})",
+                self.part(left),
+                self.part(right),
+                self.part(length),
+            )),
+            Expression::Mul { left, right } => self.fmt_binary(f, left, "*", right, 12),
+            Expression::MulOverflows { left, right, .. } => {
+                f.write_str("overflows(")?;
+                self.fmt_binary(f, left, "*", right, 12)?;
+                f.write_str(")")
+            }
+            Expression::Ne { left, right } => {
+                self.fmt_binary(f, left, "!=", right, COMPARISON_PRECEDENCE)
+            }
+            Expression::Neg { operand } => {
+                f.write_str("-")?;
+                self.fmt_operand(f, operand, UNARY_PRECEDENCE)
+            }
+            Expression::Or { left, right } => self.fmt_binary(f, left, "||", right, 4),
+            Expression::Offset { left, right } => {
+                f.write_str("&")?;
+                self.fmt_operand(f, left, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!("[{}]", self.part(right)))
+            }
+            Expression::Reference(path) => {
+                f.write_str("&")?;
+                self.path(path).fmt_as_operand(f, UNARY_PRECEDENCE)
+            }
+            Expression::InitialParameterValue { path, .. } => {
+                f.write_fmt(format_args!("old({})", self.path(path)))
+            }
+            Expression::Rem { left, right } => self.fmt_binary(f, left, "%", right, 12),
+            Expression::Shl { left, right } => self.fmt_binary(f, left, "<<", right, 10),
+            Expression::ShlOverflows { left, right, .. } => {
+                f.write_str("overflows(")?;
+                self.fmt_binary(f, left, "<<", right, 10)?;
+                f.write_str(")")
+            }
+            Expression::Shr { left, right, .. } => self.fmt_binary(f, left, ">>", right, 10),
+            Expression::ShrOverflows { left, right, .. } => {
+                f.write_str("overflows(")?;
+                self.fmt_binary(f, left, ">>", right, 10)?;
+                f.write_str(")")
+            }
+            Expression::Sub { left, right } => self.fmt_binary(f, left, "-", right, 11),
+            Expression::SubOverflows { left, right, .. } => {
+                f.write_str("overflows(")?;
+                self.fmt_binary(f, left, "-", right, 11)?;
+                f.write_str(")")
+            }
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => {
+                f.write_fmt(format_args!("switch {} {{", self.part(discriminator)))?;
+                for (switch_case, value) in cases {
+                    f.write_fmt(format_args!(
+                        " {} => {},",
+                        self.part(switch_case),
+                        self.part(value)
+                    ))?;
+                }
+                f.write_fmt(format_args!(" _ => {} }}", self.part(default)))
+            }
+            Expression::TaggedExpression { operand, tag } => {
+                self.fmt_operand(f, operand, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!(" tagged with {tag:?}"))
+            }
+            Expression::Transmute {
+                operand,
+                target_type,
+            } => f.write_fmt(format_args!(
+                "transmute({}, {})",
+                self.part(operand),
+                target_type.as_rust_str()
+            )),
+            Expression::UninterpretedCall {
+                callee, arguments, ..
+            } => {
+                self.fmt_operand(f, callee, ATOM_PRECEDENCE)?;
+                f.write_str("(")?;
+                for (i, argument) in arguments.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.part(argument).fmt(f)?;
+                }
+                f.write_str(")")
+            }
+            Expression::UnknownModelField { path, default } => {
+                self.path(path).fmt_as_operand(f, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!(".default({})", self.part(default)))
+            }
+            Expression::UnknownTagCheck {
+                operand,
+                tag,
+                checking_presence,
+            } => {
+                self.fmt_operand(f, operand, ATOM_PRECEDENCE)?;
+                f.write_fmt(format_args!(".check_tag({tag:?}, {checking_presence})"))
+            }
+            Expression::UnknownTagField { path } | Expression::Variable { path, .. } => {
+                self.path(path).fmt(f)
+            }
+            Expression::WidenedJoin { operand, .. } => {
+                if operand.expression_size > 100 {
+                    f.write_str("widened(..)")
+                } else {
+                    f.write_fmt(format_args!("widened({})", self.part(operand)))
+                }
+            }
+            Expression::Top | Expression::Bottom | Expression::HeapBlock { .. } => {
+                Debug::fmt(self.value, f)
+            }
+        }
+    }
+}
+
+/// See WithParameterNames.
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        WithParameterNames::new(self, &[]).fmt(f)
+    }
+}
+
 impl Expression {
+    /// The precedence of the operator at the root of the expression when it is displayed, which
+    /// is that of the corresponding Rust operator. Operators with a higher precedence bind more
+    /// tightly.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expression::Or { .. } => 4,
+            Expression::And { .. } => 5,
+            Expression::Equals { .. }
+            | Expression::GreaterOrEqual { .. }
+            | Expression::GreaterThan { .. }
+            | Expression::LessOrEqual { .. }
+            | Expression::LessThan { .. }
+            | Expression::Ne { .. } => COMPARISON_PRECEDENCE,
+            Expression::BitOr { .. } => 7,
+            Expression::BitXor { .. } => 8,
+            Expression::BitAnd { .. } => 9,
+            Expression::Shl { .. } | Expression::Shr { .. } => 10,
+            Expression::Add { .. } | Expression::Sub { .. } => 11,
+            Expression::Div { .. } | Expression::Mul { .. } | Expression::Rem { .. } => 12,
+            Expression::Cast { .. } => CAST_PRECEDENCE,
+            Expression::BitNot { .. }
+            | Expression::LogicalNot { .. }
+            | Expression::Neg { .. }
+            | Expression::Offset { .. }
+            | Expression::Reference(..) => UNARY_PRECEDENCE,
+            Expression::CompileTimeConstant(ConstantDomain::I128(val)) if *val < 0 => {
+                UNARY_PRECEDENCE
+            }
+            Expression::ConditionalExpression { .. }
+            | Expression::Switch { .. }
+            | Expression::TaggedExpression { .. } => 0,
+            Expression::UnknownTagField { path } | Expression::Variable { path, .. } => {
+                path.precedence()
+            }
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+
     /// Returns true if any part of the expression is a local variable.
     /// Use this to weed out inferred preconditions that cannot be satisfied by the caller.
     #[logfn_inputs(TRACE)]
@@ -1405,6 +1734,112 @@ impl Expression {
             }
         }
     }
+
+    /// Adds the primitive variables of the expression whose paths are rooted by parameters,
+    /// along with their types, to the given map.
+    #[logfn_inputs(TRACE)]
+    pub fn record_parameter_variables(&self, result: &mut BTreeMap<Rc<Path>, ExpressionType>) {
+        match &self {
+            Expression::Bottom | Expression::Top => (),
+            Expression::Add { left, right }
+            | Expression::AddOverflows { left, right, .. }
+            | Expression::And { left, right }
+            | Expression::BitAnd { left, right }
+            | Expression::BitOr { left, right }
+            | Expression::BitXor { left, right }
+            | Expression::Div { left, right }
+            | Expression::Equals { left, right }
+            | Expression::GreaterOrEqual { left, right }
+            | Expression::GreaterThan { left, right }
+            | Expression::IntrinsicBinary { left, right, .. }
+            | Expression::Join { left, right }
+            | Expression::LessOrEqual { left, right }
+            | Expression::LessThan { left, right }
+            | Expression::Mul { left, right }
+            | Expression::MulOverflows { left, right, .. }
+            | Expression::Ne { left, right }
+            | Expression::Offset { left, right }
+            | Expression::Or { left, right }
+            | Expression::Rem { left, right }
+            | Expression::Shl { left, right }
+            | Expression::ShlOverflows { left, right, .. }
+            | Expression::Shr { left, right, .. }
+            | Expression::ShrOverflows { left, right, .. }
+            | Expression::Sub { left, right }
+            | Expression::SubOverflows { left, right, .. } => {
+                left.expression.record_parameter_variables(result);
+                right.expression.record_parameter_variables(result);
+            }
+            Expression::BitNot { operand, .. }
+            | Expression::Cast { operand, .. }
+            | Expression::IntrinsicBitVectorUnary { operand, .. }
+            | Expression::IntrinsicFloatingPointUnary { operand, .. }
+            | Expression::Neg { operand }
+            | Expression::LogicalNot { operand }
+            | Expression::TaggedExpression { operand, .. }
+            | Expression::Transmute { operand, .. }
+            | Expression::UnknownTagCheck { operand, .. } => {
+                operand.expression.record_parameter_variables(result);
+            }
+            Expression::CompileTimeConstant(..)
+            | Expression::HeapBlock { .. }
+            | Expression::Reference(..)
+            | Expression::UnknownTagField { .. } => (),
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => {
+                condition.expression.record_parameter_variables(result);
+                consequent.expression.record_parameter_variables(result);
+                alternate.expression.record_parameter_variables(result);
+            }
+            Expression::HeapBlockLayout {
+                length, alignment, ..
+            } => {
+                length.expression.record_parameter_variables(result);
+                alignment.expression.record_parameter_variables(result);
+            }
+            Expression::InitialParameterValue { path, var_type }
+            | Expression::Variable { path, var_type } => {
+                if var_type.is_primitive() && path.is_rooted_by_parameter() {
+                    result.insert(path.clone(), *var_type);
+                }
+            }
+            Expression::Memcmp {
+                left,
+                right,
+                length,
+            } => {
+                left.expression.record_parameter_variables(result);
+                right.expression.record_parameter_variables(result);
+                length.expression.record_parameter_variables(result);
+            }
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => {
+                discriminator.expression.record_parameter_variables(result);
+                for (case_val, case_result) in cases {
+                    case_val.expression.record_parameter_variables(result);
+                    case_result.expression.record_parameter_variables(result);
+                }
+                default.expression.record_parameter_variables(result);
+            }
+            Expression::UninterpretedCall { arguments, .. } => {
+                for arg in arguments {
+                    arg.expression.record_parameter_variables(result);
+                }
+            }
+            Expression::UnknownModelField { default, .. } => {
+                default.expression.record_parameter_variables(result);
+            }
+            Expression::WidenedJoin { operand, .. } => {
+                operand.expression.record_parameter_variables(result);
+            }
+        }
+    }
 }
 
 /// The type of a place in memory, as understood by MIR.
@@ -1460,6 +1895,32 @@ impl From<&ConstantDomain> for ExpressionType {
 }
 
 impl ExpressionType {
+    /// The name of the type in Rust source, as far as it is known.
+    pub fn as_rust_str(&self) -> &'static str {
+        match self {
+            ExpressionType::Bool => "bool",
+            ExpressionType::Char => "char",
+            ExpressionType::F16 => "f16",
+            ExpressionType::F32 => "f32",
+            ExpressionType::F64 => "f64",
+            ExpressionType::I8 => "i8",
+            ExpressionType::I16 => "i16",
+            ExpressionType::I32 => "i32",
+            ExpressionType::I64 => "i64",
+            ExpressionType::I128 => "i128",
+            ExpressionType::Isize => "isize",
+            ExpressionType::NonPrimitive => "_",
+            ExpressionType::ThinPointer => "*const _",
+            ExpressionType::U8 => "u8",
+            ExpressionType::U16 => "u16",
+            ExpressionType::U32 => "u32",
+            ExpressionType::U64 => "u64",
+            ExpressionType::U128 => "u128",
+            ExpressionType::Unit => "()",
+            ExpressionType::Usize => "usize",
+        }
+    }
+
     pub fn from(ty_kind: &TyKind) -> ExpressionType {
         match ty_kind {
             TyKind::Adt(..) => ExpressionType::NonPrimitive,
@@ -17,6 +17,7 @@ use rustc_middle::mir;
 use crate::abstract_value::{AbstractValue, AbstractValueTrait};
 use crate::block_visitor::BlockVisitor;
 use crate::body_visitor::BodyVisitor;
+use crate::diagnostic_rules::DiagnosticRule;
 use crate::environment::Environment;
 use crate::options::DiagLevel;
 use crate::{abstract_value, k_limits};
@@ -168,7 +169,8 @@ impl<'fixed, 'analysis, 'compilation, 'tcx>
                             k_limits::MAX_FIXPOINT_ITERATIONS
                         ),
                     );
-                    self.bv.emit_diagnostic(warning);
+                    self.bv
+                        .emit_diagnostic(warning, DiagnosticRule::IncompleteAnalysis);
                 } else {
                     warn!(
                         "Fixed point loop iterations {} exceeded limit of {} at {:?} in function {}.",
@@ -0,0 +1,740 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Supports incremental re-analysis. The results of analyzing each entry point (root) of a crate
+// are recorded in an index, along with content hashes of the bodies that were analyzed in the
+// process. When the crate is analyzed again, a root is only analyzed again if its body, or the
+// body of a function it (transitively) calls, has changed. The summaries of roots that have been
+// analyzed again are compared with their previous summaries, so that the callers of a root whose
+// body changed in a way that does not affect its summary can still reuse their previous results.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use rustc_errors::{Diag, DiagArgValue, DiagCtxtHandle};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_middle::ty::{InstanceKind, TyCtxt};
+use rustc_span::{BytePos, Span};
+
+use crate::diagnostic_rules::{DiagnosticRule, RuleConfig};
+use crate::options::Options;
+use crate::sarif;
+use crate::summaries::{StoreHeader, Summary};
+use crate::suppressions::Suppressions;
+use crate::utils;
+
+/// The information about previous runs that is persisted between runs.
+#[derive(Default, Deserialize, Serialize)]
+struct Index {
+    /// Identifies the build of MIRAI that wrote the index.
+    header: Option<StoreHeader>,
+    /// A hash of the options and of everything outside of function bodies that can influence
+    /// the analysis. The index is discarded if this changes.
+    environment_hash: u64,
+    /// The summary keys of the functions called by each function body that has been analyzed.
+    callees: HashMap<String, Vec<String>>,
+    /// The results of analyzing each root, by summary key.
+    roots: HashMap<String, RootEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RootEntry {
+    /// The content hash of the body of the root.
+    body_hash: u64,
+    /// The local functions that are reachable from the root, along with the content hashes
+    /// that their bodies had when the root was analyzed.
+    dependencies: Vec<(String, u64)>,
+    /// The summary computed for the root.
+    summary: Summary,
+    /// The diagnostics found while analyzing the root, before suppressions and rule severities
+    /// were applied to them.
+    diagnostics: Vec<CachedDiagnostic>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CachedDiagnostic {
+    message: String,
+    rule: DiagnosticRule,
+    span: Option<CachedSpan>,
+    notes: Vec<(Option<CachedSpan>, String)>,
+    provenance: Option<String>,
+}
+
+/// A source location, relative to the start of the body of the function that contains it, so
+/// that it remains valid when code before the function is edited.
+#[derive(Clone, Deserialize, Serialize)]
+struct CachedSpan {
+    function: String,
+    lo: u32,
+    hi: u32,
+}
+
+/// The contents of a diagnostic, in a form that does not borrow the diagnostic context, so
+/// that it can be kept after the diagnostic has been dropped or be sent to another thread.
+pub struct DetachedDiagnostic {
+    message: String,
+    rule: DiagnosticRule,
+    span: Option<Span>,
+    notes: Vec<(Option<Span>, String)>,
+    provenance: Option<String>,
+}
+
+impl DetachedDiagnostic {
+    pub fn new(diag: &Diag<'_, ()>) -> DetachedDiagnostic {
+        let provenance = match diag.args.get(sarif::PROVENANCE_ARG) {
+            Some(DiagArgValue::Str(provenance)) => Some(provenance.to_string()),
+            _ => None,
+        };
+        DetachedDiagnostic {
+            message: sarif::message_text(&diag.messages[0].0).to_string(),
+            rule: DiagnosticRule::for_diagnostic(diag),
+            span: diag.span.primary_span(),
+            notes: diag
+                .children
+                .iter()
+                .map(|child| {
+                    (
+                        child.span.primary_span(),
+                        sarif::message_text(&child.messages[0].0).to_string(),
+                    )
+                })
+                .collect(),
+            provenance,
+        }
+    }
+
+    /// Creates a warning with the contents of this diagnostic.
+    pub fn to_diag<'a>(&self, dcx: DiagCtxtHandle<'a>) -> Diag<'a, ()> {
+        let mut diag = match self.span {
+            Some(span) => dcx.struct_span_warn(span, self.message.clone()),
+            None => dcx.struct_warn(self.message.clone()),
+        };
+        for (note_span, note) in self.notes.iter() {
+            match note_span {
+                Some(span) => diag.span_note(*span, note.clone()),
+                None => diag.note(note.clone()),
+            };
+        }
+        if let Some(provenance) = &self.provenance {
+            diag.arg(sarif::PROVENANCE_ARG, provenance.clone());
+        }
+        self.rule.attach_to(&mut diag);
+        diag
+    }
+}
+
+/// The state of incremental analysis for the crate being compiled.
+pub struct IncrementalAnalysis {
+    /// The file in which the index is persisted.
+    path: PathBuf,
+    index: Index,
+    /// The body owners of the crate, by summary key.
+    local_functions: HashMap<String, LocalDefId>,
+    /// The current content hashes of the bodies of local functions, computed on demand.
+    body_hashes: HashMap<String, u64>,
+    /// The summary keys of the roots that are selected for analysis in this run.
+    selected_roots: HashSet<String>,
+    /// The functions called by each function body that has been analyzed in this run.
+    callees_seen: HashMap<DefId, HashSet<DefId>>,
+    /// The summary keys of the functions in callees_seen.
+    keys: HashMap<DefId, Rc<str>>,
+    /// The diagnostics found so far while analyzing the current root.
+    pending_diagnostics: Vec<DetachedDiagnostic>,
+    /// True if the analysis of a body timed out while analyzing the current root.
+    timed_out: bool,
+    /// Roots that have been analyzed in this run and whose summaries did not change.
+    unchanged_summaries: HashSet<String>,
+    /// Roots that have been analyzed in this run and whose summaries changed.
+    changed_summaries: HashSet<String>,
+    /// The number of roots whose previous results have been reused.
+    reused_roots: usize,
+}
+
+impl IncrementalAnalysis {
+    /// Sets up incremental analysis using the index for the current crate that is kept in the
+    /// given directory. The index is ignored if it was written by a different build of MIRAI or
+    /// if the options, the rule configuration, the suppressions or the declarations outside of
+    /// function bodies have changed.
+    pub fn new(
+        directory: &str,
+        tcx: TyCtxt<'_>,
+        file_name: &str,
+        options: &Options,
+        rule_config: &RuleConfig,
+        suppressions: &Suppressions,
+    ) -> Result<IncrementalAnalysis, String> {
+        fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        let crate_name = tcx.crate_name(LOCAL_CRATE);
+        // A crate can be compiled as a library, a binary and a test harness, with different bodies.
+        let mut hasher = DefaultHasher::new();
+        file_name.hash(&mut hasher);
+        options.test_only.hash(&mut hasher);
+        let path =
+            Path::new(directory).join(format!("{crate_name}-{:016x}.mirai_index", hasher.finish()));
+        let environment_hash = Self::environment_hash(tcx, options, rule_config, suppressions);
+        let index = Self::read_index(&path)
+            .filter(|index| {
+                index.header == Some(StoreHeader::current())
+                    && index.environment_hash == environment_hash
+            })
+            .unwrap_or_else(|| Index {
+                header: Some(StoreHeader::current()),
+                environment_hash,
+                ..Index::default()
+            });
+        let local_functions = tcx
+            .hir()
+            .body_owners()
+            .map(|def_id| {
+                (
+                    utils::summary_key_str(tcx, def_id.to_def_id()).to_string(),
+                    def_id,
+                )
+            })
+            .collect();
+        Ok(IncrementalAnalysis {
+            path,
+            index,
+            local_functions,
+            body_hashes: HashMap::new(),
+            selected_roots: HashSet::new(),
+            callees_seen: HashMap::new(),
+            keys: HashMap::new(),
+            pending_diagnostics: vec![],
+            timed_out: false,
+            unchanged_summaries: HashSet::new(),
+            changed_summaries: HashSet::new(),
+            reused_roots: 0,
+        })
+    }
+
+    fn read_index(path: &Path) -> Option<Index> {
+        let bytes = fs::read(path).ok()?;
+        match bincode::deserialize(&bytes) {
+            Ok(index) => Some(index),
+            Err(e) => {
+                info!("ignoring incremental index {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Hashes the options, the rule configuration and the suppressions, the identities of the
+    /// crates that the current crate depends on, and the local declarations that are not function
+    /// bodies, such as type definitions and constants, since function bodies depend on these
+    /// without calling them.
+    fn environment_hash(
+        tcx: TyCtxt<'_>,
+        options: &Options,
+        rule_config: &RuleConfig,
+        suppressions: &Suppressions,
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        Self::hash_options(options, &mut hasher);
+        rule_config.hash(&mut hasher);
+        suppressions.hash_rules(tcx, &mut hasher);
+        for cnum in tcx.crates(()).iter() {
+            tcx.crate_name(*cnum).as_str().hash(&mut hasher);
+            tcx.crate_hash(*cnum).hash(&mut hasher);
+        }
+        let source_map = tcx.sess.source_map();
+        for def_id in tcx.hir_crate_items(()).definitions() {
+            match tcx.def_kind(def_id) {
+                DefKind::Impl { .. } => {
+                    // The span of an impl includes the bodies of its methods, so only the
+                    // header is hashed.
+                    format!(
+                        "{:?} {:?}",
+                        tcx.type_of(def_id).skip_binder(),
+                        tcx.impl_trait_ref(def_id)
+                    )
+                    .hash(&mut hasher);
+                }
+                DefKind::Struct
+                | DefKind::Enum
+                | DefKind::Union
+                | DefKind::Trait
+                | DefKind::TraitAlias
+                | DefKind::TyAlias
+                | DefKind::AssocTy
+                | DefKind::Const
+                | DefKind::AssocConst
+                | DefKind::Static { .. } => {
+                    let span = tcx.hir().span(tcx.local_def_id_to_hir_id(def_id));
+                    source_map
+                        .span_to_snippet(span)
+                        .unwrap_or_default()
+                        .hash(&mut hasher);
+                }
+                _ => {}
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Hashes the options that influence the summaries and the diagnostics of a root. Options
+    /// that only select what is analyzed, or where results are written, are left out.
+    fn hash_options(options: &Options, hasher: &mut DefaultHasher) {
+        format!("{:?}", options.diag_level).hash(hasher);
+        options.constant_time_tag_name.hash(hasher);
+        options.max_analysis_time_for_body.hash(hasher);
+        options.shared_summary_store.hash(hasher);
+        format!("{:?}", options.solver).hash(hasher);
+        // Queries that time out, or run out of resources, have undefined results.
+        options.solver_timeout.hash(hasher);
+        options.solver_rlimit.hash(hasher);
+        options.counterexamples.hash(hasher);
+    }
+
+    /// Returns the current content hash of the body of the local function with the given key,
+    /// or None if there is no such function. The hash covers the source text of the body as
+    /// well as its MIR, which also reflects changes to the macros the body uses.
+    fn body_hash(&mut self, tcx: TyCtxt<'_>, key: &str) -> Option<u64> {
+        if let Some(hash) = self.body_hashes.get(key) {
+            return Some(*hash);
+        }
+        let local_def_id = *self.local_functions.get(key)?;
+        let mut hasher = DefaultHasher::new();
+        let span = tcx
+            .hir()
+            .span_with_body(tcx.local_def_id_to_hir_id(local_def_id));
+        tcx.sess
+            .source_map()
+            .span_to_snippet(span)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        let def_id = local_def_id.to_def_id();
+        if tcx.is_mir_available(def_id) {
+            let mir = if tcx.is_const_fn(def_id) {
+                tcx.mir_for_ctfe(def_id)
+            } else {
+                tcx.instance_mir(InstanceKind::Item(def_id))
+            };
+            let mut text = String::new();
+            for local_decl in mir.local_decls.iter() {
+                let _ = write!(text, "{:?};", local_decl.ty);
+            }
+            for block in mir.basic_blocks.iter() {
+                for statement in block.statements.iter() {
+                    let _ = write!(text, "{statement:?};");
+                }
+                let _ = write!(text, "{:?};", block.terminator().kind);
+            }
+            text.hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+        self.body_hashes.insert(key.to_string(), hash);
+        Some(hash)
+    }
+
+    /// Records the roots that will be analyzed in this run.
+    pub fn select_roots(&mut self, tcx: TyCtxt<'_>, roots: &[DefId]) {
+        self.selected_roots = roots
+            .iter()
+            .map(|def_id| utils::summary_key_str(tcx, *def_id).to_string())
+            .collect();
+    }
+
+    /// Returns true if the previous results for the given root are still valid.
+    /// If assume_roots_unchanged is true, a changed body of a function that is itself a
+    /// selected root is not held against the given root, since the function may still turn out
+    /// to have the same summary. Otherwise, the body of such a function must have been analyzed
+    /// again in this run and must not have had its summary changed.
+    pub fn can_reuse(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        def_id: DefId,
+        assume_roots_unchanged: bool,
+    ) -> bool {
+        let key = utils::summary_key_str(tcx, def_id).to_string();
+        let Some(entry) = self.index.roots.get(&key) else {
+            return false;
+        };
+        let body_hash = entry.body_hash;
+        let dependencies = entry.dependencies.clone();
+        let functions_with_diagnostics: HashSet<String> = entry
+            .diagnostics
+            .iter()
+            .flat_map(|d| {
+                d.span
+                    .iter()
+                    .chain(d.notes.iter().filter_map(|(s, _)| s.as_ref()))
+            })
+            .map(|s| s.function.clone())
+            .collect();
+        if self.body_hash(tcx, &key) != Some(body_hash) {
+            return false;
+        }
+        for (dependency, hash) in dependencies.iter() {
+            if self.changed_summaries.contains(dependency) {
+                return false;
+            }
+            if self.body_hash(tcx, dependency) == Some(*hash) {
+                continue;
+            }
+            // The diagnostics located in the changed body can no longer be placed.
+            if functions_with_diagnostics.contains(dependency) {
+                return false;
+            }
+            let may_be_unchanged = if assume_roots_unchanged {
+                self.selected_roots.contains(dependency)
+            } else {
+                self.unchanged_summaries.contains(dependency)
+            };
+            if !may_be_unchanged {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Records that the body of the given function is being analyzed.
+    pub fn note_body(&mut self, def_id: DefId) {
+        self.callees_seen.entry(def_id).or_default();
+    }
+
+    /// Records that the body of caller calls callee.
+    pub fn note_call(&mut self, caller: DefId, callee: DefId) {
+        self.callees_seen.entry(caller).or_default().insert(callee);
+    }
+
+    /// Records that the analysis of a body timed out, which makes the results of the current
+    /// root depend on more than its inputs.
+    pub fn note_timeout(&mut self) {
+        self.timed_out = true;
+    }
+
+    /// Records a diagnostic of the current root.
+    pub fn note_diagnostic(&mut self, diag: &Diag<'_, ()>) {
+        self.pending_diagnostics.push(DetachedDiagnostic::new(diag));
+    }
+
+    /// Records the results of analyzing the given root, replacing the previous results.
+    pub fn root_analyzed(&mut self, tcx: TyCtxt<'_>, def_id: DefId, summary: &Summary) {
+        let key = utils::summary_key_str(tcx, def_id).to_string();
+        let pending_diagnostics = std::mem::take(&mut self.pending_diagnostics);
+        let timed_out = std::mem::replace(&mut self.timed_out, false);
+        // Preconditions carry spans that are not persisted, so compare the persisted forms.
+        let summary: Summary = summary
+            .to_bytes()
+            .ok()
+            .and_then(|bytes| Summary::from_bytes(&bytes).ok())
+            .unwrap_or_else(|| summary.clone());
+        let old_entry = self.index.roots.remove(&key);
+        let unchanged = old_entry.as_ref().is_some_and(|entry| {
+            summary.is_subset_of(&entry.summary) && entry.summary.is_subset_of(&summary)
+        });
+        if unchanged {
+            self.unchanged_summaries.insert(key.clone());
+        } else {
+            self.changed_summaries.insert(key.clone());
+        }
+        if timed_out {
+            return;
+        }
+        let Some(body_hash) = self.body_hash(tcx, &key) else {
+            return;
+        };
+        let mut dependencies = vec![];
+        let mut function_spans = vec![];
+        for dependency in self.reachable_functions(tcx, &key) {
+            let Some(hash) = self.body_hash(tcx, &dependency) else {
+                continue;
+            };
+            let local_def_id = self.local_functions[&dependency];
+            let span = tcx
+                .hir()
+                .span_with_body(tcx.local_def_id_to_hir_id(local_def_id));
+            function_spans.push((dependency.clone(), span));
+            dependencies.push((dependency, hash));
+        }
+        let mut diagnostics = vec![];
+        for pending in pending_diagnostics.into_iter() {
+            let Some(diagnostic) = Self::relativize(pending, &function_spans) else {
+                debug!("not caching the diagnostics of {key}");
+                return;
+            };
+            diagnostics.push(diagnostic);
+        }
+        self.index.roots.insert(
+            key,
+            RootEntry {
+                body_hash,
+                dependencies,
+                summary,
+                diagnostics,
+            },
+        );
+    }
+
+    /// Returns the summary keys of the local functions that can be reached from the given one,
+    /// including itself. Calls found in this run take precedence over those in the index.
+    fn reachable_functions(&mut self, tcx: TyCtxt<'_>, key: &str) -> Vec<String> {
+        let mut callees: HashMap<String, Vec<String>> = HashMap::new();
+        for (caller, called) in self.callees_seen.iter() {
+            let caller_key = Self::key_for(&mut self.keys, tcx, *caller);
+            let called_keys = called
+                .iter()
+                .map(|c| Self::key_for(&mut self.keys, tcx, *c).to_string())
+                .collect();
+            callees.insert(caller_key.to_string(), called_keys);
+        }
+        let mut result = vec![];
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut to_visit = vec![key.to_string()];
+        while let Some(current) = to_visit.pop() {
+            if !visited.insert(current.clone()) {
+                continue;
+            }
+            let called = callees
+                .get(&current)
+                .or_else(|| self.index.callees.get(&current));
+            to_visit.extend(called.into_iter().flatten().cloned());
+            if self.local_functions.contains_key(&current) {
+                result.push(current);
+            }
+        }
+        result.sort();
+        result
+    }
+
+    fn key_for(keys: &mut HashMap<DefId, Rc<str>>, tcx: TyCtxt<'_>, def_id: DefId) -> Rc<str> {
+        keys.entry(def_id)
+            .or_insert_with(|| utils::summary_key_str(tcx, def_id))
+            .clone()
+    }
+
+    /// Expresses the locations of the given diagnostic relative to the innermost of the given
+    /// function bodies that contains them. Returns None if some location is not inside any of them.
+    fn relativize(
+        pending: DetachedDiagnostic,
+        function_spans: &[(String, Span)],
+    ) -> Option<CachedDiagnostic> {
+        let relativize_span = |span: Option<Span>| -> Option<Option<CachedSpan>> {
+            let Some(span) = span.filter(|s| !s.is_dummy()) else {
+                return Some(None);
+            };
+            let (function, function_span) = function_spans
+                .iter()
+                .filter(|(_, function_span)| function_span.contains(span))
+                .min_by_key(|(_, function_span)| function_span.hi() - function_span.lo())?;
+            Some(Some(CachedSpan {
+                function: function.clone(),
+                lo: (span.lo() - function_span.lo()).0,
+                hi: (span.hi() - function_span.lo()).0,
+            }))
+        };
+        let span = relativize_span(pending.span)?;
+        let mut notes = vec![];
+        for (note_span, note) in pending.notes.into_iter() {
+            notes.push((relativize_span(note_span)?, note));
+        }
+        Some(CachedDiagnostic {
+            message: pending.message,
+            rule: pending.rule,
+            span,
+            notes,
+            provenance: pending.provenance,
+        })
+    }
+
+    /// Returns the location of the given span in the current version of the body of its
+    /// function, which has the given span.
+    fn absolutize(span: &CachedSpan, function_span: Span) -> Span {
+        Span::with_root_ctxt(
+            function_span.lo() + BytePos(span.lo),
+            function_span.lo() + BytePos(span.hi),
+        )
+    }
+
+    /// Recreates the diagnostics recorded for the given root, which must be reusable.
+    /// The dependencies of the root are updated to their current hashes, since the root
+    /// does not need to be analyzed again for the changes that made it here.
+    pub fn cached_diagnostics<'a>(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        def_id: DefId,
+        dcx: DiagCtxtHandle<'a>,
+    ) -> Vec<Diag<'a, ()>> {
+        let key = utils::summary_key_str(tcx, def_id).to_string();
+        let Some(mut entry) = self.index.roots.remove(&key) else {
+            return vec![];
+        };
+        for (dependency, hash) in entry.dependencies.iter_mut() {
+            if let Some(current_hash) = self.body_hash(tcx, dependency) {
+                *hash = current_hash;
+            }
+        }
+        let absolute_span = |span: &CachedSpan| -> Option<Span> {
+            let local_def_id = self.local_functions.get(&span.function)?;
+            let function_span = tcx
+                .hir()
+                .span_with_body(tcx.local_def_id_to_hir_id(*local_def_id));
+            Some(Self::absolutize(span, function_span))
+        };
+        let mut result = vec![];
+        for cached in entry.diagnostics.iter() {
+            let detached = DetachedDiagnostic {
+                message: cached.message.clone(),
+                rule: cached.rule,
+                span: cached.span.as_ref().and_then(absolute_span),
+                notes: cached
+                    .notes
+                    .iter()
+                    .map(|(span, note)| (span.as_ref().and_then(absolute_span), note.clone()))
+                    .collect(),
+                provenance: cached.provenance.clone(),
+            };
+            result.push(detached.to_diag(dcx));
+        }
+        self.index.roots.insert(key, entry);
+        self.reused_roots += 1;
+        result
+    }
+
+    /// Writes the index, updated with the results of this run, to its file.
+    pub fn write(&mut self, tcx: TyCtxt<'_>) -> Result<(), String> {
+        info!(
+            "reused the previous results of {} functions",
+            self.reused_roots
+        );
+        for (caller, called) in self.callees_seen.iter() {
+            let caller_key = Self::key_for(&mut self.keys, tcx, *caller).to_string();
+            let mut called_keys: Vec<String> = called
+                .iter()
+                .map(|c| Self::key_for(&mut self.keys, tcx, *c).to_string())
+                .collect();
+            called_keys.sort();
+            self.index.callees.insert(caller_key, called_keys);
+        }
+        let local_functions = &self.local_functions;
+        self.index
+            .roots
+            .retain(|key, _| local_functions.contains_key(key));
+        let bytes = bincode::serialize(&self.index).map_err(|e| e.to_string())?;
+        fs::write(&self.path, bytes).map_err(|e| format!("{}: {e}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::SolverOption;
+
+    fn span(lo: u32, hi: u32) -> Span {
+        Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+    }
+
+    fn options_hash(options: &Options) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        IncrementalAnalysis::hash_options(options, &mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn options_that_affect_results_change_the_hash() {
+        let default_hash = options_hash(&Options::default());
+        let changed = [
+            Options {
+                solver: SolverOption::None,
+                ..Options::default()
+            },
+            Options {
+                solver_timeout: 1000,
+                ..Options::default()
+            },
+            Options {
+                solver_rlimit: 5000,
+                ..Options::default()
+            },
+            Options {
+                counterexamples: true,
+                ..Options::default()
+            },
+            Options {
+                shared_summary_store: Some("deps".to_string()),
+                ..Options::default()
+            },
+            Options {
+                max_analysis_time_for_body: 7,
+                ..Options::default()
+            },
+        ];
+        for options in changed.iter() {
+            assert_ne!(options_hash(options), default_hash, "{options:?}");
+        }
+        let unchanged = Options {
+            jobs: 4,
+            statistics: true,
+            ..Options::default()
+        };
+        assert_eq!(options_hash(&unchanged), default_hash);
+    }
+
+    #[test]
+    fn spans_are_relative_to_the_innermost_function() {
+        rustc_span::create_default_session_globals_then(|| {
+            let function_spans = [
+                ("outer".to_string(), span(100, 200)),
+                ("inner".to_string(), span(120, 150)),
+            ];
+            let pending = DetachedDiagnostic {
+                message: "possible attempt to add with overflow".to_string(),
+                rule: DiagnosticRule::Overflow,
+                span: Some(span(130, 135)),
+                notes: vec![(Some(span(160, 170)), "related location".to_string())],
+                provenance: None,
+            };
+            let cached = IncrementalAnalysis::relativize(pending, &function_spans).unwrap();
+            let primary = cached.span.as_ref().unwrap();
+            assert_eq!(
+                (primary.function.as_str(), primary.lo, primary.hi),
+                ("inner", 10, 15)
+            );
+            let note = cached.notes[0].0.as_ref().unwrap();
+            assert_eq!(
+                (note.function.as_str(), note.lo, note.hi),
+                ("outer", 60, 70)
+            );
+
+            // The function has moved, since code before it was edited.
+            let moved = IncrementalAnalysis::absolutize(primary, span(1120, 1150));
+            assert_eq!((moved.lo(), moved.hi()), (BytePos(1130), BytePos(1135)));
+        });
+    }
+
+    #[test]
+    fn diagnostics_outside_of_the_functions_are_not_cached() {
+        rustc_span::create_default_session_globals_then(|| {
+            let function_spans = [("f".to_string(), span(100, 200))];
+            let pending = DetachedDiagnostic {
+                message: "unreachable".to_string(),
+                rule: DiagnosticRule::Unreachable,
+                span: Some(span(10, 20)),
+                notes: vec![],
+                provenance: None,
+            };
+            assert!(IncrementalAnalysis::relativize(pending, &function_spans).is_none());
+            let without_span = DetachedDiagnostic {
+                message: "unreachable".to_string(),
+                rule: DiagnosticRule::Unreachable,
+                span: None,
+                notes: vec![],
+                provenance: None,
+            };
+            let cached = IncrementalAnalysis::relativize(without_span, &function_spans).unwrap();
+            assert!(cached.span.is_none());
+        });
+    }
+}
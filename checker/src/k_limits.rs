@@ -23,3 +23,13 @@ pub const MAX_PATH_LENGTH: usize = 300;
 
 /// Refining values with a path condition that is a really deep expression leads to exponential blow up.
 pub const MAX_REFINE_DEPTH: usize = 40;
+
+/// Limits how far the call graph generator follows the components of the types of edges when
+/// it infers type relations.
+pub const MAX_INFERRED_TYPE_RELATION_DEPTH: usize = 4;
+
+/// Limits how deeply the arguments of generated tests are built up from nested types.
+pub const MAX_TEST_ARGUMENT_DEPTH: usize = 4;
+
+/// Arrays with more elements than this are left as placeholders in generated tests.
+pub const MAX_TEST_ARGUMENT_ELEMENTS: u64 = 32;
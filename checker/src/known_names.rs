@@ -166,6 +166,8 @@ pub enum KnownNames {
     StdPanickingBeginPanicFmt,
     StdPtrSwapNonOverlapping,
     StdSliceCmpMemcmp,
+    // Summaries refer to known names by their position, so new names are added at the end.
+    MiraiVerifyUnreachable,
 }
 
 /// An analysis lifetime cache that contains a map from def ids to known names.
@@ -240,6 +242,7 @@ fn known_name_for_mirai_annotations(
             "mirai_result" => KnownNames::MiraiResult,
             "mirai_set_model_field" => KnownNames::MiraiSetModelField,
             "mirai_verify" => KnownNames::MiraiVerify,
+            "mirai_verify_unreachable" => KnownNames::MiraiVerifyUnreachable,
         ),
         _ => None,
     }
@@ -27,9 +27,11 @@ extern crate log;
 extern crate rustc_abi;
 extern crate rustc_ast;
 extern crate rustc_attr_parsing;
+extern crate rustc_builtin_macros;
 extern crate rustc_data_structures;
 extern crate rustc_driver;
 extern crate rustc_errors;
+extern crate rustc_expand;
 extern crate rustc_hir;
 extern crate rustc_index;
 extern crate rustc_interface;
@@ -74,27 +76,39 @@ macro_rules! check_for_early_return {
 }
 
 pub mod abstract_value;
+pub mod baseline;
 pub mod block_visitor;
 pub mod body_visitor;
 pub mod bool_domain;
 pub mod call_graph;
+pub mod call_graph_diff;
 pub mod call_visitor;
 pub mod callbacks;
 pub mod constant_domain;
 pub mod crate_visitor;
+pub mod diagnostic_rules;
 pub mod environment;
 pub mod expected_errors;
 pub mod expression;
 pub mod fixed_point_visitor;
+pub mod incremental;
 pub mod interval_domain;
 pub mod k_limits;
 pub mod known_names;
 pub mod options;
+pub mod parallel;
 pub mod path;
+pub mod sarif;
+pub mod smt_queries;
 pub mod smt_solver;
+pub mod smtlib_solver;
 pub mod summaries;
+pub mod summary_store;
+pub mod suppressions;
 pub mod tag_domain;
+pub mod test_skeletons;
 pub mod type_visitor;
+pub mod unresolved_calls;
 pub mod utils;
 #[cfg(feature = "z3")]
 pub mod z3_solver;
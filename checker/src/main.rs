@@ -23,8 +23,10 @@ extern crate rustc_session;
 
 use itertools::Itertools;
 use log::*;
+use mirai::call_graph_diff;
 use mirai::callbacks;
 use mirai::options::Options;
+use mirai::summary_store;
 use mirai::utils;
 use mirai_annotations::*;
 use std::env;
@@ -45,6 +47,18 @@ fn main() {
         env_logger::init_from_env(e);
     }
 
+    // "cargo mirai store" forwards to this binary, since decoding summaries requires this crate.
+    if env::args().nth(1).as_deref() == Some("store") {
+        let store_args = env::args().skip(2).collect::<Vec<_>>();
+        std::process::exit(summary_store::run(&store_args));
+    }
+
+    // "cargo mirai callgraph-diff" forwards to this binary as well.
+    if env::args().nth(1).as_deref() == Some("callgraph-diff") {
+        let diff_args = env::args().skip(2).collect::<Vec<_>>();
+        std::process::exit(call_graph_diff::run(&diff_args));
+    }
+
     // Get any options specified via the MIRAI_FLAGS environment variable
     let mut options = Options::default();
     let rustc_args = options.parse_from_str(
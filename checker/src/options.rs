@@ -47,11 +47,86 @@ fn make_options_parser(running_test_harness: bool) -> Command {
             .default_value("240")
             .help("The maximum number of seconds that MIRAI will spend analyzing a function body.")
             .long_help("The default is 240 seconds."))
+        .arg(Arg::new("jobs")
+            .long("jobs")
+            .num_args(1)
+            .default_value("1")
+            .help("The number of threads that MIRAI uses to analyze the functions of a crate.")
+            .long_help("The functions that are analyzed are partitioned into groups that do not call the same functions, which are analyzed on different threads. This runs the compiler in parallel mode, as with -Z threads=<n>, unless a larger number of threads is already given that way. The default is 1."))
         .arg(Arg::new("statistics")
             .long("statistics")
             .num_args(0)
             .help("Just print out whether crates were analyzed, etc.")
             .long_help("Just print out whether crates were analyzed and how many diagnostics were produced for each crate."))
+        .arg(Arg::new("diagnostics_output")
+            .long("diagnostics_output")
+            .num_args(1)
+            .help("Also write diagnostics to a file in a machine readable format.")
+            .long_help("The value has the form <format>:<path>. Currently the only supported format is `sarif`, which writes a SARIF 2.1.0 log to <path>."))
+        .arg(Arg::new("rule_config")
+            .long("rule_config")
+            .num_args(1)
+            .help("Path to a JSON file that configures the severity of diagnostic rules.")
+            .long_help(r#"The file maps rule codes (such as MIRAI0001) or rule names (such as overflow) to "allow", "warn" or "deny". Rules that are not mentioned are reported as warnings."#))
+        .arg(Arg::new("baseline")
+            .long("baseline")
+            .num_args(1)
+            .help("Path to a JSON file that records known diagnostics, which are not reported again.")
+            .long_help("Diagnostics are identified by the summary key of the enclosing function, their rule code and their normalized message, so that line number changes do not invalidate the baseline."))
+        .arg(Arg::new("update_baseline")
+            .long("update_baseline")
+            .num_args(0)
+            .requires("baseline")
+            .help("Record the diagnostics of the current crate in the baseline file.")
+            .long_help("The file given by --baseline is created if necessary. Entries for other crates are retained. Diagnostics that are not yet in the baseline are still reported."))
+        .arg(Arg::new("shared_summary_store")
+            .long("shared_summary_store")
+            .num_args(1)
+            .help("Path to a read-only summary store with summaries for the functions of dependencies.")
+            .long_help("The path can be a directory that contains a .summary_store.sled directory, such a directory itself, or a tar archive of one. It is consulted when the summary store of the project has no summary for a function of another crate."))
+        .arg(Arg::new("incremental")
+            .long("incremental")
+            .num_args(1)
+            .help("Directory in which to keep the results of previous runs, so that only changed functions are analyzed again.")
+            .long_help("Functions whose bodies, and whose callees, have not changed since the previous run are not analyzed again and their previous diagnostics are reported instead. This is not done if call graph output, summaries, unresolved calls or tests are requested, since these need every function to be analyzed."))
+        .arg(Arg::new("unresolved_calls")
+            .long("unresolved_calls")
+            .num_args(1)
+            .help("Path to a JSON file in which to list the calls that could not be analyzed with a summary of the callee.")
+            .long_help("These are calls whose callee could not be resolved, or has no MIR body and no summary in the summary store. The entries of other crates in the file are retained, so a workspace can share one file."))
+        .arg(Arg::new("solver")
+            .long("solver")
+            .num_args(1)
+            .default_value("builtin")
+            .help("The SMT solver that MIRAI uses: builtin, none, or the command line of a solver that reads SMT-LIB2 from its standard input.")
+            .long_help("With `builtin`, MIRAI uses the Z3 library that it was built with, if any. With `none`, no solver is used. Any other value is split into words like a shell command, for example `cvc5 --lang=smt2` or `z3 -in`, and every solver query is written as an SMT-LIB2 script to the standard input of a new process that runs this command. Such a solver must support the theory of integers."))
+        .arg(Arg::new("solver_timeout")
+            .long("solver_timeout")
+            .num_args(1)
+            .default_value("100")
+            .help("The maximum number of milliseconds that the SMT solver may spend on a query.")
+            .long_help("The default is 100 milliseconds and 0 means no limit. An external solver gets another 400 milliseconds to start up. A query that times out has an undefined result, and --statistics counts these for each function."))
+        .arg(Arg::new("solver_rlimit")
+            .long("solver_rlimit")
+            .num_args(1)
+            .default_value("0")
+            .help("The maximum number of resource units that the Z3 solver may spend on a query.")
+            .long_help("Unlike the time-out, this limit does not depend on the speed of the machine. The default is 0, which means no limit. It does not apply to external solvers, whose command lines can set limits of their own, such as --rlimit-per for cvc5."))
+        .arg(Arg::new("dump_smt_queries")
+            .long("dump_smt_queries")
+            .num_args(1)
+            .help("Directory in which to write every solver query as a standalone SMT-LIB2 file.")
+            .long_help("Each file is named after the summary key of the analyzed function and the line and column of the query, and starts with comments that record the result of the query and the time it took."))
+        .arg(Arg::new("counterexamples")
+            .long("counterexamples")
+            .num_args(0)
+            .help("Add a note with example parameter values to diagnostics about conditions that might fail.")
+            .long_help("When the SMT solver finds values for which a verification condition, a precondition or a run-time check fails, the diagnostic gets a note that lists the values of the parameters, and of their fields, that the condition depends on."))
+        .arg(Arg::new("test_skeletons")
+            .long("test_skeletons")
+            .num_args(1)
+            .help("Directory in which to write a #[test] function for every possible failure for which the SMT solver finds parameter values.")
+            .long_help("The tests of a crate are written to mirai_<crate name>.rs. Each test calls the public function in which the failure was found with the values from the solver and expects it to panic. Arguments of types other than primitive types, references, arrays, tuples and structs with public fields are left as todo!() placeholders."))
         .arg(Arg::new("call_graph_config")
             .long("call_graph_config")
             .num_args(1)
@@ -84,7 +159,21 @@ pub struct Options {
     pub constant_time_tag_name: Option<String>,
     pub max_analysis_time_for_body: u64,
     pub max_analysis_time_for_crate: u64,
+    pub jobs: usize,
     pub statistics: bool,
+    pub diagnostics_output: Option<DiagnosticsOutput>,
+    pub rule_config: Option<String>,
+    pub baseline: Option<String>,
+    pub update_baseline: bool,
+    pub shared_summary_store: Option<String>,
+    pub incremental: Option<String>,
+    pub unresolved_calls: Option<String>,
+    pub solver: SolverOption,
+    pub solver_timeout: u64,
+    pub solver_rlimit: u64,
+    pub dump_smt_queries: Option<String>,
+    pub counterexamples: bool,
+    pub test_skeletons: Option<String>,
     pub call_graph_config: Option<String>,
     pub print_function_names: bool,
     pub print_summaries: bool,
@@ -108,6 +197,27 @@ pub enum DiagLevel {
     Paranoid,
 }
 
+/// Represents a machine readable output format for diagnostics, along with the path of the
+/// file to write the output to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DiagnosticsOutput {
+    /// Static Analysis Results Interchange Format, version 2.1.0.
+    Sarif(String),
+}
+
+/// Represents the SMT solver that is used to decide conditions that the abstract domains cannot.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum SolverOption {
+    /// The Z3 library, if MIRAI is built with the z3 feature, and no solver otherwise.
+    #[default]
+    Builtin,
+    /// No solver, so that every solver query has an undefined result.
+    None,
+    /// An external solver that reads SMT-LIB2 scripts from its standard input. The first
+    /// element is the solver binary and the others are its arguments.
+    SmtLib(Vec<String>),
+}
+
 impl Options {
     /// Parse options from an argument string. The argument string will be split using unix
     /// shell escaping rules. Any content beyond the leftmost `--` token will be returned
@@ -224,12 +334,94 @@ impl Options {
                 None => assume_unreachable!(),
             }
         }
+        if matches.contains_id("jobs") {
+            self.jobs = match matches.get_one::<String>("jobs") {
+                Some(s) => match s.parse::<usize>() {
+                    Ok(v) if v > 0 => v,
+                    _ => handler.early_fatal("--jobs expects a positive integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
         if !matches!(
             matches.value_source("statistics"),
             Some(ValueSource::DefaultValue)
         ) {
             self.statistics = true;
         }
+        if matches.contains_id("diagnostics_output") {
+            self.diagnostics_output = match matches
+                .get_one::<String>("diagnostics_output")
+                .and_then(|s| s.split_once(':'))
+            {
+                Some(("sarif", path)) if !path.is_empty() => {
+                    Some(DiagnosticsOutput::Sarif(path.to_string()))
+                }
+                _ => handler.early_fatal("--diagnostics_output expects sarif:<path>"),
+            }
+        }
+        if matches.contains_id("rule_config") {
+            self.rule_config = matches.get_one::<String>("rule_config").cloned();
+        }
+        if matches.contains_id("baseline") {
+            self.baseline = matches.get_one::<String>("baseline").cloned();
+        }
+        if !matches!(
+            matches.value_source("update_baseline"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.update_baseline = true;
+        }
+        if matches.contains_id("shared_summary_store") {
+            self.shared_summary_store = matches.get_one::<String>("shared_summary_store").cloned();
+        }
+        if matches.contains_id("incremental") {
+            self.incremental = matches.get_one::<String>("incremental").cloned();
+        }
+        if matches.contains_id("unresolved_calls") {
+            self.unresolved_calls = matches.get_one::<String>("unresolved_calls").cloned();
+        }
+        if matches.contains_id("solver") {
+            self.solver = match matches.get_one::<String>("solver").map(String::as_str) {
+                Some("builtin") => SolverOption::Builtin,
+                Some("none") => SolverOption::None,
+                Some(s) => match shellwords::split(s) {
+                    Ok(command) if !command.is_empty() => SolverOption::SmtLib(command),
+                    _ => handler.early_fatal("--solver expects builtin, none or a command line"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if matches.contains_id("solver_timeout") {
+            self.solver_timeout = match matches.get_one::<String>("solver_timeout") {
+                Some(s) => match s.parse::<u64>() {
+                    Ok(v) => v,
+                    Err(_) => handler.early_fatal("--solver_timeout expects an integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if matches.contains_id("solver_rlimit") {
+            self.solver_rlimit = match matches.get_one::<String>("solver_rlimit") {
+                Some(s) => match s.parse::<u64>() {
+                    Ok(v) => v,
+                    Err(_) => handler.early_fatal("--solver_rlimit expects an integer"),
+                },
+                None => assume_unreachable!(),
+            }
+        }
+        if matches.contains_id("dump_smt_queries") {
+            self.dump_smt_queries = matches.get_one::<String>("dump_smt_queries").cloned();
+        }
+        if !matches!(
+            matches.value_source("counterexamples"),
+            Some(ValueSource::DefaultValue)
+        ) {
+            self.counterexamples = true;
+        }
+        if matches.contains_id("test_skeletons") {
+            self.test_skeletons = matches.get_one::<String>("test_skeletons").cloned();
+        }
         if matches.contains_id("call_graph_config") {
             self.call_graph_config = matches.get_one::<String>("call_graph_config").cloned();
         }
@@ -0,0 +1,253 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Supports analyzing the functions of a crate on several threads. The state of the analysis,
+// which includes the caches of summaries and the values they contain, is not thread safe,
+// so every thread gets its own crate visitor and only detached diagnostics are sent back.
+
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::rc::Rc;
+use std::time::Instant;
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir;
+use rustc_middle::ty::{InstanceKind, TyCtxt};
+use rustc_session::Session;
+
+use crate::call_graph::CallGraph;
+use crate::constant_domain::ConstantValueCache;
+use crate::crate_visitor::CrateVisitor;
+use crate::diagnostic_rules::RuleConfig;
+use crate::incremental::DetachedDiagnostic;
+use crate::known_names::KnownNamesCache;
+use crate::options::Options;
+use crate::summaries::{SummaryCache, SummaryStores};
+use crate::suppressions::Suppressions;
+use crate::test_skeletons::TestSkeleton;
+use crate::type_visitor::TypeCache;
+use crate::unresolved_calls::UnresolvedCall;
+
+/// The results of analyzing a partition of the roots of a crate.
+pub struct PartitionResult {
+    /// The diagnostics of each root, in the order in which the roots were analyzed.
+    pub diagnostics: Vec<(DefId, Vec<DetachedDiagnostic>)>,
+    /// The unresolved calls found while analyzing the roots, if these are to be reported.
+    pub unresolved_calls: Vec<UnresolvedCall>,
+    /// The number of solver queries with an undefined result, by function, if statistics are to
+    /// be printed.
+    pub undefined_solver_results: BTreeMap<String, usize>,
+    /// The tests for the possible failures found in the roots, if these are to be written.
+    pub test_skeletons: Vec<TestSkeleton>,
+}
+
+/// Splits the roots into at most jobs partitions, such that roots that can reach the same local
+/// function are in the same partition, since the summaries computed for that function while
+/// analyzing one root can then be reused while analyzing the other. The call graph used for this
+/// only has the calls that are evident from the MIR bodies, so a function that is only called
+/// via a trait method or a function pointer may still be analyzed by more than one thread.
+/// Within a partition, the roots keep their order. The result does not depend on the number of
+/// threads that are actually available.
+pub fn partition_roots(tcx: TyCtxt<'_>, roots: &[DefId], jobs: usize) -> Vec<Vec<DefId>> {
+    let reachable: Vec<HashSet<DefId>> = roots
+        .iter()
+        .map(|root| reachable_local_functions(tcx, *root))
+        .collect();
+    partition_indices(&reachable, jobs)
+        .into_iter()
+        .map(|partition| partition.into_iter().map(|i| roots[i]).collect())
+        .collect()
+}
+
+/// Partitions the indices of the given sets of reachable functions, such that indices whose sets
+/// overlap end up in the same partition, and returns at most jobs partitions, each of which is
+/// sorted.
+fn partition_indices<T: Copy + Eq + Hash>(
+    reachable: &[HashSet<T>],
+    jobs: usize,
+) -> Vec<Vec<usize>> {
+    // A union-find structure over the indices of the roots.
+    let mut parents: Vec<usize> = (0..reachable.len()).collect();
+    fn find(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    let mut first_root_reaching: HashMap<T, usize> = HashMap::new();
+    for (i, functions) in reachable.iter().enumerate() {
+        for function in functions.iter() {
+            match first_root_reaching.entry(*function) {
+                Entry::Occupied(entry) => {
+                    let (a, b) = (find(&mut parents, *entry.get()), find(&mut parents, i));
+                    // Keep the smallest index as the representative, for determinism.
+                    parents[a.max(b)] = a.min(b);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(i);
+                }
+            }
+        }
+    }
+    let mut components: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..reachable.len() {
+        let representative = find(&mut parents, i);
+        components.entry(representative).or_default().push(i);
+    }
+    // Assign the largest components first, each to the partition with the fewest roots so far.
+    let mut components: Vec<Vec<usize>> = components.into_values().collect();
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    let mut partitions: Vec<Vec<usize>> = vec![vec![]; jobs.min(components.len())];
+    for component in components {
+        if let Some(smallest) = partitions.iter_mut().min_by_key(|p| p.len()) {
+            smallest.extend(component);
+        }
+    }
+    for partition in partitions.iter_mut() {
+        partition.sort_unstable();
+    }
+    partitions
+}
+
+/// Returns the local functions with MIR bodies that the given function calls, directly or
+/// indirectly, or whose closures it creates, including the function itself.
+fn reachable_local_functions(tcx: TyCtxt<'_>, def_id: DefId) -> HashSet<DefId> {
+    let mut reachable = HashSet::new();
+    let mut to_visit = vec![def_id];
+    while let Some(def_id) = to_visit.pop() {
+        if !def_id.is_local() || !tcx.is_mir_available(def_id) || !reachable.insert(def_id) {
+            continue;
+        }
+        let mir = if tcx.is_const_fn(def_id) {
+            tcx.mir_for_ctfe(def_id)
+        } else {
+            tcx.instance_mir(InstanceKind::Item(def_id))
+        };
+        for block in mir.basic_blocks.iter() {
+            for statement in block.statements.iter() {
+                if let mir::StatementKind::Assign(box (_, rvalue)) = &statement.kind {
+                    match rvalue {
+                        mir::Rvalue::Aggregate(box mir::AggregateKind::Closure(def_id, _), _)
+                        | mir::Rvalue::Aggregate(box mir::AggregateKind::Coroutine(def_id, _), _)
+                        | mir::Rvalue::Aggregate(
+                            box mir::AggregateKind::CoroutineClosure(def_id, _),
+                            _,
+                        ) => to_visit.push(*def_id),
+                        mir::Rvalue::Use(operand) | mir::Rvalue::Cast(_, operand, _) => {
+                            if let Some((def_id, _)) = operand.const_fn_def() {
+                                to_visit.push(def_id);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if let mir::TerminatorKind::Call { func, .. } = &block.terminator().kind {
+                if let Some((def_id, _)) = func.const_fn_def() {
+                    to_visit.push(def_id);
+                }
+            }
+        }
+    }
+    reachable
+}
+
+/// Analyzes the given roots with a crate visitor of their own, which shares only the persistent
+/// summary stores with the other threads. The diagnostics are returned without applying
+/// suppressions or rule severities, since the state for doing so belongs to the main visitor.
+pub fn analyze_partition(
+    tcx: TyCtxt<'_>,
+    session: &Session,
+    options: &Options,
+    file_name: &str,
+    stores: SummaryStores,
+    roots: Vec<DefId>,
+    start_instant: Instant,
+) -> PartitionResult {
+    let mut crate_visitor = CrateVisitor {
+        buffered_diagnostics: Vec::new(),
+        constant_time_tag_cache: None,
+        constant_time_tag_not_found: false,
+        constant_value_cache: ConstantValueCache::default(),
+        diagnostics_for: HashMap::new(),
+        file_name,
+        known_names_cache: KnownNamesCache::create_cache(),
+        options,
+        rule_config: RuleConfig::default(),
+        session,
+        generic_args_cache: HashMap::new(),
+        incremental: None,
+        is_worker: true,
+        summary_cache: SummaryCache::with_stores(stores),
+        suppressions: Suppressions::default(),
+        tcx,
+        test_run: false,
+        type_cache: Rc::new(RefCell::new(TypeCache::new())),
+        unresolved_calls: Vec::new(),
+        undefined_solver_results: BTreeMap::new(),
+        test_skeletons: Vec::new(),
+        call_graph: CallGraph::new(None, tcx),
+    };
+    crate_visitor.analyze_roots(roots.clone(), start_instant);
+    let mut diagnostics = vec![];
+    for def_id in roots {
+        let Some(diags) = crate_visitor.diagnostics_for.remove(&def_id) else {
+            continue;
+        };
+        let detached = diags
+            .into_iter()
+            .map(|diag| {
+                let detached = DetachedDiagnostic::new(&diag);
+                diag.cancel();
+                detached
+            })
+            .collect();
+        diagnostics.push((def_id, detached));
+    }
+    PartitionResult {
+        diagnostics,
+        unresolved_calls: crate_visitor.unresolved_calls,
+        undefined_solver_results: crate_visitor.undefined_solver_results,
+        test_skeletons: crate_visitor.test_skeletons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reachable(sets: &[&[u32]]) -> Vec<HashSet<u32>> {
+        sets.iter()
+            .map(|set| set.iter().copied().collect())
+            .collect()
+    }
+
+    #[test]
+    fn roots_that_reach_the_same_function_share_a_partition() {
+        // 0 and 2 both reach 10, and 2 and 4 both reach 12, so 0, 2 and 4 go together.
+        let reachable = reachable(&[&[0, 10], &[1], &[2, 10, 12], &[3], &[4, 12]]);
+        let partitions = partition_indices(&reachable, 2);
+        assert_eq!(partitions, vec![vec![0, 2, 4], vec![1, 3]]);
+    }
+
+    #[test]
+    fn there_are_no_more_partitions_than_jobs_or_components() {
+        let reachable = reachable(&[&[0], &[1], &[2], &[3], &[4]]);
+        let partitions = partition_indices(&reachable, 2);
+        assert_eq!(partitions, vec![vec![0, 2, 4], vec![1, 3]]);
+        let partitions = partition_indices(&reachable, 8);
+        assert_eq!(partitions.len(), 5);
+        assert!(partition_indices(&reachable[0..0], 4).is_empty());
+    }
+
+    #[test]
+    fn a_single_job_gets_every_root_in_order() {
+        let reachable = reachable(&[&[0, 5], &[1], &[2, 5]]);
+        assert_eq!(partition_indices(&reachable, 1), vec![vec![0, 1, 2]]);
+    }
+}
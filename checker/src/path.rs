@@ -9,7 +9,7 @@ use rustc_middle::ty::{Ty, TyCtxt};
 
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
-use std::fmt::{Debug, Formatter, Result};
+use std::fmt::{Debug, Display, Formatter, Result};
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
@@ -20,7 +20,10 @@ use serde::{Deserialize, Serialize};
 use crate::abstract_value::{self, AbstractValue, AbstractValueTrait};
 use crate::constant_domain::ConstantDomain;
 use crate::environment::Environment;
-use crate::expression::{Expression, ExpressionType};
+use crate::expression::{
+    Expression, ExpressionType, WithParameterNames, ATOM_PRECEDENCE, CAST_PRECEDENCE,
+    UNARY_PRECEDENCE,
+};
 use crate::tag_domain::Tag;
 use crate::{k_limits, utils};
 
@@ -46,6 +49,113 @@ impl Debug for Path {
     }
 }
 
+/// See WithParameterNames.
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        WithParameterNames::new(self, &[]).fmt(f)
+    }
+}
+
+impl<'a> WithParameterNames<'a, Path> {
+    /// Renders the path and parenthesizes it if it binds less tightly than the given precedence.
+    pub fn fmt_as_operand(&self, f: &mut Formatter<'_>, precedence: u8) -> Result {
+        if self.value.precedence() < precedence {
+            f.write_fmt(format_args!("({self})"))
+        } else {
+            self.fmt(f)
+        }
+    }
+}
+
+impl Display for WithParameterNames<'_, Path> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self.value.value {
+            PathEnum::Computed { value } => self.part(value).fmt(f),
+            PathEnum::HeapBlock { value } | PathEnum::Offset { value } => {
+                f.write_fmt(format_args!("<{}>", self.part(value)))
+            }
+            PathEnum::LocalVariable { ordinal, .. } => f.write_fmt(format_args!("local_{ordinal}")),
+            PathEnum::Parameter { ordinal } => {
+                let name = ordinal
+                    .checked_sub(1)
+                    .and_then(|i| self.parameter_names.get(i))
+                    .filter(|name| !name.is_empty() && name.as_str() != "_");
+                match name {
+                    Some(name) => f.write_str(name),
+                    None => f.write_fmt(format_args!("param_{ordinal}")),
+                }
+            }
+            PathEnum::Result => f.write_str("result"),
+            PathEnum::StaticVariable {
+                summary_cache_key, ..
+            } => f.write_str(summary_cache_key),
+            PathEnum::PhantomData => f.write_str("phantom_data"),
+            PathEnum::PromotedConstant { ordinal } => {
+                f.write_fmt(format_args!("constant_{ordinal}"))
+            }
+            PathEnum::QualifiedPath {
+                qualifier,
+                selector,
+                ..
+            } => {
+                let qualifier = self.path(qualifier);
+                match selector.as_ref() {
+                    PathSelector::Deref => {
+                        f.write_str("*")?;
+                        return qualifier.fmt_as_operand(f, UNARY_PRECEDENCE);
+                    }
+                    PathSelector::Downcast(name, ..) => {
+                        f.write_str("(")?;
+                        qualifier.fmt_as_operand(f, CAST_PRECEDENCE)?;
+                        return f.write_fmt(format_args!(" as {name})"));
+                    }
+                    _ => {}
+                }
+                qualifier.fmt_as_operand(f, ATOM_PRECEDENCE)?;
+                match selector.as_ref() {
+                    PathSelector::Layout => f.write_str(".layout"),
+                    PathSelector::Discriminant => f.write_str(".discr"),
+                    PathSelector::Function => f.write_str(".func"),
+                    PathSelector::Field(index) => f.write_fmt(format_args!(".{index}")),
+                    PathSelector::UnionField {
+                        case_index,
+                        num_cases,
+                    } => f.write_fmt(format_args!(".({case_index} of {num_cases})")),
+                    PathSelector::Index(value) => {
+                        if value.expression_size > 100 {
+                            f.write_str("[..]")
+                        } else {
+                            f.write_fmt(format_args!("[{}]", self.part(value)))
+                        }
+                    }
+                    PathSelector::Slice(value) => {
+                        f.write_fmt(format_args!("[0..{}]", self.part(value)))
+                    }
+                    PathSelector::ConstantIndex {
+                        offset, from_end, ..
+                    } => {
+                        if *from_end {
+                            f.write_fmt(format_args!("[len - {offset}]"))
+                        } else {
+                            f.write_fmt(format_args!("[{offset}]"))
+                        }
+                    }
+                    PathSelector::ConstantSlice { from, to, from_end } => {
+                        if *from_end {
+                            f.write_fmt(format_args!("[{from}..len - {to}]"))
+                        } else {
+                            f.write_fmt(format_args!("[{from}..{to}]"))
+                        }
+                    }
+                    PathSelector::ModelField(name) => f.write_fmt(format_args!(".{name}")),
+                    PathSelector::TagField => f.write_str(".$tag"),
+                    PathSelector::Deref | PathSelector::Downcast(..) => assume_unreachable!(),
+                }
+            }
+        }
+    }
+}
+
 impl Hash for Path {
     fn hash<H: Hasher>(&self, state: &mut H) {
         state.write_u64(self.hash);
@@ -95,6 +205,19 @@ impl From<PathEnum> for Path {
 }
 
 impl Path {
+    /// The precedence of the path when it is displayed, see Expression::precedence.
+    pub fn precedence(&self) -> u8 {
+        match &self.value {
+            PathEnum::Computed { value } => value.expression.precedence(),
+            PathEnum::QualifiedPath { selector, .. }
+                if matches!(selector.as_ref(), PathSelector::Deref) =>
+            {
+                UNARY_PRECEDENCE
+            }
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+
     /// Returns a qualified path of the form root.selectors[0].selectors[1]...
     #[logfn_inputs(TRACE)]
     pub fn add_selectors(root: &Rc<Path>, selectors: &[Rc<PathSelector>]) -> Rc<Path> {
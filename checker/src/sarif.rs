@@ -0,0 +1,351 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use rustc_errors::{Diag, DiagArgValue, DiagMessage, MultiSpan};
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
+
+use crate::diagnostic_rules::DiagnosticRule;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The name of the diagnostic argument that carries the provenance of a precondition
+/// whose source spans are not available in the current compilation.
+pub const PROVENANCE_ARG: &str = "mirai_provenance";
+
+/// A log in the Static Analysis Results Interchange Format (SARIF), version 2.1.0.
+/// See https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html.
+/// The log contains a run for every crate, which holds the results of analyzing the crate and is
+/// identified by the name of the crate in its automation details. Each crate is analyzed by a
+/// separate MIRAI invocation, so the log for a whole workspace is built up by replacing the run of
+/// one crate at a time.
+#[derive(Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun {
+    tool: SarifTool,
+    automation_details: SarifAutomationDetails,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct SarifAutomationDetails {
+    /// The name of the crate.
+    id: String,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+struct SarifRule {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    physical_location: Option<SarifPhysicalLocation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logical_locations: Vec<SarifLogicalLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<SarifMessage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+/// Line and column numbers are 1 based.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLogicalLocation {
+    /// The summary key of the function in which the diagnostic was found.
+    fully_qualified_name: String,
+    kind: &'static str,
+}
+
+impl SarifLog {
+    /// Creates a log with an empty run for the crate with the given name.
+    pub fn new(crate_name: &str) -> SarifLog {
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: "MIRAI",
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: "https://github.com/endorlabs/MIRAI",
+                        rules: vec![],
+                    },
+                },
+                automation_details: SarifAutomationDetails {
+                    id: crate_name.to_string(),
+                },
+                results: vec![],
+            }],
+        }
+    }
+
+    /// Adds a result for the given diagnostic, which was found while analyzing the function
+    /// with the given summary key. The rule id is the tag in square brackets that starts
+    /// every MIRAI diagnostic message, i.e. the code of the rule the diagnostic belongs to.
+    /// The notes attached to the diagnostic, which point out the locations of promoted
+    /// preconditions, become related locations.
+    pub fn add_result(&mut self, diag: &Diag<'_, ()>, summary_key: &str, source_map: &SourceMap) {
+        let (rule_id, text) = split_rule_id(message_text(&diag.messages[0].0));
+        let mut location = SarifLocation::new(Self::physical_location(&diag.span, source_map));
+        location.logical_locations.push(SarifLogicalLocation {
+            fully_qualified_name: summary_key.to_string(),
+            kind: "function",
+        });
+        let mut related_locations = vec![];
+        for child in diag.children.iter() {
+            if let Some(physical_location) = Self::physical_location(&child.span, source_map) {
+                let mut related = SarifLocation::new(Some(physical_location));
+                related.id = Some(related_locations.len());
+                related.message = Some(SarifMessage {
+                    text: message_text(&child.messages[0].0).to_string(),
+                });
+                related_locations.push(related);
+            }
+        }
+        if let Some(DiagArgValue::Str(provenance)) = diag.args.get(PROVENANCE_ARG) {
+            if let Some(physical_location) = Self::parse_provenance(provenance) {
+                let mut related = SarifLocation::new(Some(physical_location));
+                related.id = Some(related_locations.len());
+                related.message = Some(SarifMessage {
+                    text: "precondition defined here".to_string(),
+                });
+                related_locations.push(related);
+            }
+        }
+        let run = &mut self.runs[0];
+        run.results.push(SarifResult {
+            rule_id: rule_id.to_string(),
+            level: if diag.is_error() { "error" } else { "warning" },
+            message: SarifMessage {
+                text: text.to_string(),
+            },
+            locations: vec![location],
+            related_locations,
+        });
+    }
+
+    /// Serializes the log as JSON. If the JSON of a previous log is given, its runs for other
+    /// crates are included, so that they are kept when the file of the previous log is replaced.
+    pub fn to_json(mut self, previous_log: Option<&str>) -> Result<String, String> {
+        let run = &mut self.runs[0];
+        let rule_ids: BTreeSet<&str> = run.results.iter().map(|r| r.rule_id.as_str()).collect();
+        run.tool.driver.rules = rule_ids
+            .into_iter()
+            .map(|id| SarifRule {
+                id: id.to_string(),
+                name: DiagnosticRule::from_code_or_name(id).map(|rule| rule.name()),
+            })
+            .collect();
+        let mut log = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        if let Some(previous_log) = previous_log {
+            let mut previous_log: Value =
+                serde_json::from_str(previous_log).map_err(|e| e.to_string())?;
+            let run = log["runs"][0].take();
+            let crate_name = run_id(&run).to_string();
+            let mut runs: Vec<Value> = match previous_log["runs"].take() {
+                Value::Array(runs) => runs,
+                _ => vec![],
+            };
+            runs.retain(|previous_run| run_id(previous_run) != crate_name);
+            runs.push(run);
+            // Sorted, so that the file does not depend on the order in which crates are analyzed.
+            runs.sort_by(|run1, run2| run_id(run1).cmp(run_id(run2)));
+            log["runs"] = Value::Array(runs);
+        }
+        serde_json::to_string_pretty(&log).map_err(|e| e.to_string())
+    }
+
+    /// Returns the location of the first primary span, if it corresponds to a source location.
+    fn physical_location(
+        multi_span: &MultiSpan,
+        source_map: &SourceMap,
+    ) -> Option<SarifPhysicalLocation> {
+        let span: Span = multi_span.primary_span()?;
+        if span.is_dummy() {
+            return None;
+        }
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        Some(SarifPhysicalLocation {
+            artifact_location: SarifArtifactLocation {
+                uri: lo.file.name.prefer_remapped_unconditionaly().to_string(),
+            },
+            region: SarifRegion {
+                start_line: lo.line,
+                start_column: lo.col.0 + 1,
+                end_line: hi.line,
+                end_column: hi.col.0 + 1,
+            },
+        })
+    }
+
+    /// Parses a precondition provenance, which has the form "file:line:col: line:col",
+    /// as produced by SourceMap::span_to_diagnostic_string.
+    fn parse_provenance(provenance: &str) -> Option<SarifPhysicalLocation> {
+        let (start, end) = provenance.rsplit_once(": ")?;
+        let mut start_parts = start.rsplitn(3, ':');
+        let start_column = start_parts.next()?.parse::<usize>().ok()?;
+        let start_line = start_parts.next()?.parse::<usize>().ok()?;
+        let file = start_parts.next()?;
+        let (end_line, end_column) = end.split_once(':')?;
+        Some(SarifPhysicalLocation {
+            artifact_location: SarifArtifactLocation {
+                uri: file.to_string(),
+            },
+            region: SarifRegion {
+                start_line,
+                start_column,
+                end_line: end_line.parse::<usize>().ok()?,
+                end_column: end_column.parse::<usize>().ok()?,
+            },
+        })
+    }
+}
+
+impl SarifLocation {
+    fn new(physical_location: Option<SarifPhysicalLocation>) -> SarifLocation {
+        SarifLocation {
+            id: None,
+            physical_location,
+            logical_locations: vec![],
+            message: None,
+        }
+    }
+}
+
+/// Returns the name of the crate whose results are in the given run.
+fn run_id(run: &Value) -> &str {
+    run["automationDetails"]["id"].as_str().unwrap_or_default()
+}
+
+/// Returns the text of a diagnostic message. MIRAI only creates non-translatable messages.
+pub fn message_text(message: &DiagMessage) -> &str {
+    match message {
+        DiagMessage::Str(s) | DiagMessage::Translated(s) => s,
+        _ => "",
+    }
+}
+
+/// Splits a message of the form "[TAG] text" into ("TAG", "text").
+/// If the message does not start with a tag, the tag defaults to "MIRAI".
+pub fn split_rule_id(message: &str) -> (&str, &str) {
+    if let Some(rest) = message.strip_prefix('[') {
+        if let Some((tag, text)) = rest.split_once(']') {
+            return (tag, text.trim_start());
+        }
+    }
+    ("MIRAI", message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_id_is_taken_from_message_tag() {
+        assert_eq!(
+            split_rule_id("[MIRAI] possible attempt to add with overflow"),
+            ("MIRAI", "possible attempt to add with overflow")
+        );
+        assert_eq!(
+            split_rule_id("unsatisfied precondition"),
+            ("MIRAI", "unsatisfied precondition")
+        );
+    }
+
+    #[test]
+    fn runs_of_other_crates_are_kept() {
+        let foo = SarifLog::new("foo").to_json(None).unwrap();
+        let bar = SarifLog::new("bar").to_json(Some(&foo)).unwrap();
+        let foo_again = SarifLog::new("foo").to_json(Some(&bar)).unwrap();
+        let log: Value = serde_json::from_str(&foo_again).unwrap();
+        let runs: Vec<&str> = log["runs"].as_array().unwrap().iter().map(run_id).collect();
+        assert_eq!(runs, vec!["bar", "foo"]);
+        assert_eq!(log["version"], SARIF_VERSION);
+        assert_eq!(log["runs"][1]["tool"]["driver"]["name"], "MIRAI");
+    }
+
+    #[test]
+    fn provenance_is_parsed_into_a_region() {
+        let location = SarifLog::parse_provenance("src/lib.rs:10:5: 12:20").unwrap();
+        assert_eq!(location.artifact_location.uri, "src/lib.rs");
+        assert_eq!(location.region.start_line, 10);
+        assert_eq!(location.region.start_column, 5);
+        assert_eq!(location.region.end_line, 12);
+        assert_eq!(location.region.end_column, 20);
+        assert!(SarifLog::parse_provenance("no location").is_none());
+    }
+}
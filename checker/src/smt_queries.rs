@@ -0,0 +1,203 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Supports writing the queries that MIRAI sends to the SMT solver to files, so that a wrong or
+// slow answer can be investigated with the solver alone, and replaying such files with a solver.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::smt_solver::{SmtResult, SmtSolver};
+
+/// File names are limited to 255 bytes on most file systems, so long summary keys are cut short.
+const MAX_KEY_LENGTH_IN_FILE_NAME: usize = 150;
+
+/// Where a query was made: the source file, line and column of the current span of a function.
+pub struct QueryLocation<'a> {
+    /// The summary key of the function being analyzed.
+    pub function: &'a str,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Writes a query, which is a standalone SMT-LIB2 script that ends with check-sat, to a new
+/// file in the directory. The file is named after the summary key of the function and the line
+/// and column of the query, and starts with comments that record the result and time taken.
+pub fn dump_query(
+    dir: &Path,
+    location: &QueryLocation<'_>,
+    script: &str,
+    result: &SmtResult,
+    time_taken: Duration,
+) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let key: String = location
+        .function
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '.'
+            }
+        })
+        .take(MAX_KEY_LENGTH_IN_FILE_NAME)
+        .collect();
+    let contents = format!(
+        "; function: {}\n; location: {}:{}:{}\n; result: {:?}\n; time: {} ms\n{}",
+        location.function,
+        location.file,
+        location.line,
+        location.column,
+        result,
+        time_taken.as_millis(),
+        script
+    );
+    // A function can make several queries at the same location, and can be analyzed by more
+    // than one thread, so the first name that is not taken yet is used.
+    let mut n = 0;
+    loop {
+        let path = dir.join(format!(
+            "{key}.{}.{}.{n}.smt2",
+            location.line, location.column
+        ));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// A query that was replayed with a solver.
+#[derive(Debug)]
+pub struct ReplayedQuery {
+    pub path: PathBuf,
+    /// The result recorded in the file, if there is one.
+    pub recorded_result: Option<SmtResult>,
+    /// The result of the solver that replayed the query.
+    pub result: SmtResult,
+}
+
+/// Replays the queries in the .smt2 files in the directory with the given solver, in the order
+/// of the file names. This is meant for tests that compare solvers, or versions of a solver,
+/// on the queries that MIRAI makes.
+pub fn replay_queries<SmtExpressionType>(
+    dir: &Path,
+    solver: &impl SmtSolver<SmtExpressionType>,
+) -> std::io::Result<Vec<ReplayedQuery>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "smt2")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let mut replayed_queries = vec![];
+    for path in paths {
+        let script = fs::read_to_string(&path)?;
+        let recorded_result =
+            script
+                .lines()
+                .find_map(|line| match line.strip_prefix("; result: ")? {
+                    "Satisfiable" => Some(SmtResult::Satisfiable),
+                    "Unsatisfiable" => Some(SmtResult::Unsatisfiable),
+                    "Undefined" => Some(SmtResult::Undefined),
+                    _ => None,
+                });
+        let result = solver.solve_script(&script);
+        replayed_queries.push(ReplayedQuery {
+            path,
+            recorded_result,
+            result,
+        });
+    }
+    Ok(replayed_queries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smt_solver::SolverStub;
+
+    #[test]
+    fn dumped_queries_are_replayed_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = QueryLocation {
+            function: "foo.bar",
+            file: "src/lib.rs".to_string(),
+            line: 7,
+            column: 5,
+        };
+        let script = "(declare-const x Int)\n(assert (< x 0))\n(check-sat)\n";
+        let first = dump_query(
+            dir.path(),
+            &location,
+            script,
+            &SmtResult::Satisfiable,
+            Duration::from_millis(3),
+        )
+        .unwrap();
+        let second = dump_query(
+            dir.path(),
+            &location,
+            script,
+            &SmtResult::Undefined,
+            Duration::from_millis(100),
+        )
+        .unwrap();
+        assert!(first.ends_with("foo.bar.7.5.0.smt2"));
+        assert!(second.ends_with("foo.bar.7.5.1.smt2"));
+        let replayed = replay_queries(dir.path(), &SolverStub::default()).unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[0].recorded_result, Some(SmtResult::Satisfiable));
+        assert_eq!(replayed[1].recorded_result, Some(SmtResult::Undefined));
+        assert_eq!(replayed[0].result, SmtResult::Undefined);
+    }
+
+    #[cfg(feature = "z3")]
+    #[test]
+    fn replayed_queries_are_solved_by_z3() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = QueryLocation {
+            function: "foo.bar",
+            file: "src/lib.rs".to_string(),
+            line: 7,
+            column: 5,
+        };
+        for script in [
+            "(declare-const x Int)\n(assert (< x 0))\n(check-sat)\n",
+            "(declare-const x Int)\n(assert (< x 0))\n(assert (> x 0))\n(check-sat)\n",
+        ] {
+            let time_taken = Duration::from_millis(1);
+            dump_query(
+                dir.path(),
+                &location,
+                script,
+                &SmtResult::Undefined,
+                time_taken,
+            )
+            .unwrap();
+        }
+        let solver = crate::z3_solver::Z3Solver::new();
+        let replayed = replay_queries(dir.path(), &solver).unwrap();
+        assert_eq!(replayed[0].result, SmtResult::Satisfiable);
+        assert_eq!(replayed[1].result, SmtResult::Unsatisfiable);
+    }
+}
@@ -3,9 +3,16 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use crate::expression::Expression;
+use std::rc::Rc;
 
-use mirai_annotations::{get_model_field, precondition, set_model_field};
+use crate::expression::{Expression, ExpressionType};
+use crate::options::{Options, SolverOption};
+use crate::path::Path;
+use crate::smtlib_solver::{SmtLibExpression, SmtLibSolver};
+#[cfg(feature = "z3")]
+use crate::z3_solver::{Z3ExpressionType, Z3Solver};
+
+use mirai_annotations::{assume_unreachable, get_model_field, precondition, set_model_field};
 use serde::{Deserialize, Serialize};
 
 /// The result of using the solver to solve an expression.
@@ -39,6 +46,13 @@ pub trait SmtSolver<SmtExpressionType> {
     /// assertions in the solver. Can only be called after self.solve return SmtResult::Satisfiable.
     fn get_model_as_string(&self) -> String;
 
+    /// Returns the value that the model found by the last satisfiable call of solve gives to
+    /// the variable for the given path, as a decimal numeral or as true or false, or None if
+    /// the model does not have a value for it. Call this before the context is changed.
+    fn get_model_value_for(&self, _path: &Rc<Path>, _var_type: ExpressionType) -> Option<String> {
+        None
+    }
+
     /// Provides a string that contains a listing of all of the definitions and assertions that
     /// have been added to the solver.
     fn get_solver_state_as_string(&self) -> String;
@@ -61,6 +75,12 @@ pub trait SmtSolver<SmtExpressionType> {
     /// current context are all true.
     fn solve(&self) -> SmtResult;
 
+    /// Solves a standalone SMT-LIB2 script that ends with check-sat, such as a query written by
+    /// --dump_smt_queries, without changing the current context.
+    fn solve_script(&self, _script: &str) -> SmtResult {
+        SmtResult::Undefined
+    }
+
     /// Establish if the given expression can be satisfied (or not) without changing the current context.
     fn solve_expression(&self, expression: &SmtExpressionType) -> SmtResult {
         self.set_backtrack_position();
@@ -106,3 +126,179 @@ impl SmtSolver<usize> for SolverStub {
         SmtResult::Undefined
     }
 }
+
+/// The solver selected with the --solver option.
+pub enum ConfiguredSolver {
+    #[cfg(feature = "z3")]
+    Z3(Z3Solver),
+    SmtLib(SmtLibSolver),
+    Stub(SolverStub),
+}
+
+/// An expression of the solver selected with the --solver option.
+#[derive(Debug)]
+pub enum ConfiguredExpression {
+    #[cfg(feature = "z3")]
+    Z3(Z3ExpressionType),
+    SmtLib(SmtLibExpression),
+    Stub(usize),
+}
+
+impl ConfiguredSolver {
+    pub fn new(options: &Options) -> ConfiguredSolver {
+        match &options.solver {
+            #[cfg(feature = "z3")]
+            SolverOption::Builtin => ConfiguredSolver::Z3(Z3Solver::with_limits(
+                options.solver_timeout,
+                options.solver_rlimit,
+            )),
+            #[cfg(not(feature = "z3"))]
+            SolverOption::Builtin => ConfiguredSolver::Stub(SolverStub::default()),
+            SolverOption::None => ConfiguredSolver::Stub(SolverStub::default()),
+            SolverOption::SmtLib(command) => {
+                ConfiguredSolver::SmtLib(SmtLibSolver::new(command.clone(), options.solver_timeout))
+            }
+        }
+    }
+}
+
+impl SmtSolver<ConfiguredExpression> for ConfiguredSolver {
+    fn as_debug_string(&self, expression: &ConfiguredExpression) -> String {
+        match (self, expression) {
+            #[cfg(feature = "z3")]
+            (ConfiguredSolver::Z3(solver), ConfiguredExpression::Z3(e)) => {
+                solver.as_debug_string(e)
+            }
+            (ConfiguredSolver::SmtLib(solver), ConfiguredExpression::SmtLib(e)) => {
+                solver.as_debug_string(e)
+            }
+            (ConfiguredSolver::Stub(solver), ConfiguredExpression::Stub(e)) => {
+                solver.as_debug_string(e)
+            }
+            _ => assume_unreachable!("expression of another solver"),
+        }
+    }
+
+    fn assert(&self, expression: &ConfiguredExpression) {
+        match (self, expression) {
+            #[cfg(feature = "z3")]
+            (ConfiguredSolver::Z3(solver), ConfiguredExpression::Z3(e)) => solver.assert(e),
+            (ConfiguredSolver::SmtLib(solver), ConfiguredExpression::SmtLib(e)) => solver.assert(e),
+            (ConfiguredSolver::Stub(solver), ConfiguredExpression::Stub(e)) => solver.assert(e),
+            _ => assume_unreachable!("expression of another solver"),
+        }
+    }
+
+    fn backtrack(&self) {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.backtrack(),
+            ConfiguredSolver::SmtLib(solver) => solver.backtrack(),
+            ConfiguredSolver::Stub(solver) => solver.backtrack(),
+        }
+    }
+
+    fn get_as_smt_predicate(&self, mirai_expression: &Expression) -> ConfiguredExpression {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => {
+                ConfiguredExpression::Z3(solver.get_as_smt_predicate(mirai_expression))
+            }
+            ConfiguredSolver::SmtLib(solver) => {
+                ConfiguredExpression::SmtLib(solver.get_as_smt_predicate(mirai_expression))
+            }
+            ConfiguredSolver::Stub(solver) => {
+                ConfiguredExpression::Stub(solver.get_as_smt_predicate(mirai_expression))
+            }
+        }
+    }
+
+    fn get_model_as_string(&self) -> String {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.get_model_as_string(),
+            ConfiguredSolver::SmtLib(solver) => solver.get_model_as_string(),
+            ConfiguredSolver::Stub(solver) => solver.get_model_as_string(),
+        }
+    }
+
+    fn get_model_value_for(&self, path: &Rc<Path>, var_type: ExpressionType) -> Option<String> {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.get_model_value_for(path, var_type),
+            ConfiguredSolver::SmtLib(solver) => solver.get_model_value_for(path, var_type),
+            ConfiguredSolver::Stub(solver) => solver.get_model_value_for(path, var_type),
+        }
+    }
+
+    fn get_solver_state_as_string(&self) -> String {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.get_solver_state_as_string(),
+            ConfiguredSolver::SmtLib(solver) => solver.get_solver_state_as_string(),
+            ConfiguredSolver::Stub(solver) => solver.get_solver_state_as_string(),
+        }
+    }
+
+    fn invert_predicate(&self, expression: &ConfiguredExpression) -> ConfiguredExpression {
+        match (self, expression) {
+            #[cfg(feature = "z3")]
+            (ConfiguredSolver::Z3(solver), ConfiguredExpression::Z3(e)) => {
+                ConfiguredExpression::Z3(solver.invert_predicate(e))
+            }
+            (ConfiguredSolver::SmtLib(solver), ConfiguredExpression::SmtLib(e)) => {
+                ConfiguredExpression::SmtLib(solver.invert_predicate(e))
+            }
+            (ConfiguredSolver::Stub(solver), ConfiguredExpression::Stub(e)) => {
+                ConfiguredExpression::Stub(solver.invert_predicate(e))
+            }
+            _ => assume_unreachable!("expression of another solver"),
+        }
+    }
+
+    fn set_backtrack_position(&self) {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.set_backtrack_position(),
+            ConfiguredSolver::SmtLib(solver) => solver.set_backtrack_position(),
+            ConfiguredSolver::Stub(solver) => solver.set_backtrack_position(),
+        }
+    }
+
+    fn solve(&self) -> SmtResult {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.solve(),
+            ConfiguredSolver::SmtLib(solver) => solver.solve(),
+            ConfiguredSolver::Stub(solver) => solver.solve(),
+        }
+    }
+
+    fn solve_script(&self, script: &str) -> SmtResult {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => solver.solve_script(script),
+            ConfiguredSolver::SmtLib(solver) => solver.solve_script(script),
+            ConfiguredSolver::Stub(solver) => solver.solve_script(script),
+        }
+    }
+}
+
+impl ConfiguredSolver {
+    /// Returns the assertions of the current context as a standalone SMT-LIB2 script that ends
+    /// with check-sat, or None if there is no solver.
+    pub fn get_query_as_smtlib(&self) -> Option<String> {
+        match self {
+            #[cfg(feature = "z3")]
+            ConfiguredSolver::Z3(solver) => Some(format!(
+                "{}(check-sat)\n",
+                solver.get_solver_state_as_string()
+            )),
+            ConfiguredSolver::SmtLib(solver) => Some(format!(
+                "{}(check-sat)\n",
+                solver.get_solver_state_as_string()
+            )),
+            ConfiguredSolver::Stub(..) => None,
+        }
+    }
+}
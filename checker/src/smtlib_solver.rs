@@ -0,0 +1,940 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// An SMT solver that runs as an external process, such as cvc5, yices or z3, and that
+// reads queries in the SMT-LIB2 language from its standard input. Every query is sent to a new
+// process as a complete script, so the solver need not support incremental solving.
+//
+// Integers are encoded with the theory of integers, so the solver must support the Int sort.
+// A solver that does not will report an error, which counts as an undefined result. Expressions
+// that cannot be encoded, such as floating point operations and most bitwise operations, become
+// uninterpreted constants, which means that the solver can only fail to prove things about them.
+// Left shifts are encoded with bit-vector operations, so the solver must also support the
+// int2bv and bv2nat conversions between integers and bit-vectors.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result};
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use log_derive::*;
+
+use crate::abstract_value::AbstractValue;
+use crate::abstract_value::AbstractValueTrait;
+use crate::constant_domain::ConstantDomain;
+use crate::expression::{Expression, ExpressionType};
+use crate::path::Path;
+use crate::smt_solver::SmtResult;
+use crate::smt_solver::SmtSolver;
+
+/// An SMT-LIB2 term.
+pub type SmtLibExpression = String;
+
+/// The time that the solver process gets to start up, in addition to the time-out of a query.
+const SOLVER_START_UP_TIME: Duration = Duration::from_millis(400);
+
+/// The commands that start every script.
+const SCRIPT_PRELUDE: &str = "(set-option :produce-models true)
+(set-logic ALL)
+(declare-sort Any 0)
+";
+
+/// 2^128, which does not fit into a u128.
+const TWO_TO_THE_128: &str = "340282366920938463463374607431768211456";
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Sort {
+    /// The sort of values that are not booleans or integers, such as references and floating
+    /// point numbers.
+    Any,
+    Bool,
+    Int,
+}
+
+impl Sort {
+    fn for_type(var_type: ExpressionType) -> Sort {
+        if var_type == ExpressionType::Bool {
+            Sort::Bool
+        } else if is_integral(var_type) {
+            Sort::Int
+        } else {
+            Sort::Any
+        }
+    }
+
+    fn as_smtlib(self) -> &'static str {
+        match self {
+            Sort::Any => "Any",
+            Sort::Bool => "Bool",
+            Sort::Int => "Int",
+        }
+    }
+}
+
+pub struct SmtLibSolver {
+    /// The solver binary, followed by its arguments.
+    command: Vec<String>,
+    /// The time after which the solver process is killed, if any.
+    time_out: Option<Duration>,
+    /// The names of the declared constants, keyed by the debug string of the path or expression
+    /// that they stand for, along with their sort.
+    constants: RefCell<HashMap<(String, Sort), String>>,
+    /// The declarations of the constants. These are never retracted, since a translated
+    /// expression can be asserted in any context.
+    declarations: RefCell<Vec<String>>,
+    /// The assertions of each context, starting with the outermost one.
+    assertions: RefCell<Vec<Vec<SmtLibExpression>>>,
+    /// The output of the get-model command after the last satisfiable query.
+    model: RefCell<String>,
+}
+
+impl Debug for SmtLibSolver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        "SmtLibSolver".fmt(f)
+    }
+}
+
+impl SmtLibSolver {
+    /// Creates a solver that runs the given command line and gives each query time_out_ms
+    /// milliseconds, or unlimited time if time_out_ms is 0.
+    #[logfn_inputs(TRACE)]
+    pub fn new(command: Vec<String>, time_out_ms: u64) -> SmtLibSolver {
+        let time_out =
+            (time_out_ms > 0).then(|| Duration::from_millis(time_out_ms) + SOLVER_START_UP_TIME);
+        SmtLibSolver {
+            command,
+            time_out,
+            constants: RefCell::new(HashMap::new()),
+            declarations: RefCell::new(Vec::new()),
+            assertions: RefCell::new(vec![Vec::new()]),
+            model: RefCell::new(String::new()),
+        }
+    }
+}
+
+impl SmtSolver<SmtLibExpression> for SmtLibSolver {
+    #[logfn_inputs(TRACE)]
+    fn as_debug_string(&self, expression: &SmtLibExpression) -> String {
+        expression.clone()
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn assert(&self, expression: &SmtLibExpression) {
+        if let Some(context) = self.assertions.borrow_mut().last_mut() {
+            context.push(expression.clone());
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn backtrack(&self) {
+        let mut assertions = self.assertions.borrow_mut();
+        if assertions.len() > 1 {
+            assertions.pop();
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_smt_predicate(&self, mirai_expression: &Expression) -> SmtLibExpression {
+        self.get_as_bool_term(mirai_expression)
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_model_as_string(&self) -> String {
+        self.model.borrow().clone()
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_model_value_for(&self, path: &Rc<Path>, var_type: ExpressionType) -> Option<String> {
+        let sort = Sort::for_type(var_type);
+        if sort == Sort::Any {
+            return None;
+        }
+        let key = (format!("{path:?}"), sort);
+        let name = self.constants.borrow().get(&key)?.clone();
+        get_value_from_model(&self.model.borrow(), &name)
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_solver_state_as_string(&self) -> String {
+        let mut script = String::from(SCRIPT_PRELUDE);
+        for declaration in self.declarations.borrow().iter() {
+            script.push_str(declaration);
+            script.push('\n');
+        }
+        for context in self.assertions.borrow().iter() {
+            for assertion in context {
+                script.push_str(&format!("(assert {assertion})\n"));
+            }
+        }
+        script
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn invert_predicate(&self, expression: &SmtLibExpression) -> SmtLibExpression {
+        format!("(not {expression})")
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn set_backtrack_position(&self) {
+        self.assertions.borrow_mut().push(Vec::new());
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn solve(&self) -> SmtResult {
+        let script = format!(
+            "{}(check-sat)\n(get-model)\n",
+            self.get_solver_state_as_string()
+        );
+        let Some(output) = self.run_solver(&script) else {
+            return SmtResult::Undefined;
+        };
+        let mut lines = output.lines();
+        let result = get_result_from(lines.next());
+        if result == SmtResult::Satisfiable {
+            *self.model.borrow_mut() = lines.collect::<Vec<&str>>().join("\n");
+        }
+        result
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn solve_script(&self, script: &str) -> SmtResult {
+        match self.run_solver(script) {
+            Some(output) => get_result_from(output.lines().next()),
+            None => SmtResult::Undefined,
+        }
+    }
+}
+
+/// Interprets the first line of the output of the solver, which answers check-sat.
+fn get_result_from(line: Option<&str>) -> SmtResult {
+    match line.map(str::trim) {
+        Some("sat") => SmtResult::Satisfiable,
+        Some("unsat") => SmtResult::Unsatisfiable,
+        _ => SmtResult::Undefined,
+    }
+}
+
+/// Returns the value of the named constant in the output of get-model, if it is a boolean or an
+/// integer literal, which can be negated.
+fn get_value_from_model(model: &str, name: &str) -> Option<String> {
+    let definition = format!("(define-fun {name} () ");
+    let start = model.find(&definition)? + definition.len();
+    // Skip the sort, which can be followed by a new line.
+    let rest = model[start..].trim_start();
+    let term = rest[rest.find(char::is_whitespace)?..].trim_start();
+    let (negated, literal) = match term.strip_prefix('(') {
+        Some(negation) => (true, negation.trim_start().strip_prefix('-')?.trim_start()),
+        None => (false, term),
+    };
+    let end = literal.find(|c: char| c.is_whitespace() || c == ')')?;
+    let literal = &literal[..end];
+    match literal {
+        "true" | "false" if !negated => Some(literal.to_string()),
+        _ if !literal.is_empty() && literal.chars().all(|c| c.is_ascii_digit()) => {
+            Some(if negated {
+                format!("-{literal}")
+            } else {
+                literal.to_string()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if the type is an integer type or char, which are both encoded as integers.
+fn is_integral(var_type: ExpressionType) -> bool {
+    var_type.is_integer() || var_type == ExpressionType::Char
+}
+
+/// Returns the smallest and largest values of an integral type.
+fn get_range_of(var_type: ExpressionType) -> Option<(i128, u128)> {
+    let min = match var_type.min_value() {
+        ConstantDomain::I128(v) => v,
+        ConstantDomain::U128(v) => i128::try_from(v).ok()?,
+        _ => return None,
+    };
+    let max = match var_type.max_value() {
+        ConstantDomain::I128(v) => u128::try_from(v).ok()?,
+        ConstantDomain::U128(v) => v,
+        _ => return None,
+    };
+    Some((min, max))
+}
+
+/// Returns 2^num_bits as an SMT-LIB2 numeral.
+fn get_power_of_two(num_bits: u8) -> String {
+    if num_bits >= 128 {
+        TWO_TO_THE_128.to_string()
+    } else {
+        (1u128 << num_bits).to_string()
+    }
+}
+
+/// Returns the value of the given integer type that has the two's complement representation
+/// of the unsigned term, which is a value of the unsigned type of the same size.
+fn get_from_unsigned(unsigned: SmtLibExpression, var_type: ExpressionType) -> SmtLibExpression {
+    if var_type.is_signed_integer() {
+        let modulus = get_power_of_two(var_type.bit_length());
+        let max = get_range_of(var_type).map(|(_, max)| max).unwrap_or(0);
+        format!("(ite (> {unsigned} {max}) (- {unsigned} {modulus}) {unsigned})")
+    } else {
+        unsigned
+    }
+}
+
+fn get_signed_numeral(value: i128) -> String {
+    if value < 0 {
+        format!("(- {})", value.unsigned_abs())
+    } else {
+        value.to_string()
+    }
+}
+
+impl SmtLibSolver {
+    /// Runs the solver on the script and returns its output, or None if the solver could not
+    /// be started or did not finish within the time-out.
+    fn run_solver(&self, script: &str) -> Option<String> {
+        let (program, arguments) = self.command.split_first()?;
+        let mut child = match Command::new(program)
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                debug!("could not start solver {}: {}", program, e);
+                return None;
+            }
+        };
+        let deadline = self.time_out.map(|time_out| Instant::now() + time_out);
+        // The output is read on another thread, so that the solver cannot block on a full pipe
+        // while this thread waits for it to finish.
+        let mut stdout = child.stdout.take()?;
+        let reader = thread::spawn(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_| output)
+        });
+        // The script is written on another thread as well, since the write blocks if the solver
+        // stops reading, and that must not keep the time-out from killing the solver. Killing
+        // it ends the write with an error, which ends the thread. The pipe is closed when the
+        // thread ends, which tells the solver that the script is complete.
+        if let Some(mut stdin) = child.stdin.take() {
+            let script = script.to_string();
+            thread::spawn(move || {
+                // If the solver stops reading, its output explains why.
+                let _ = stdin.write_all(script.as_bytes());
+            });
+        }
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if deadline.is_none_or(|deadline| Instant::now() < deadline) => {
+                    thread::sleep(Duration::from_millis(1))
+                }
+                _ => {
+                    debug!("solver {} timed out", program);
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+            }
+        }
+        reader.join().ok()?.ok()
+    }
+
+    /// Adds an assertion about a constant to the current context. Like the range checks of the
+    /// Z3 solver, this is a kind of lazy variable declaration.
+    fn assume(&self, assertion: SmtLibExpression) {
+        if let Some(context) = self.assertions.borrow_mut().last_mut() {
+            if !context.contains(&assertion) {
+                context.push(assertion);
+            }
+        }
+    }
+
+    /// Assumes that the integer constant is a value of the given type.
+    fn assume_in_range(&self, name: &str, var_type: ExpressionType) {
+        if let Some((min, max)) = get_range_of(var_type) {
+            self.assume(format!("(<= {} {name} {max})", get_signed_numeral(min)));
+        }
+    }
+
+    /// Returns the name of the constant of the given sort that stands for value, which is
+    /// declared when it is first used.
+    #[logfn_inputs(TRACE)]
+    fn get_constant_for<T>(&self, value: T, sort: Sort) -> String
+    where
+        T: Debug,
+    {
+        let key = (format!("{value:?}"), sort);
+        if let Some(name) = self.constants.borrow().get(&key) {
+            return name.clone();
+        }
+        let name = self.declare_constant(sort, &key.0);
+        self.constants.borrow_mut().insert(key, name.clone());
+        name
+    }
+
+    /// Returns the name of a new constant of the given sort, which can have any value.
+    #[logfn_inputs(TRACE)]
+    fn get_fresh_constant(&self, sort: Sort) -> String {
+        self.declare_constant(sort, "fresh")
+    }
+
+    fn declare_constant(&self, sort: Sort, comment: &str) -> String {
+        let mut declarations = self.declarations.borrow_mut();
+        let name = format!("x{}", declarations.len());
+        // The comment relates the constant to the MIRAI value when debugging.
+        declarations.push(format!(
+            "(declare-const {name} {}) ; {}",
+            sort.as_smtlib(),
+            comment.replace(['\n', '\r'], " ")
+        ));
+        name
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_term_of_sort(&self, expression: &Expression, sort: Sort) -> SmtLibExpression {
+        match sort {
+            Sort::Any => self.get_as_any_term(expression),
+            Sort::Bool => self.get_as_bool_term(expression),
+            Sort::Int => self.get_as_int_term(expression),
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_any_term(&self, expression: &Expression) -> SmtLibExpression {
+        match expression {
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => format!(
+                "(ite {} {} {})",
+                self.get_as_bool_term(&condition.expression),
+                self.get_as_any_term(&consequent.expression),
+                self.get_as_any_term(&alternate.expression)
+            ),
+            Expression::InitialParameterValue { path, .. }
+            | Expression::UninterpretedCall { path, .. }
+            | Expression::UnknownModelField { path, .. }
+            | Expression::UnknownTagField { path }
+            | Expression::Variable { path, .. }
+            | Expression::WidenedJoin { path, .. } => self.get_constant_for(path, Sort::Any),
+            Expression::Join { left, right } => format!(
+                "(ite {} {} {})",
+                self.get_fresh_constant(Sort::Bool),
+                self.get_as_any_term(&left.expression),
+                self.get_as_any_term(&right.expression)
+            ),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => self.general_switch(discriminator, cases, default, Sort::Any),
+            Expression::TaggedExpression { operand, .. } => {
+                self.get_as_any_term(&operand.expression)
+            }
+            Expression::Top | Expression::Bottom => self.get_fresh_constant(Sort::Any),
+            _ => self.get_constant_for(expression, Sort::Any),
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_bool_term(&self, expression: &Expression) -> SmtLibExpression {
+        match expression {
+            Expression::AddOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("+", left, right, *result_type, expression),
+            Expression::And { left, right } => format!(
+                "(and {} {})",
+                self.get_as_bool_term(&left.expression),
+                self.get_as_bool_term(&right.expression)
+            ),
+            Expression::CompileTimeConstant(ConstantDomain::False) => "false".to_string(),
+            Expression::CompileTimeConstant(ConstantDomain::True) => "true".to_string(),
+            Expression::CompileTimeConstant(ConstantDomain::U128(v)) => (*v != 0).to_string(),
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => format!(
+                "(ite {} {} {})",
+                self.get_as_bool_term(&condition.expression),
+                self.get_as_bool_term(&consequent.expression),
+                self.get_as_bool_term(&alternate.expression)
+            ),
+            Expression::Equals { left, right } => self.relational("=", left, right, expression),
+            Expression::GreaterOrEqual { left, right } => {
+                self.relational(">=", left, right, expression)
+            }
+            Expression::GreaterThan { left, right } => {
+                self.relational(">", left, right, expression)
+            }
+            Expression::InitialParameterValue { path, .. }
+            | Expression::UninterpretedCall { path, .. }
+            | Expression::UnknownModelField { path, .. }
+            | Expression::UnknownTagField { path }
+            | Expression::Variable { path, .. }
+            | Expression::WidenedJoin { path, .. }
+                if !is_integral(expression.infer_type()) =>
+            {
+                self.get_constant_for(path, Sort::Bool)
+            }
+            Expression::Join { left, right } => format!(
+                "(ite {} {} {})",
+                self.get_fresh_constant(Sort::Bool),
+                self.get_as_bool_term(&left.expression),
+                self.get_as_bool_term(&right.expression)
+            ),
+            Expression::LessOrEqual { left, right } => {
+                self.relational("<=", left, right, expression)
+            }
+            Expression::LessThan { left, right } => self.relational("<", left, right, expression),
+            Expression::LogicalNot { operand } => {
+                format!("(not {})", self.get_as_bool_term(&operand.expression))
+            }
+            Expression::MulOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("*", left, right, *result_type, expression),
+            Expression::Ne { left, right } => {
+                format!("(not {})", self.relational("=", left, right, expression))
+            }
+            Expression::Or { left, right } => format!(
+                "(or {} {})",
+                self.get_as_bool_term(&left.expression),
+                self.get_as_bool_term(&right.expression)
+            ),
+            Expression::ShlOverflows {
+                right, result_type, ..
+            }
+            | Expression::ShrOverflows {
+                right, result_type, ..
+            } => format!(
+                "(>= {} {})",
+                self.get_as_int_term(&right.expression),
+                result_type.bit_length()
+            ),
+            Expression::SubOverflows {
+                left,
+                right,
+                result_type,
+            } => self.overflows("-", left, right, *result_type, expression),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => self.general_switch(discriminator, cases, default, Sort::Bool),
+            Expression::TaggedExpression { operand, .. } => {
+                self.get_as_bool_term(&operand.expression)
+            }
+            Expression::Top | Expression::Bottom => self.get_fresh_constant(Sort::Bool),
+            _ => {
+                if is_integral(expression.infer_type()) {
+                    format!("(not (= {} 0))", self.get_as_int_term(expression))
+                } else {
+                    debug!("uninterpreted expression: {:?}", expression);
+                    self.get_constant_for(expression, Sort::Bool)
+                }
+            }
+        }
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn get_as_int_term(&self, expression: &Expression) -> SmtLibExpression {
+        match expression {
+            Expression::Add { left, right } => self.int_binary("+", left, right, expression),
+            Expression::BitAnd { left, right } => {
+                if let Expression::CompileTimeConstant(ConstantDomain::U128(v)) = left.expression {
+                    if v < u128::MAX && (v + 1).is_power_of_two() {
+                        // right & (2^n - 1) is the remainder of right divided by 2^n.
+                        return format!(
+                            "(mod {} {})",
+                            self.get_as_int_term(&right.expression),
+                            v + 1
+                        );
+                    }
+                }
+                self.get_opaque_int(expression, expression.infer_type())
+            }
+            Expression::Cast {
+                operand,
+                target_type,
+            } => self.int_cast(&operand.expression, *target_type),
+            Expression::CompileTimeConstant(const_domain) => match const_domain {
+                ConstantDomain::Char(v) => u32::from(*v).to_string(),
+                ConstantDomain::False => "0".to_string(),
+                ConstantDomain::I128(v) => get_signed_numeral(*v),
+                ConstantDomain::True => "1".to_string(),
+                ConstantDomain::U128(v) => v.to_string(),
+                _ => self.get_constant_for(const_domain, Sort::Int),
+            },
+            Expression::ConditionalExpression {
+                condition,
+                consequent,
+                alternate,
+            } => format!(
+                "(ite {} {} {})",
+                self.get_as_bool_term(&condition.expression),
+                self.get_as_int_term(&consequent.expression),
+                self.get_as_int_term(&alternate.expression)
+            ),
+            Expression::Div { left, right } => {
+                if is_float(left) || is_float(right) {
+                    return self.get_opaque_int(expression, expression.infer_type());
+                }
+                let left_term = self.get_as_int_term(&left.expression);
+                let right_term = self.get_as_int_term(&right.expression);
+                // Rust rounds towards zero, whereas div is Euclidean division.
+                format!(
+                    "(ite (< {left_term} 0) (- (div (- {left_term}) {right_term})) \
+                     (div {left_term} {right_term}))"
+                )
+            }
+            Expression::InitialParameterValue { path, .. }
+            | Expression::UninterpretedCall { path, .. }
+            | Expression::UnknownModelField { path, .. }
+            | Expression::UnknownTagField { path }
+            | Expression::Variable { path, .. }
+                if expression.infer_type() != ExpressionType::Bool =>
+            {
+                self.get_opaque_int(path, expression.infer_type())
+            }
+            Expression::Join { left, right } => format!(
+                "(ite {} {} {})",
+                self.get_fresh_constant(Sort::Bool),
+                self.get_as_int_term(&left.expression),
+                self.get_as_int_term(&right.expression)
+            ),
+            Expression::Mul { left, right } => self.int_binary("*", left, right, expression),
+            Expression::Neg { operand } => {
+                if is_float(operand) {
+                    return self.get_opaque_int(expression, expression.infer_type());
+                }
+                format!("(- {})", self.get_as_int_term(&operand.expression))
+            }
+            Expression::Rem { left, right } => {
+                if is_float(left) || is_float(right) {
+                    return self.get_opaque_int(expression, expression.infer_type());
+                }
+                let left_term = self.get_as_int_term(&left.expression);
+                let right_term = self.get_as_int_term(&right.expression);
+                // The remainder has the sign of the dividend.
+                format!(
+                    "(ite (< {left_term} 0) (- (mod (- {left_term}) (abs {right_term}))) \
+                     (mod {left_term} (abs {right_term})))"
+                )
+            }
+            Expression::Shl { left, right } => self.int_shl(left, right, expression),
+            Expression::Shr { left, right } => match &right.expression {
+                Expression::CompileTimeConstant(ConstantDomain::U128(n)) if *n < 128 => format!(
+                    "(div {} {})",
+                    self.get_as_int_term(&left.expression),
+                    1u128 << n
+                ),
+                _ => self.get_opaque_int(expression, expression.infer_type()),
+            },
+            Expression::Sub { left, right } => self.int_binary("-", left, right, expression),
+            Expression::Switch {
+                discriminator,
+                cases,
+                default,
+            } => self.general_switch(discriminator, cases, default, Sort::Int),
+            Expression::TaggedExpression { operand, .. } => {
+                self.get_as_int_term(&operand.expression)
+            }
+            Expression::Top | Expression::Bottom => self.get_fresh_constant(Sort::Int),
+            Expression::Transmute {
+                operand,
+                target_type,
+            } => {
+                if is_integral(*target_type) {
+                    self.int_cast(&operand.expression, *target_type)
+                } else {
+                    self.get_as_int_term(&operand.expression)
+                }
+            }
+            Expression::WidenedJoin { path, operand } => self.int_widened(path, operand),
+            _ => {
+                if expression.infer_type() == ExpressionType::Bool {
+                    format!("(ite {} 1 0)", self.get_as_bool_term(expression))
+                } else {
+                    debug!("uninterpreted expression: {:?}", expression);
+                    self.get_opaque_int(expression, expression.infer_type())
+                }
+            }
+        }
+    }
+
+    /// Returns an integer constant that stands for value, which is assumed to be a value of
+    /// the given type.
+    fn get_opaque_int<T>(&self, value: T, var_type: ExpressionType) -> SmtLibExpression
+    where
+        T: Debug,
+    {
+        let name = self.get_constant_for(value, Sort::Int);
+        if is_integral(var_type) {
+            self.assume_in_range(&name, var_type);
+        }
+        name
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn general_switch(
+        &self,
+        discriminator: &Rc<AbstractValue>,
+        cases: &[(Rc<AbstractValue>, Rc<AbstractValue>)],
+        default: &Rc<AbstractValue>,
+        result_sort: Sort,
+    ) -> SmtLibExpression {
+        let discriminator_sort = Sort::for_type(discriminator.expression.infer_type());
+        let discriminator_term =
+            self.get_as_term_of_sort(&discriminator.expression, discriminator_sort);
+        let default_term = self.get_as_term_of_sort(&default.expression, result_sort);
+        cases
+            .iter()
+            .fold(default_term, |acc_term, (case_val, case_result)| {
+                format!(
+                    "(ite (= {discriminator_term} {}) {} {acc_term})",
+                    self.get_as_term_of_sort(&case_val.expression, discriminator_sort),
+                    self.get_as_term_of_sort(&case_result.expression, result_sort)
+                )
+            })
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn int_binary(
+        &self,
+        operator: &str,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+        expression: &Expression,
+    ) -> SmtLibExpression {
+        if is_float(left) || is_float(right) {
+            return self.get_opaque_int(expression, expression.infer_type());
+        }
+        format!(
+            "({operator} {} {})",
+            self.get_as_int_term(&left.expression),
+            self.get_as_int_term(&right.expression)
+        )
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn int_cast(&self, operand: &Expression, target_type: ExpressionType) -> SmtLibExpression {
+        let operand_type = operand.infer_type();
+        if !is_integral(target_type) || !is_integral(operand_type) {
+            if operand_type == ExpressionType::Bool {
+                return format!("(ite {} 1 0)", self.get_as_bool_term(operand));
+            }
+            // Could be a thin pointer, an enum or a floating point number.
+            return self.get_opaque_int((operand, target_type), target_type);
+        }
+        let operand_term = self.get_as_int_term(operand);
+        match (get_range_of(operand_type), get_range_of(target_type)) {
+            (Some((operand_min, operand_max)), Some((target_min, target_max)))
+                if target_min <= operand_min && operand_max <= target_max =>
+            {
+                operand_term
+            }
+            _ => {
+                // Truncate the two's complement representation of the value.
+                let modulus = get_power_of_two(target_type.bit_length());
+                get_from_unsigned(format!("(mod {operand_term} {modulus})"), target_type)
+            }
+        }
+    }
+
+    /// Encodes a left shift with the bit-vector operation bvshl, so that the bits that are
+    /// shifted out of the value are lost, which they are not if the value is multiplied by a
+    /// power of two.
+    #[logfn_inputs(TRACE)]
+    fn int_shl(
+        &self,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+        expression: &Expression,
+    ) -> SmtLibExpression {
+        let result_type = expression.infer_type();
+        if !result_type.is_integer() || !right.expression.infer_type().is_integer() {
+            return self.get_opaque_int(expression, result_type);
+        }
+        let num_bits = result_type.bit_length();
+        let bit_vector = format!(
+            "(bvshl ((_ int2bv {num_bits}) {}) ((_ int2bv {num_bits}) {}))",
+            self.get_as_int_term(&left.expression),
+            self.get_as_int_term(&right.expression)
+        );
+        get_from_unsigned(format!("(bv2nat {bit_vector})"), result_type)
+    }
+
+    #[logfn_inputs(TRACE)]
+    fn int_widened(&self, path: &Rc<Path>, operand: &Rc<AbstractValue>) -> SmtLibExpression {
+        let var_type = operand.expression.infer_type();
+        let name = self.get_constant_for(path, Sort::Int);
+        if is_integral(var_type) {
+            let interval = operand.widen(path).get_as_interval();
+            if !interval.is_bottom() {
+                if let Some(lower_bound) = interval.lower_bound() {
+                    self.assume(format!("(<= {} {name})", get_signed_numeral(lower_bound)));
+                }
+                if let Some(upper_bound) = interval.upper_bound() {
+                    self.assume(format!("(<= {name} {})", get_signed_numeral(upper_bound)));
+                }
+            }
+        }
+        name
+    }
+
+    /// Returns a term that is true if the result of the operation is out of the range of the
+    /// result type, while the operands are in range.
+    #[logfn_inputs(TRACE)]
+    fn overflows(
+        &self,
+        operator: &str,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+        result_type: ExpressionType,
+        expression: &Expression,
+    ) -> SmtLibExpression {
+        let Some((min, max)) = get_range_of(result_type) else {
+            return self.get_constant_for(expression, Sort::Bool);
+        };
+        let min = get_signed_numeral(min);
+        let left_term = self.get_as_int_term(&left.expression);
+        let right_term = self.get_as_int_term(&right.expression);
+        format!(
+            "(and (<= {min} {left_term} {max}) (<= {min} {right_term} {max}) \
+             (not (<= {min} ({operator} {left_term} {right_term}) {max})))"
+        )
+    }
+
+    /// Encodes a comparison of integers, booleans or other values. Comparisons of floating
+    /// point numbers are not encoded, since the encoding of these numbers ignores NaN.
+    #[logfn_inputs(TRACE)]
+    fn relational(
+        &self,
+        operator: &str,
+        left: &Rc<AbstractValue>,
+        right: &Rc<AbstractValue>,
+        expression: &Expression,
+    ) -> SmtLibExpression {
+        let left_type = left.expression.infer_type();
+        let right_type = right.expression.infer_type();
+        if left_type.is_floating_point_number() || right_type.is_floating_point_number() {
+            self.get_constant_for(expression, Sort::Bool)
+        } else if is_integral(left_type) || is_integral(right_type) {
+            format!(
+                "({operator} {} {})",
+                self.get_as_int_term(&left.expression),
+                self.get_as_int_term(&right.expression)
+            )
+        } else if operator != "=" {
+            self.get_constant_for(expression, Sort::Bool)
+        } else if left_type == ExpressionType::Bool && right_type == ExpressionType::Bool {
+            format!(
+                "(= {} {})",
+                self.get_as_bool_term(&left.expression),
+                self.get_as_bool_term(&right.expression)
+            )
+        } else {
+            format!(
+                "(= {} {})",
+                self.get_as_any_term(&left.expression),
+                self.get_as_any_term(&right.expression)
+            )
+        }
+    }
+}
+
+fn is_float(value: &Rc<AbstractValue>) -> bool {
+    value.expression.infer_type().is_floating_point_number()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_read_from_the_model() {
+        let model = "(\n(define-fun x1 () Int (- 42))\n(define-fun x10 () Bool\n    true)\n\
+                     (define-fun x2 () Int 7)\n(define-fun x3 () Any (as @Any_0 Any))\n)";
+        assert_eq!(get_value_from_model(model, "x1").as_deref(), Some("-42"));
+        assert_eq!(get_value_from_model(model, "x10").as_deref(), Some("true"));
+        assert_eq!(get_value_from_model(model, "x2").as_deref(), Some("7"));
+        assert_eq!(get_value_from_model(model, "x3"), None);
+        assert_eq!(get_value_from_model(model, "x4"), None);
+    }
+
+    /// Returns a solver for each of the solver binaries that are on the path, so that the tests
+    /// that run a solver do nothing where none is installed.
+    fn installed_solvers() -> Vec<SmtLibSolver> {
+        let paths = std::env::var_os("PATH").unwrap_or_default();
+        [["z3", "-in"], ["cvc5", "--lang=smt2"]]
+            .into_iter()
+            .filter(|command| {
+                std::env::split_paths(&paths).any(|dir| dir.join(command[0]).is_file())
+            })
+            .map(|command| {
+                SmtLibSolver::new(command.iter().map(|s| s.to_string()).collect(), 10_000)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn satisfiable_and_unsatisfiable_queries_round_trip() {
+        for solver in installed_solvers() {
+            let x = solver.get_fresh_constant(Sort::Int);
+            solver.assert(&format!("(> {x} 41)"));
+            assert_eq!(solver.solve(), SmtResult::Satisfiable);
+            assert!(get_value_from_model(&solver.get_model_as_string(), &x).is_some());
+            solver.set_backtrack_position();
+            solver.assert(&format!("(< {x} 0)"));
+            assert_eq!(solver.solve(), SmtResult::Unsatisfiable);
+            solver.backtrack();
+            assert_eq!(solver.solve(), SmtResult::Satisfiable);
+            assert_eq!(
+                solver.solve_script("(assert (> 1 2))\n(check-sat)\n"),
+                SmtResult::Unsatisfiable
+            );
+        }
+    }
+
+    #[test]
+    fn left_shifts_lose_the_bits_that_are_shifted_out() {
+        let solver = SmtLibSolver::new(vec![], 0);
+        let value = AbstractValue::make_from(
+            Expression::Cast {
+                operand: Rc::new(ConstantDomain::U128(200).into()),
+                target_type: ExpressionType::U8,
+            },
+            1,
+        );
+        let shift = Expression::Shl {
+            left: value,
+            right: Rc::new(ConstantDomain::U128(1).into()),
+        };
+        let term = solver.get_as_int_term(&shift);
+        assert_eq!(
+            term,
+            "(bv2nat (bvshl ((_ int2bv 8) (mod 200 256)) ((_ int2bv 8) 1)))"
+        );
+        for solver in installed_solvers() {
+            solver.assert(&format!("(not (= {term} 144))"));
+            assert_eq!(solver.solve(), SmtResult::Unsatisfiable);
+        }
+    }
+}
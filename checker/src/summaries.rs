@@ -4,13 +4,14 @@
 // LICENSE file in the root directory of this source tree.
 
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env::current_dir;
 use std::fmt::{Debug, Formatter, Result};
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use itertools::Itertools;
 use log_derive::{logfn, logfn_inputs};
@@ -25,8 +26,9 @@ use rustc_span::Span;
 use crate::abstract_value::AbstractValue;
 use crate::abstract_value::AbstractValueTrait;
 use crate::constant_domain::FunctionReference;
+use crate::diagnostic_rules::DiagnosticRule;
 use crate::environment::Environment;
-use crate::expression::Expression;
+use crate::expression::{Expression, WithParameterNames};
 use crate::path::{Path, PathEnum, PathRoot, PathSelector};
 use crate::utils;
 
@@ -114,6 +116,11 @@ pub struct Precondition {
     pub condition: Rc<AbstractValue>,
     /// A diagnostic message to issue if the precondition is not met.
     pub message: Rc<str>,
+    /// The rule of the diagnostic to issue if the precondition is not met, which is that of the
+    /// check from which the precondition was inferred. It is not part of the serialized layout of
+    /// a precondition, see PreconditionRules.
+    #[serde(skip, default = "Precondition::default_rule")]
+    pub rule: DiagnosticRule,
     /// The source location of the precondition definition (or the source expression/statement that
     /// would panic if the precondition is not met). This is in textual form because it needs to be
     /// persistable and crate independent.
@@ -129,7 +136,57 @@ pub struct Precondition {
     pub spans: Vec<rustc_span::Span>,
 }
 
+impl Precondition {
+    /// The rule of a precondition that was stored without one, which is the rule of the
+    /// preconditions that are written down with precondition!, such as the standard contracts.
+    fn default_rule() -> DiagnosticRule {
+        DiagnosticRule::UnsatisfiedPrecondition
+    }
+}
+
+/// The rules of the preconditions of a summary, in the same order.
+/// Rules were added to preconditions after stores such as the embedded one were written, so they
+/// are appended to the serialized summary rather than being part of it. Readers that predate them
+/// ignore the trailing bytes and summaries stored without them get the default rule.
+#[derive(Deserialize, Serialize)]
+struct PreconditionRules {
+    version: u32,
+    rules: Vec<DiagnosticRule>,
+}
+
+/// Incremented whenever the serialized form of PreconditionRules changes.
+const PRECONDITION_RULES_VERSION: u32 = 1;
+
 impl Summary {
+    /// Serializes the summary for a summary store, followed by the rules of its preconditions.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        let mut bytes = bincode::serialize(self)?;
+        let rules = PreconditionRules {
+            version: PRECONDITION_RULES_VERSION,
+            rules: self.preconditions.iter().map(|p| p.rule).collect(),
+        };
+        bincode::serialize_into(&mut bytes, &rules)?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a summary that was serialized by to_bytes, or by a build of MIRAI that did
+    /// not yet append the rules of its preconditions.
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Summary> {
+        let mut reader = bytes;
+        let mut summary: Summary = bincode::deserialize_from(&mut reader)?;
+        if reader.is_empty() {
+            return Ok(summary);
+        }
+        if let Ok(PreconditionRules { version, rules }) = bincode::deserialize_from(&mut reader) {
+            if version == PRECONDITION_RULES_VERSION && rules.len() == summary.preconditions.len() {
+                for (precondition, rule) in summary.preconditions.iter_mut().zip(rules) {
+                    precondition.rule = rule;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
     #[logfn_inputs(TRACE)]
     pub fn is_subset_of(&self, other: &Summary) -> bool {
         if !Self::is_subset_of_preconditions(&self.preconditions[0..], &other.preconditions[0..]) {
@@ -398,11 +455,71 @@ impl Hash for CallSiteKey<'_> {
     }
 }
 
+/// The key under which the header of a summary store is stored. Summary keys start with a
+/// crate name, so they never collide with this.
+pub const STORE_HEADER_KEY: &str = "$mirai_store_header";
+
+/// A tar archive of a summary store with the summaries of standard library functions, which are
+/// computed from mirai/standard_contracts by rebuild_std.sh.
+const EMBEDDED_SUMMARY_STORE: &[u8] = include_bytes!("../../binaries/summary_store.tar");
+
+/// Incremented whenever a change to Summary, or to any of the types it contains, changes the
+/// serialized form of summaries.
+const STORE_FORMAT_VERSION: u32 = 1;
+
+/// Describes the build of MIRAI that wrote a summary store.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StoreHeader {
+    /// The version of the layout of serialized summaries.
+    pub format_version: u32,
+    /// The version of MIRAI.
+    pub mirai_version: String,
+    /// The version of the compiler that MIRAI was built with, which determines the standard
+    /// library that summaries have been computed for.
+    pub rustc_version: String,
+    /// A hash of the sources of the standard contracts.
+    pub standard_contracts_hash: String,
+}
+
+impl StoreHeader {
+    /// The header for stores written by this build of MIRAI.
+    pub fn current() -> StoreHeader {
+        StoreHeader {
+            format_version: STORE_FORMAT_VERSION,
+            mirai_version: env!("CARGO_PKG_VERSION").to_string(),
+            rustc_version: env!("MIRAI_RUSTC_VERSION").to_string(),
+            standard_contracts_hash: env!("MIRAI_STANDARD_CONTRACTS_HASH").to_string(),
+        }
+    }
+
+    /// Reads the header of the given store, if it has one.
+    /// The header is stored as JSON, so that it can be read whatever the summary layout.
+    pub fn read(db: &Db) -> Option<StoreHeader> {
+        let value = db.get(STORE_HEADER_KEY.as_bytes()).ok()??;
+        serde_json::from_slice(value.deref()).ok()
+    }
+
+    /// Writes the header to the given store. It is flushed right away, so that a store that is
+    /// archived when the analysis is done, such as the embedded store built by rebuild_std.sh,
+    /// always has it, which saves later runs from having to check every summary.
+    fn write(&self, db: &Db) {
+        let json = serde_json::to_vec(self).expect("serializable header");
+        if let Err(e) = db.insert(STORE_HEADER_KEY.as_bytes(), json) {
+            warn!("unable to write summary store header: {e:?}");
+        } else if let Err(e) = db.flush() {
+            warn!("unable to flush summary store header: {e:?}");
+        }
+    }
+}
+
 /// A database and collection of in-memory caches for function summaries.
 pub struct SummaryCache<'tcx> {
     /// The sled database that stores the summaries when persisted between runs.
     /// Chiefly used to store summaries for rust standard library functions that have no MIR.
     db: Db,
+    /// A read-only store, typically shared by a team, with summaries for functions of other
+    /// crates. It is consulted when db has no summary for such a function.
+    shared_store: Option<Arc<SharedStore>>,
     /// Functions that are entry points have def_ids but no function_id, because they are not
     /// derived from function references, have their summaries cached here.
     def_id_cache: HashMap<DefId, Summary>,
@@ -456,8 +573,18 @@ impl<'tcx> SummaryCache<'tcx> {
             debug!("{} ", err);
             assume_unreachable!();
         });
-        SummaryCache {
+        Self::validate_store(&db);
+        Self::with_stores(SummaryStores {
             db,
+            shared_store: None,
+        })
+    }
+
+    /// Creates a new summary cache with empty in-memory caches that uses the given stores.
+    pub fn with_stores(stores: SummaryStores) -> SummaryCache<'tcx> {
+        SummaryCache {
+            db: stores.db,
+            shared_store: stores.shared_store,
             def_id_cache: HashMap::new(),
             function_id_cache: HashMap::new(),
             call_site_cache: HashMap::new(),
@@ -466,6 +593,139 @@ impl<'tcx> SummaryCache<'tcx> {
         }
     }
 
+    /// Returns the persistent stores used by this cache, so that they can be shared with caches
+    /// that are used by other threads. Sled databases can be used by several threads at once.
+    pub fn stores(&self) -> SummaryStores {
+        SummaryStores {
+            db: self.db.clone(),
+            shared_store: self.shared_store.clone(),
+        }
+    }
+
+    /// Adds a read-only store with summaries for the functions of other crates, which is
+    /// consulted when the project store has no summary for such a function.
+    /// The path can be a directory that contains a .summary_store.sled directory, such a
+    /// directory itself, or a tar archive of such a directory. The store is never modified.
+    /// Its content is copied to the given cache directory the first time it is used, so that
+    /// the other crates of the build, which may be analyzed concurrently, can read the copy.
+    pub fn use_shared_store(
+        &mut self,
+        path: &std::path::Path,
+        cache_directory: &std::path::Path,
+        local_crate_name: &str,
+    ) -> std::result::Result<(), String> {
+        let shared_store = SharedStore::open(path, cache_directory, local_crate_name)?;
+        let current = StoreHeader::current();
+        match shared_store.header() {
+            Some(header)
+                if header.format_version == current.format_version
+                    && header.rustc_version == current.rustc_version
+                    && header.standard_contracts_hash == current.standard_contracts_hash => {}
+            Some(header) => {
+                return Err(format!(
+                    "the store was written by MIRAI {} built with {}",
+                    header.mirai_version, header.rustc_version
+                ));
+            }
+            None => return Err("the store has no header".to_string()),
+        }
+        self.shared_store = Some(Arc::new(shared_store));
+        Ok(())
+    }
+
+    /// Makes sure that the store only holds summaries that can be used by this build of MIRAI.
+    /// If the store was written by a build with a different summary layout, compiler or
+    /// standard contracts, all of its summaries are discarded. If only the version of MIRAI
+    /// differs, or the store predates store headers, the summaries that can still be decoded
+    /// are kept. Either way, the store is then marked as belonging to this build.
+    fn validate_store(db: &Db) {
+        let current = StoreHeader::current();
+        match StoreHeader::read(db) {
+            Some(header) if header == current => return,
+            Some(header)
+                if header.format_version == current.format_version
+                    && header.rustc_version == current.rustc_version
+                    && header.standard_contracts_hash == current.standard_contracts_hash =>
+            {
+                info!(
+                    "summary store was written by MIRAI {}, keeping summaries that can be decoded",
+                    header.mirai_version
+                );
+                Self::remove_undecodable_summaries(db);
+            }
+            Some(header) => {
+                info!("discarding summary store written by {:?}", header);
+                if let Err(e) = db.clear() {
+                    warn!("unable to clear summary store: {e:?}");
+                }
+                // A store that is shared between runs is not seeded again, so the summaries of
+                // the standard library must be restored here.
+                if let Err(e) = Self::add_embedded_summaries(db) {
+                    warn!("unable to add the embedded summaries to the summary store: {e}");
+                }
+            }
+            None => Self::remove_undecodable_summaries(db),
+        }
+        current.write(db);
+    }
+
+    /// Adds the summaries of the embedded tar file that can be used by this build of MIRAI to the
+    /// given store. They are only used if the embedded store was written by a build with the
+    /// same summary layout, compiler and standard contracts, or if it predates store headers,
+    /// in which case only the summaries that can be decoded are used.
+    fn add_embedded_summaries(db: &Db) -> std::result::Result<(), String> {
+        use tar::Archive;
+
+        let directory = tempfile::TempDir::new().map_err(|e| e.to_string())?;
+        Archive::new(EMBEDDED_SUMMARY_STORE)
+            .unpack(directory.path())
+            .map_err(|e| e.to_string())?;
+        let embedded = Config::default()
+            .path(directory.path().join(".summary_store.sled"))
+            .open()
+            .map_err(|e| e.to_string())?;
+        let current = StoreHeader::current();
+        let check_summaries = match StoreHeader::read(&embedded) {
+            Some(header)
+                if header.format_version == current.format_version
+                    && header.rustc_version == current.rustc_version
+                    && header.standard_contracts_hash == current.standard_contracts_hash =>
+            {
+                false
+            }
+            Some(header) => {
+                info!("not using embedded summaries written by {:?}", header);
+                return Ok(());
+            }
+            None => true,
+        };
+        for (key, value) in embedded.iter().flatten() {
+            if key.as_ref() == STORE_HEADER_KEY.as_bytes()
+                || (check_summaries && Summary::from_bytes(value.deref()).is_err())
+            {
+                continue;
+            }
+            db.insert(key, value).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Removes every entry that cannot be decoded as a summary with the current layout.
+    fn remove_undecodable_summaries(db: &Db) {
+        for (key, value) in db.iter().flatten() {
+            if key.as_ref() == STORE_HEADER_KEY.as_bytes() {
+                continue;
+            }
+            if Summary::from_bytes(value.deref()).is_err() {
+                debug!(
+                    "removing undecodable summary for {}",
+                    String::from_utf8_lossy(&key)
+                );
+                let _ = db.remove(key);
+            }
+        }
+    }
+
     /// Creates a Sled database at the given directory path, if it does not already exist.
     /// The initial value of the database contains summaries of standard library functions.
     /// The code used to create these summaries are mirai/standard_contracts.
@@ -487,8 +747,7 @@ impl<'tcx> SummaryCache<'tcx> {
             {
                 let tar_path = directory_path.join(".summary_store.tar");
                 let mut tar_file = File::create(tar_path.clone()).unwrap();
-                let bytes = include_bytes!("../../binaries/summary_store.tar");
-                tar_file.write_all(bytes).unwrap();
+                tar_file.write_all(EMBEDDED_SUMMARY_STORE).unwrap();
                 let tar_file = File::open(tar_path).unwrap();
                 let mut ar = Archive::new(tar_file);
                 ar.unpack(directory_path).unwrap();
@@ -548,11 +807,19 @@ impl<'tcx> SummaryCache<'tcx> {
                     calls.push((call_snippet, callee_name));
                 }
             };
+            let parameters = if tcx.def_kind(*key).is_fn_like() {
+                tcx.fn_arg_names(*key)
+                    .iter()
+                    .map(|ident| ident.to_string())
+                    .collect()
+            } else {
+                vec![]
+            };
             entries.push((
                 path.unwrap_or_default(),
                 fully_qualified_name,
                 source,
-                LLMSummary::from_summary(value, calls),
+                LLMSummary::from_summary(value, parameters, calls),
             ));
         }
         SummariesForLLM { entries }
@@ -613,9 +880,13 @@ impl<'tcx> SummaryCache<'tcx> {
 
                     // In this case we default to the summary that is not argument type specific.
                     let db = &self.db;
+                    let shared_store = self.shared_store.as_deref();
                     self.def_id_cache.entry(def_id).or_insert_with(|| {
-                        let summary =
-                            Self::get_persistent_summary_for_db(db, &func_ref.summary_cache_key);
+                        let summary = Self::get_persistent_summary_for_stores(
+                            db,
+                            shared_store,
+                            &func_ref.summary_cache_key,
+                        );
                         summary.unwrap_or_default()
                     })
                 }
@@ -640,11 +911,13 @@ impl<'tcx> SummaryCache<'tcx> {
                     }
 
                     let db = &self.db;
+                    let shared_store = self.shared_store.as_deref();
                     self.reference_cache
                         .entry(func_ref.clone())
                         .or_insert_with(|| {
-                            let summary = Self::get_persistent_summary_for_db(
+                            let summary = Self::get_persistent_summary_for_stores(
                                 db,
+                                shared_store,
                                 &func_ref.summary_cache_key,
                             );
                             if summary.is_none() {
@@ -673,7 +946,11 @@ impl<'tcx> SummaryCache<'tcx> {
             let mut mangled_key = String::new();
             mangled_key.push_str(persistent_key);
             mangled_key.push_str(arg_types_key);
-            Self::get_persistent_summary_for_db(&self.db, mangled_key.as_str())
+            Self::get_persistent_summary_for_stores(
+                &self.db,
+                self.shared_store.as_deref(),
+                mangled_key.as_str(),
+            )
         } else {
             None
         }
@@ -683,14 +960,57 @@ impl<'tcx> SummaryCache<'tcx> {
     /// The caller is expected to cache this.
     #[logfn_inputs(TRACE)]
     pub fn get_persistent_summary_for(&self, persistent_key: &str) -> Summary {
-        Self::get_persistent_summary_for_db(&self.db, persistent_key).unwrap_or_default()
+        Self::get_persistent_summary_for_stores(
+            &self.db,
+            self.shared_store.as_deref(),
+            persistent_key,
+        )
+        .unwrap_or_default()
+    }
+
+    /// Returns true if the persistent stores have a summary for the given key, or summaries that
+    /// are specific to particular argument types of the function with the given key.
+    pub fn has_persistent_summaries_for(&self, persistent_key: &str) -> bool {
+        let specialized_prefix = format!("{persistent_key}__");
+        let in_db = |db: &Db| {
+            matches!(db.get(persistent_key.as_bytes()), Ok(Some(_)))
+                || db
+                    .scan_prefix(specialized_prefix.as_bytes())
+                    .next()
+                    .is_some()
+        };
+        in_db(&self.db)
+            || self
+                .shared_store
+                .as_deref()
+                .is_some_and(|store| store.has_summaries_for(persistent_key, &specialized_prefix))
+    }
+
+    /// Looks for the summary in the project store and then, if it belongs to a function of
+    /// another crate, in the shared store.
+    fn get_persistent_summary_for_stores(
+        db: &Db,
+        shared_store: Option<&SharedStore>,
+        persistent_key: &str,
+    ) -> Option<Summary> {
+        Self::get_persistent_summary_for_db(db, persistent_key)
+            .or_else(|| shared_store.and_then(|store| store.get(persistent_key)))
     }
 
-    /// Helper for get_summary_for and get_persistent_summary_for.
+    /// Helper for get_persistent_summary_for_stores.
     #[logfn(TRACE)]
     fn get_persistent_summary_for_db(db: &Db, persistent_key: &str) -> Option<Summary> {
         if let Ok(Some(pinned_value)) = db.get(persistent_key.as_bytes()) {
-            Some(bincode::deserialize(pinned_value.deref()).unwrap())
+            match Summary::from_bytes(pinned_value.deref()) {
+                Ok(summary) => Some(summary),
+                Err(e) => {
+                    // The store has been validated when it was opened, so this should not
+                    // happen, but a corrupt entry is no reason to abandon the analysis.
+                    warn!("discarding undecodable summary for {persistent_key}: {e:?}");
+                    let _ = db.remove(persistent_key.as_bytes());
+                    None
+                }
+            }
         } else {
             None
         }
@@ -736,7 +1056,7 @@ impl<'tcx> SummaryCache<'tcx> {
         summary: Summary,
     ) -> Option<Summary> {
         let persistent_key = utils::summary_key_str(tcx, def_id);
-        let serialized_summary = bincode::serialize(&summary).unwrap();
+        let serialized_summary = summary.to_bytes().unwrap();
         let result = self
             .db
             .insert(persistent_key.as_bytes(), serialized_summary);
@@ -747,6 +1067,166 @@ impl<'tcx> SummaryCache<'tcx> {
     }
 }
 
+/// The persistent stores of a summary cache, without its in-memory caches, which cannot be
+/// shared between threads.
+#[derive(Clone)]
+pub struct SummaryStores {
+    db: Db,
+    shared_store: Option<Arc<SharedStore>>,
+}
+
+/// The content of a shared summary store. Sled lets only one process at a time open a store,
+/// so the entries of the store are copied to a plain file the first time that a build uses the
+/// store, and every crate of the build then reads that file.
+struct SharedStore {
+    /// The entries of the store, with the summaries still serialized.
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The prefix of the summary keys of the crate being analyzed. Summaries of its functions
+    /// are never taken from the shared store, since they may be out of date.
+    local_key_prefix: String,
+}
+
+impl SharedStore {
+    /// The name of the file, in the cache directory, that holds the copy of the shared store.
+    const COPY_FILE_NAME: &'static str = ".shared_summary_store";
+
+    fn open(
+        path: &std::path::Path,
+        cache_directory: &std::path::Path,
+        local_crate_name: &str,
+    ) -> std::result::Result<Self, String> {
+        use fs2::FileExt;
+        use std::fs::OpenOptions;
+
+        let source = Self::describe_source(path).map_err(|e| e.to_string())?;
+        let copy_path = cache_directory.join(Self::COPY_FILE_NAME);
+        let lock_file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(copy_path.with_extension("lock"))
+            .map_err(|e| e.to_string())?;
+        // The lock is released when the file is closed.
+        lock_file.lock_exclusive().map_err(|e| e.to_string())?;
+        let copy = std::fs::read(&copy_path)
+            .ok()
+            .and_then(|bytes| {
+                bincode::deserialize::<(String, BTreeMap<Vec<u8>, Vec<u8>>)>(&bytes).ok()
+            })
+            .filter(|(copied_source, _)| *copied_source == source);
+        let entries = match copy {
+            Some((_, entries)) => entries,
+            None => {
+                let entries = Self::read_entries(path)?;
+                let bytes = bincode::serialize(&(&source, &entries)).map_err(|e| e.to_string())?;
+                std::fs::write(&copy_path, bytes).map_err(|e| e.to_string())?;
+                entries
+            }
+        };
+        Ok(SharedStore {
+            entries,
+            local_key_prefix: format!("{local_crate_name}."),
+        })
+    }
+
+    /// Identifies the shared store at the given path and the time it was last changed, so that
+    /// a copy of an older store, or of another store, is not used.
+    fn describe_source(path: &std::path::Path) -> std::io::Result<String> {
+        let path = path.canonicalize()?;
+        let changed_file = if path.is_file() {
+            path.clone()
+        } else if path.ends_with(".summary_store.sled") {
+            path.join("db")
+        } else {
+            path.join(".summary_store.sled").join("db")
+        };
+        let metadata = std::fs::metadata(changed_file)?;
+        Ok(format!(
+            "{} {} {:?}",
+            path.display(),
+            metadata.len(),
+            metadata.modified()?
+        ))
+    }
+
+    /// Reads all of the entries of the shared store at the given path. Opening a sled store can
+    /// change its files, so the store is opened in a private temporary directory.
+    fn read_entries(
+        path: &std::path::Path,
+    ) -> std::result::Result<BTreeMap<Vec<u8>, Vec<u8>>, String> {
+        use std::fs::File;
+        use tar::Archive;
+
+        let directory = tempfile::TempDir::new().map_err(|e| e.to_string())?;
+        let store_path = directory.path().join(".summary_store.sled");
+        if path.is_file() {
+            let tar_file = File::open(path).map_err(|e| e.to_string())?;
+            Archive::new(tar_file)
+                .unpack(directory.path())
+                .map_err(|e| e.to_string())?;
+        } else if path.ends_with(".summary_store.sled") {
+            copy_directory(path, &store_path).map_err(|e| e.to_string())?;
+        } else {
+            copy_directory(&path.join(".summary_store.sled"), &store_path)
+                .map_err(|e| e.to_string())?;
+        }
+        if !store_path.is_dir() {
+            return Err("no .summary_store.sled found".to_string());
+        }
+        let db = Config::default()
+            .path(store_path)
+            .open()
+            .map_err(|e| e.to_string())?;
+        db.iter()
+            .map(|entry| {
+                let (key, value) = entry.map_err(|e| e.to_string())?;
+                Ok((key.to_vec(), value.to_vec()))
+            })
+            .collect()
+    }
+
+    /// The header of the shared store, if it has one.
+    fn header(&self) -> Option<StoreHeader> {
+        let value = self.entries.get(STORE_HEADER_KEY.as_bytes())?;
+        serde_json::from_slice(value).ok()
+    }
+
+    fn get(&self, persistent_key: &str) -> Option<Summary> {
+        if persistent_key.starts_with(&self.local_key_prefix) {
+            return None;
+        }
+        let value = self.entries.get(persistent_key.as_bytes())?;
+        Summary::from_bytes(value).ok()
+    }
+
+    /// Returns true if the store has a summary for the given key, or a key with the given
+    /// prefix, and the key does not belong to the crate being analyzed.
+    fn has_summaries_for(&self, persistent_key: &str, specialized_prefix: &str) -> bool {
+        !persistent_key.starts_with(&self.local_key_prefix)
+            && (self.entries.contains_key(persistent_key.as_bytes())
+                || self
+                    .entries
+                    .range(specialized_prefix.as_bytes().to_vec()..)
+                    .next()
+                    .is_some_and(|(key, _)| key.starts_with(specialized_prefix.as_bytes())))
+    }
+}
+
+/// Copies the files in the from directory, and its sub directories, to the to directory.
+fn copy_directory(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_directory(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 #[derive(Serialize)]
 pub struct SummariesForLLM {
     // (source path, fully qualified function name, function source, summary)
@@ -759,43 +1239,290 @@ impl SummariesForLLM {
     }
 }
 
+/// A JSON friendly rendering of a Summary. Abstract values and paths are rendered as Rust like
+/// expressions, see WithParameterNames, in which parameters appear by name, or as param_1,
+/// param_2 and so on if their names are not known, the result of the function appears as
+/// result and a static variable appears as its summary key.
 #[derive(Serialize)]
 pub struct LLMSummary {
-    // Conditions that should hold prior to the call.
-    // Callers should substitute parameter values with argument values and simplify the results
-    // under the current path condition. Any values that do not simplify to true will require the
-    // caller to either generate an error message or to add a precondition to its own summary that
-    // will be sufficient to ensure that all the preconditions in this summary are met.
-    // The string value bundled with the condition is the message that details what would go
-    // wrong at runtime if the precondition is not satisfied by the caller.
-    //pub preconditions: Vec<Precondition>,
+    /// The names of the parameters, in order, so that param_n can be related to the source
+    /// where a parameter has no name, as with a pattern. Omitted if not known.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    parameters: Vec<String>,
 
-    // Modifications the function makes to mutable state external to the function.
-    // Every path will be rooted in a static or in a mutable parameter.
-    // No two paths in this collection will lead to the same place in memory.
-    // Callers should substitute parameter values with argument values and simplify the results
-    // under the current path condition. They should then update their current state to reflect the
-    // side effects of the call.
-    //pub side_effects: Vec<(Rc<Path>, Rc<AbstractValue>)>,
+    /// False if the summary was not computed from a function body, but was derived from the
+    /// function signature. Such a summary has no conditions or side effects.
+    is_computed: bool,
 
-    // A condition that should hold after a call that completes normally.
-    // Callers should substitute parameter values with argument values and simplify the results
-    // under the current path condition.
-    // The resulting value should be conjoined to the current path condition.
-    //pub post_condition: Option<Rc<AbstractValue>>,
+    /// True if the summary is incomplete, because the analysis timed out or encountered
+    /// something it could not handle. The conditions and side effects may then be missing
+    /// or overly specific.
+    is_incomplete: bool,
+
+    /// Conditions that should hold prior to the call.
+    /// If a precondition is not satisfied by the caller, the function might panic with the
+    /// message of the precondition.
+    preconditions: Vec<LLMPrecondition>,
+
+    /// Modifications the function makes to mutable state external to the function.
+    /// Every path will be rooted in a static, in a mutable parameter or in the result.
+    side_effects: Vec<LLMSideEffect>,
 
-    // The set of function calls made by this function. The first element is the source snippet of
-    // the call and the second is the fully qualified name of the function being called.
+    /// A condition that holds after a call that completes normally.
+    post_condition: Option<String>,
+
+    /// The set of function calls made by this function. The first element is the source snippet of
+    /// the call and the second is the fully qualified name of the function being called.
     calls: Vec<(String, String)>,
 }
 
+#[derive(Serialize)]
+struct LLMPrecondition {
+    /// The condition, in terms of the parameters and static variables.
+    condition: String,
+    /// The diagnostic message that is issued if the precondition is not met.
+    message: String,
+    /// The source location of the code that defines the precondition, or of the code that
+    /// would panic if the precondition is not met, if known.
+    provenance: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LLMSideEffect {
+    /// The location that is updated.
+    path: String,
+    /// The value that is stored at the location.
+    value: String,
+}
+
 impl LLMSummary {
-    pub fn from_summary(_summary: &Summary, calls: Vec<(String, String)>) -> LLMSummary {
+    pub fn from_summary(
+        summary: &Summary,
+        parameters: Vec<String>,
+        calls: Vec<(String, String)>,
+    ) -> LLMSummary {
+        let render =
+            |value: &AbstractValue| WithParameterNames::new(value, &parameters).to_string();
         LLMSummary {
-            // preconditions: vec![],
-            // side_effects: vec![],
-            // post_condition: vec![],
+            is_computed: summary.is_computed,
+            is_incomplete: summary.is_incomplete,
+            preconditions: summary
+                .preconditions
+                .iter()
+                .map(|precondition| LLMPrecondition {
+                    condition: render(&precondition.condition),
+                    message: precondition.message.to_string(),
+                    provenance: precondition.provenance.as_ref().map(|p| p.to_string()),
+                })
+                .collect(),
+            side_effects: summary
+                .side_effects
+                .iter()
+                .map(|(path, value)| LLMSideEffect {
+                    path: WithParameterNames::<Path>::new(path, &parameters).to_string(),
+                    value: render(value),
+                })
+                .collect(),
+            post_condition: summary
+                .post_condition
+                .as_ref()
+                .map(|condition| render(condition)),
+            parameters,
             calls,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_value;
+    use crate::expression::ExpressionType;
+
+    fn summary_with_rules(rules: &[DiagnosticRule]) -> Summary {
+        Summary {
+            is_computed: true,
+            preconditions: rules
+                .iter()
+                .map(|rule| Precondition {
+                    condition: Rc::new(abstract_value::TRUE),
+                    message: Rc::from("possible attempt to add with overflow"),
+                    rule: *rule,
+                    provenance: None,
+                    spans: vec![],
+                })
+                .collect(),
+            ..Summary::default()
+        }
+    }
+
+    #[test]
+    fn precondition_rules_survive_a_round_trip() {
+        let summary = summary_with_rules(&[DiagnosticRule::Overflow, DiagnosticRule::Unreachable]);
+        let decoded = Summary::from_bytes(&summary.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, summary);
+    }
+
+    #[test]
+    fn summaries_stored_without_rules_can_be_read_by_either_layout() {
+        // Summaries in stores written before preconditions had rules end where the rules are
+        // now appended, so they get the default rule.
+        let summary = summary_with_rules(&[DiagnosticRule::Overflow]);
+        let old_bytes = bincode::serialize(&summary).unwrap();
+        let decoded = Summary::from_bytes(&old_bytes).unwrap();
+        assert_eq!(
+            decoded.preconditions[0].rule,
+            DiagnosticRule::UnsatisfiedPrecondition
+        );
+
+        // Builds that predate the rules ignore them.
+        let decoded: Summary = bincode::deserialize(&summary.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.preconditions.len(), 1);
+    }
+
+    /// Writes a shared store, with a summary for each of the given keys, in the given directory.
+    fn write_shared_store(directory: &std::path::Path, keys: &[&str]) {
+        let db = Config::default()
+            .path(directory.join(".summary_store.sled"))
+            .open()
+            .unwrap();
+        StoreHeader::current().write(&db);
+        let summary = summary_with_rules(&[DiagnosticRule::Overflow]);
+        for key in keys {
+            db.insert(key.as_bytes(), summary.to_bytes().unwrap())
+                .unwrap();
+        }
+        db.flush().unwrap();
+    }
+
+    #[test]
+    fn summaries_of_other_crates_are_found_in_the_shared_store() {
+        let shared = tempfile::tempdir().unwrap();
+        write_shared_store(shared.path(), &["other.f", "other.g__u32", "local.h"]);
+        let project = tempfile::tempdir().unwrap();
+        let db = Config::default()
+            .path(project.path().join(".summary_store.sled"))
+            .open()
+            .unwrap();
+        let mut cache = SummaryCache::with_stores(SummaryStores {
+            db,
+            shared_store: None,
+        });
+        cache
+            .use_shared_store(shared.path(), project.path(), "local")
+            .unwrap();
+
+        let summary = cache.get_persistent_summary_for("other.f");
+        assert_eq!(summary, summary_with_rules(&[DiagnosticRule::Overflow]));
+        assert!(cache.has_persistent_summaries_for("other.f"));
+        assert!(cache.has_persistent_summaries_for("other.g"));
+        assert!(!cache.has_persistent_summaries_for("other.missing"));
+        assert!(
+            !cache
+                .get_persistent_summary_for("other.missing")
+                .is_computed
+        );
+    }
+
+    #[test]
+    fn summaries_of_the_local_crate_are_not_taken_from_the_shared_store() {
+        let shared = tempfile::tempdir().unwrap();
+        write_shared_store(shared.path(), &["local.h", "local.k__u32", "localized.h"]);
+        let cache_directory = tempfile::tempdir().unwrap();
+        let store = SharedStore::open(shared.path(), cache_directory.path(), "local").unwrap();
+
+        assert!(store.get("local.h").is_none());
+        assert!(!store.has_summaries_for("local.h", "local.h__"));
+        assert!(!store.has_summaries_for("local.k", "local.k__"));
+        // Only the crate named local is excluded, not crates whose names start with local.
+        assert!(store.get("localized.h").is_some());
+        assert!(store.has_summaries_for("localized.h", "localized.h__"));
+    }
+
+    #[test]
+    fn shared_stores_are_copied_once() {
+        let shared = tempfile::tempdir().unwrap();
+        write_shared_store(shared.path(), &["other.f"]);
+        let cache_directory = tempfile::tempdir().unwrap();
+        SharedStore::open(shared.path(), cache_directory.path(), "local").unwrap();
+        let copy_path = cache_directory.path().join(SharedStore::COPY_FILE_NAME);
+        let copied = std::fs::metadata(&copy_path).unwrap().modified().unwrap();
+
+        let store = SharedStore::open(shared.path(), cache_directory.path(), "other").unwrap();
+        assert_eq!(
+            std::fs::metadata(&copy_path).unwrap().modified().unwrap(),
+            copied
+        );
+        assert!(store.get("other.f").is_none());
+        assert!(store.header().is_some());
+    }
+
+    #[test]
+    fn llm_summaries_use_infix_operators_and_parameter_names() {
+        let x = AbstractValue::make_typed_unknown(ExpressionType::U32, Path::new_parameter(1));
+        let y = AbstractValue::make_typed_unknown(ExpressionType::U32, Path::new_parameter(2));
+        let one: Rc<AbstractValue> = Rc::new(1u128.into());
+        let result = AbstractValue::make_typed_unknown(ExpressionType::U64, Path::new_result());
+        let make = |expression| AbstractValue::make_from(expression, 3);
+
+        let product = make(Expression::Mul {
+            left: make(Expression::Add {
+                left: x.clone(),
+                right: one.clone(),
+            }),
+            right: y.clone(),
+        });
+        let condition = make(Expression::And {
+            left: make(Expression::LessThan {
+                left: product,
+                right: Rc::new(10u128.into()),
+            }),
+            right: make(Expression::LogicalNot {
+                operand: make(Expression::Equals {
+                    left: y.clone(),
+                    right: Rc::new(0u128.into()),
+                }),
+            }),
+        });
+        let summary = Summary {
+            is_computed: true,
+            preconditions: vec![Precondition {
+                condition,
+                message: Rc::from("possible attempt to divide by zero"),
+                rule: DiagnosticRule::UnsatisfiedPrecondition,
+                provenance: None,
+                spans: vec![],
+            }],
+            side_effects: vec![(
+                Path::new_field(Path::new_parameter(2), 0),
+                make(Expression::Sub {
+                    left: x.clone(),
+                    right: make(Expression::Sub {
+                        left: y,
+                        right: one,
+                    }),
+                }),
+            )],
+            post_condition: Some(make(Expression::Equals {
+                left: result,
+                right: make(Expression::Cast {
+                    operand: x,
+                    target_type: ExpressionType::U64,
+                }),
+            })),
+            ..Summary::default()
+        };
+
+        let parameters = vec!["x".to_string(), "y".to_string()];
+        let json =
+            serde_json::to_value(LLMSummary::from_summary(&summary, parameters, vec![])).unwrap();
+        assert_eq!(json["parameters"], serde_json::json!(["x", "y"]));
+        assert_eq!(
+            json["preconditions"][0]["condition"],
+            "(x + 1) * y < 10 && !(y == 0)"
+        );
+        assert_eq!(json["side_effects"][0]["path"], "y.0");
+        assert_eq!(json["side_effects"][0]["value"], "x - (y - 1)");
+        assert_eq!(json["post_condition"], "result == x as u64");
+    }
+}
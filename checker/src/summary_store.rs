@@ -0,0 +1,290 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// This implements the "cargo mirai store" subcommand, which inspects and maintains the sled
+// database in which MIRAI persists function summaries between runs.
+// The cargo-mirai binary does not link against the compiler, so it forwards the subcommand to
+// the mirai binary as "mirai store <store path> <command> [<argument>]".
+
+use std::error::Error;
+use std::io::Write;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use sled::{Config, Db};
+
+use crate::summaries::{LLMSummary, StoreHeader, Summary, STORE_HEADER_KEY};
+
+pub const STORE_HELP: &str = r#"Inspect and maintain the MIRAI summary store
+
+Usage:
+    cargo mirai store [--store <path>] <command> [<argument>]
+
+The store is the .summary_store.sled directory in <path>, or <path> itself if it is a store.
+It defaults to the deps directory of the debug target, which is where MIRAI puts it when
+MIRAI_SHARE_PERSISTENT_STORE is set.
+
+Commands:
+    list [<prefix>]    list the keys of the stored summaries, optionally only those starting with <prefix>
+    dump <key>         print the summary stored under <key> as JSON
+    delete <prefix>    delete the summaries whose keys start with <prefix>
+    stats              print the number of entries and the size of the store
+    verify             check that every entry can be decoded as a summary
+    help               print this message
+"#;
+
+/// Runs a store command and returns the exit code of the process.
+/// The arguments are the store path, the command and its argument, if any.
+pub fn run(args: &[String]) -> i32 {
+    let (store_path, command, argument) = match args {
+        [_, command] if command == "help" => {
+            println!("{STORE_HELP}");
+            return 0;
+        }
+        [store_path, command] => (store_path, command.as_str(), None),
+        [store_path, command, argument] => (store_path, command.as_str(), Some(argument.as_str())),
+        _ => {
+            eprintln!("{STORE_HELP}");
+            return 2;
+        }
+    };
+    let db = match open_store(Path::new(store_path)) {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let out = &mut std::io::stdout();
+    let result = match (command, argument) {
+        ("list", prefix) => list(&db, prefix.unwrap_or_default(), out),
+        ("dump", Some(key)) => dump(&db, key, out),
+        ("delete", Some(prefix)) => delete(&db, prefix, out),
+        ("stats", None) => stats(&db, out),
+        ("verify", None) => verify(&db, out),
+        _ => {
+            eprintln!("{STORE_HELP}");
+            return 2;
+        }
+    };
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Opens the store at the given path, without creating a new store if there is none.
+fn open_store(path: &Path) -> Result<Db, String> {
+    let store_path: PathBuf = if path.ends_with(".summary_store.sled") {
+        path.to_path_buf()
+    } else {
+        path.join(".summary_store.sled")
+    };
+    if !store_path.is_dir() {
+        return Err(format!("no summary store at {}", store_path.display()));
+    }
+    Config::default().path(&store_path).open().map_err(|e| {
+        format!(
+            "could not open summary store at {}: {e}",
+            store_path.display()
+        )
+    })
+}
+
+/// The result of a command: whether it succeeded, or the error that stopped it.
+/// The output of a command is written to the given writer, which is stdout unless testing.
+type CommandResult = Result<bool, Box<dyn Error>>;
+
+fn list(db: &Db, prefix: &str, out: &mut impl Write) -> CommandResult {
+    for entry in db.scan_prefix(prefix.as_bytes()) {
+        let (key, _) = entry?;
+        if key.as_ref() != STORE_HEADER_KEY.as_bytes() {
+            writeln!(out, "{}", String::from_utf8_lossy(&key))?;
+        }
+    }
+    Ok(true)
+}
+
+fn dump(db: &Db, key: &str, out: &mut impl Write) -> CommandResult {
+    match db.get(key.as_bytes())? {
+        Some(value) => {
+            let summary = Summary::from_bytes(value.deref())
+                .map_err(|e| format!("the summary for {key} cannot be decoded: {e}"))?;
+            let rendering = LLMSummary::from_summary(&summary, vec![], vec![]);
+            writeln!(out, "{}", serde_json::to_string_pretty(&rendering)?)?;
+            Ok(true)
+        }
+        None => {
+            eprintln!("no summary for {key}");
+            Ok(false)
+        }
+    }
+}
+
+fn delete(db: &Db, prefix: &str, out: &mut impl Write) -> CommandResult {
+    let mut count = 0usize;
+    for entry in db.scan_prefix(prefix.as_bytes()) {
+        let (key, _) = entry?;
+        if key.as_ref() == STORE_HEADER_KEY.as_bytes() {
+            continue;
+        }
+        db.remove(key)?;
+        count += 1;
+    }
+    db.flush()?;
+    writeln!(out, "deleted {count} entries")?;
+    Ok(true)
+}
+
+fn stats(db: &Db, out: &mut impl Write) -> CommandResult {
+    let mut value_bytes = 0usize;
+    for entry in db.iter() {
+        let (_, value) = entry?;
+        value_bytes += value.len();
+    }
+    match StoreHeader::read(db) {
+        Some(header) => {
+            writeln!(out, "written by MIRAI {}", header.mirai_version)?;
+            writeln!(out, "compiler: {}", header.rustc_version)?;
+            writeln!(
+                out,
+                "standard contracts: {}",
+                header.standard_contracts_hash
+            )?;
+            writeln!(out, "format version: {}", header.format_version)?;
+            if header != StoreHeader::current() {
+                writeln!(out, "the store will be updated the next time MIRAI uses it")?;
+            }
+        }
+        None => writeln!(out, "the store has no header")?,
+    }
+    writeln!(out, "entries: {}", db.len())?;
+    writeln!(out, "summary bytes: {value_bytes}")?;
+    writeln!(out, "size on disk: {}", db.size_on_disk()?)?;
+    Ok(true)
+}
+
+/// Reports every entry that cannot be decoded as a Summary with the current layout.
+/// Returns false if there are any such entries.
+fn verify(db: &Db, out: &mut impl Write) -> CommandResult {
+    let mut count = 0usize;
+    let mut failures = 0usize;
+    for entry in db.iter() {
+        let (key, value) = entry?;
+        if key.as_ref() == STORE_HEADER_KEY.as_bytes() {
+            continue;
+        }
+        count += 1;
+        if let Err(e) = Summary::from_bytes(value.deref()) {
+            failures += 1;
+            writeln!(out, "{}: {e}", String::from_utf8_lossy(&key))?;
+        }
+    }
+    writeln!(out, "{count} entries, {failures} cannot be decoded")?;
+    Ok(failures == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a store with a header, a summary for each of the given keys and an entry that
+    /// cannot be decoded as a summary.
+    fn store_with(keys: &[&str]) -> (tempfile::TempDir, Db) {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::create_dir(directory.path().join(".summary_store.sled")).unwrap();
+        let db = open_store(directory.path()).unwrap();
+        let header = serde_json::to_vec(&StoreHeader::current()).unwrap();
+        db.insert(STORE_HEADER_KEY, header).unwrap();
+        let summary = Summary {
+            is_computed: true,
+            ..Summary::default()
+        };
+        for key in keys {
+            db.insert(key.as_bytes(), summary.to_bytes().unwrap())
+                .unwrap();
+        }
+        db.insert("broken.f", vec![0xff]).unwrap();
+        (directory, db)
+    }
+
+    fn output_of(command: impl FnOnce(&mut Vec<u8>) -> CommandResult) -> (bool, String) {
+        let mut out = Vec::new();
+        let succeeded = command(&mut out).unwrap();
+        (succeeded, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn stores_are_only_opened_if_they_exist() {
+        let directory = tempfile::tempdir().unwrap();
+        assert!(open_store(directory.path()).is_err());
+        let (directory, db) = store_with(&[]);
+        drop(db);
+        assert!(open_store(&directory.path().join(".summary_store.sled")).is_ok());
+    }
+
+    #[test]
+    fn list_prints_the_keys_of_the_summaries() {
+        let (_directory, db) = store_with(&["krate.g", "krate.f", "other.f"]);
+        let (succeeded, output) = output_of(|out| list(&db, "", out));
+        assert!(succeeded);
+        assert_eq!(output, "broken.f\nkrate.f\nkrate.g\nother.f\n");
+    }
+
+    #[test]
+    fn list_filters_the_keys_by_prefix() {
+        let (_directory, db) = store_with(&["krate.g", "krate.f", "other.f"]);
+        let (_, output) = output_of(|out| list(&db, "krate.", out));
+        assert_eq!(output, "krate.f\nkrate.g\n");
+        let (_, output) = output_of(|out| list(&db, "$", out));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn dump_shows_a_summary_as_json() {
+        let (_directory, db) = store_with(&["krate.f"]);
+        let (succeeded, output) = output_of(|out| dump(&db, "krate.f", out));
+        assert!(succeeded);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["is_computed"], true);
+        assert_eq!(json["preconditions"], serde_json::json!([]));
+
+        let (succeeded, output) = output_of(|out| dump(&db, "krate.g", out));
+        assert!(!succeeded);
+        assert_eq!(output, "");
+        assert!(dump(&db, "broken.f", &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn delete_removes_the_summaries_with_the_prefix() {
+        let (_directory, db) = store_with(&["krate.g", "krate.f", "other.f"]);
+        let (_, output) = output_of(|out| delete(&db, "krate.", out));
+        assert_eq!(output, "deleted 2 entries\n");
+        let (_, output) = output_of(|out| list(&db, "", out));
+        assert_eq!(output, "broken.f\nother.f\n");
+        assert!(StoreHeader::read(&db).is_some());
+    }
+
+    #[test]
+    fn verify_reports_the_summaries_that_cannot_be_decoded() {
+        let (_directory, db) = store_with(&["krate.f"]);
+        let (succeeded, output) = output_of(|out| verify(&db, out));
+        assert!(!succeeded);
+        assert!(output.starts_with("broken.f: "), "{output}");
+        assert!(
+            output.ends_with("2 entries, 1 cannot be decoded\n"),
+            "{output}"
+        );
+
+        db.remove("broken.f").unwrap();
+        let (succeeded, output) = output_of(|out| verify(&db, out));
+        assert!(succeeded);
+        assert_eq!(output, "1 entries, 0 cannot be decoded\n");
+    }
+}
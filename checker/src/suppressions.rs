@@ -0,0 +1,297 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::hash::{Hash, Hasher};
+
+use rustc_ast::attr::AttributeExt;
+use rustc_hir::def_id::{DefId, CRATE_DEF_ID};
+use rustc_hir::HirId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{BytePos, MultiSpan, Span, Symbol};
+
+use crate::diagnostic_rules::DiagnosticRule;
+use crate::utils;
+
+/// Source code can ask MIRAI not to report diagnostics that belong to particular rules, either
+/// with a tool attribute such as #[mirai::allow(overflow)] on a function or statement, or with a
+/// comment such as // mirai: allow(overflow). A comment on a line of its own applies to the next
+/// line, otherwise it applies to the line it is on. The rules may be given by name or by code.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+    /// Rule names in suppressions that do not correspond to a rule, along with their locations.
+    unknown_rules: Vec<(Span, String, Option<DefId>)>,
+}
+
+#[derive(Debug)]
+struct Suppression {
+    /// The rules whose diagnostics are suppressed.
+    rules: Vec<DiagnosticRule>,
+    /// The diagnostics whose primary span starts inside this span are suppressed.
+    scope: Span,
+    /// The location of the attribute or comment.
+    span: Span,
+    /// The function to which the suppression belongs, if any.
+    owner: Option<DefId>,
+    /// True if the suppression has dropped at least one diagnostic.
+    used: bool,
+}
+
+impl Suppressions {
+    /// Collects the suppression attributes and comments in the crate being compiled.
+    pub fn collect(tcx: TyCtxt<'_>) -> Suppressions {
+        let mut suppressions = Suppressions::default();
+        suppressions.collect_attributes(tcx);
+        suppressions.collect_comments(tcx);
+        suppressions
+    }
+
+    fn collect_attributes(&mut self, tcx: TyCtxt<'_>) {
+        let path = [Symbol::intern("mirai"), Symbol::intern("allow")];
+        for owner_id in tcx.hir_crate_items(()).owners() {
+            for (local_id, attrs) in tcx.hir_attrs(owner_id).map.iter() {
+                for attr in attrs.iter() {
+                    if !attr.path_matches(&path) {
+                        continue;
+                    }
+                    let hir_id = HirId {
+                        owner: owner_id,
+                        local_id: *local_id,
+                    };
+                    let owner = Some(tcx.typeck_root_def_id(owner_id.to_def_id()));
+                    let mut rules = vec![];
+                    for item in attr.meta_item_list().unwrap_or_default().iter() {
+                        let name = item.ident().map(|i| i.to_string()).unwrap_or_default();
+                        self.add_rule(&mut rules, &name, item.span(), owner);
+                    }
+                    self.entries.push(Suppression {
+                        rules,
+                        scope: tcx.hir().span_with_body(hir_id),
+                        span: attr.span(),
+                        owner,
+                        used: false,
+                    });
+                }
+            }
+        }
+    }
+
+    fn collect_comments(&mut self, tcx: TyCtxt<'_>) {
+        let source_map = tcx.sess.source_map();
+        for file in source_map.files().iter() {
+            if file.is_imported() || !file.name.is_real() {
+                continue;
+            }
+            let Some(src) = &file.src else {
+                continue;
+            };
+            let line_count = src.lines().count();
+            for (line_index, line) in src.lines().enumerate() {
+                let Some((comment_start, names)) = parse_allow_comment(line) else {
+                    continue;
+                };
+                let bounds = file.line_bounds(line_index);
+                let span =
+                    Span::with_root_ctxt(bounds.start + BytePos(comment_start as u32), bounds.end);
+                let scope = if line[..comment_start].trim().is_empty() {
+                    if line_index + 1 >= line_count {
+                        continue;
+                    }
+                    let next = file.line_bounds(line_index + 1);
+                    Span::with_root_ctxt(next.start, next.end)
+                } else {
+                    Span::with_root_ctxt(bounds.start, bounds.end)
+                };
+                let owner = tcx
+                    .hir()
+                    .body_owners()
+                    .find(|def_id| {
+                        tcx.hir()
+                            .span_with_body(tcx.local_def_id_to_hir_id(*def_id))
+                            .contains(scope)
+                    })
+                    .map(|def_id| tcx.typeck_root_def_id(def_id.to_def_id()));
+                let mut rules = vec![];
+                for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    self.add_rule(&mut rules, name, span, owner);
+                }
+                self.entries.push(Suppression {
+                    rules,
+                    scope,
+                    span,
+                    owner,
+                    used: false,
+                });
+            }
+        }
+    }
+
+    fn add_rule(
+        &mut self,
+        rules: &mut Vec<DiagnosticRule>,
+        name: &str,
+        span: Span,
+        owner: Option<DefId>,
+    ) {
+        match DiagnosticRule::from_code_or_name(name) {
+            Some(rule) => rules.push(rule),
+            None => self.unknown_rules.push((span, name.to_string(), owner)),
+        }
+    }
+
+    /// Hashes the rules of the suppressions and the functions they belong to, but not their
+    /// locations, which change whenever code before them is edited.
+    pub fn hash_rules<H: Hasher>(&self, tcx: TyCtxt<'_>, hasher: &mut H) {
+        let mut rules: Vec<(String, &[DiagnosticRule])> = self
+            .entries
+            .iter()
+            .map(|suppression| {
+                let owner = suppression
+                    .owner
+                    .map(|def_id| utils::summary_key_str(tcx, def_id).to_string());
+                (owner.unwrap_or_default(), suppression.rules.as_slice())
+            })
+            .collect();
+        rules.sort();
+        rules.hash(hasher);
+    }
+
+    /// Returns true if a diagnostic with the given rule and span is suppressed by an attribute
+    /// or comment. Every matching suppression is marked as used.
+    pub fn suppresses(&mut self, rule: DiagnosticRule, span: &MultiSpan) -> bool {
+        let Some(span) = span.primary_span() else {
+            return false;
+        };
+        let span = span.source_callsite();
+        let mut result = false;
+        for suppression in self.entries.iter_mut() {
+            if suppression.rules.contains(&rule) && suppression.scope.contains(span.shrink_to_lo())
+            {
+                suppression.used = true;
+                result = true;
+            }
+        }
+        result
+    }
+
+    /// Returns the location, owner and description of each problem with the suppressions of
+    /// the analyzed functions: rule names that are not known and suppressions that did not
+    /// drop any diagnostic. The latter are only reported if their owner is in analyzed_functions,
+    /// since no diagnostics are produced for functions that are not analyzed.
+    pub fn problems(&self, analyzed_functions: &[DefId]) -> Vec<(Span, DefId, String)> {
+        let crate_root = CRATE_DEF_ID.to_def_id();
+        let mut result: Vec<(Span, DefId, String)> = self
+            .unknown_rules
+            .iter()
+            .map(|(span, name, owner)| {
+                (
+                    *span,
+                    owner.unwrap_or(crate_root),
+                    format!("[MIRAI] unknown rule in suppression: {name}"),
+                )
+            })
+            .collect();
+        for suppression in self.entries.iter() {
+            if suppression.used || suppression.rules.is_empty() {
+                continue;
+            }
+            if let Some(owner) = suppression.owner {
+                if analyzed_functions.contains(&owner) {
+                    result.push((
+                        suppression.span,
+                        owner,
+                        "[MIRAI] this suppression does not apply to any diagnostic".to_string(),
+                    ));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Parses a comment of the form // mirai: allow(rule, ...) and returns the byte offset of the
+/// comment in the line, along with the text between the parentheses.
+fn parse_allow_comment(line: &str) -> Option<(usize, &str)> {
+    let comment_start = find_line_comment(line)?;
+    let names = line[comment_start + 2..]
+        .trim_start()
+        .strip_prefix("mirai:")?
+        .trim_start()
+        .strip_prefix("allow(")?;
+    let (names, _) = names.split_once(')')?;
+    Some((comment_start, names))
+}
+
+/// Returns the byte offset of the // that starts a line comment in the given line, skipping
+/// those inside string and character literals. A string literal that started on an earlier
+/// line is not recognized as such.
+fn find_line_comment(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' => in_string = !in_string,
+            // A character literal, as opposed to a lifetime, such as '"' or '\''.
+            b'\'' if !in_string => {
+                if bytes.get(i + 1) == Some(&b'\\') {
+                    i += 3 + bytes.get(i + 3..)?.iter().position(|b| *b == b'\'')?;
+                } else if bytes.get(i + 2) == Some(&b'\'') {
+                    i += 2;
+                }
+            }
+            b'/' if !in_string && bytes.get(i + 1) == Some(&b'/') => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_comments_are_found_after_code() {
+        assert_eq!(
+            parse_allow_comment("    // mirai: allow(overflow, MIRAI0003)"),
+            Some((4, "overflow, MIRAI0003"))
+        );
+        assert_eq!(
+            parse_allow_comment("a + 1 //mirai:allow(overflow)"),
+            Some((6, "overflow"))
+        );
+        assert_eq!(parse_allow_comment("a + 1 // allow(overflow)"), None);
+    }
+
+    #[test]
+    fn slashes_in_literals_do_not_start_comments() {
+        let line = r#"let url = "http://x\"//"; // mirai: allow(overflow)"#;
+        assert_eq!(
+            parse_allow_comment(line),
+            Some((line.find("; //").unwrap() + 2, "overflow"))
+        );
+        let line = r#"if c == '"' { f("//") } // mirai: allow(panic)"#;
+        assert_eq!(
+            parse_allow_comment(line),
+            Some((line.find("} //").unwrap() + 2, "panic"))
+        );
+        let line = r"if c == '\'' { f('/') } // mirai: allow(panic)";
+        assert_eq!(
+            parse_allow_comment(line),
+            Some((line.find("} //").unwrap() + 2, "panic"))
+        );
+        assert_eq!(
+            parse_allow_comment(r#"let s = "// mirai: allow(overflow)";"#),
+            None
+        );
+        assert_eq!(
+            parse_allow_comment("fn f<'a>(x: &'a u8) {} // mirai: allow(panic)"),
+            Some((23, "panic"))
+        );
+    }
+}
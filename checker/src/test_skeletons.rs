@@ -0,0 +1,166 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Supports writing a #[test] function for every possible failure for which the SMT solver found
+// parameter values, so that the findings of MIRAI can be turned into regression tests.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The messages of diagnostics about conditions that are checked by MIRAI annotations, such as
+/// verify! and precondition!, which do not panic at run time unless they are checked versions.
+const ANNOTATION_MESSAGES: [&str; 2] = ["false verification condition", "unsatisfied precondition"];
+
+/// A test that calls a public function with the values for which it might fail.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct TestSkeleton {
+    /// The name of the test function, which is made unique when the tests are written.
+    pub name: String,
+    /// The source location of the diagnostic, as file:line:column.
+    pub location: String,
+    /// The message of the diagnostic, without the [MIRAI] prefix.
+    pub message: String,
+    /// The path of the function under test, starting with the name of its crate.
+    pub function: String,
+    /// The Rust expressions for the arguments of the call.
+    pub arguments: Vec<String>,
+    /// True if the function under test is an unsafe function.
+    pub is_unsafe: bool,
+}
+
+/// Writes the tests to a file named after the crate in the directory, so that they can be
+/// copied to the tests directory of the crate. The file of a previous run is replaced.
+pub fn write_test_skeletons(
+    dir: &Path,
+    crate_name: &str,
+    mut skeletons: Vec<TestSkeleton>,
+) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    // Sorted, and without duplicates from functions that were analyzed more than once,
+    // so that the file does not change if the findings do not.
+    skeletons.sort();
+    skeletons.dedup();
+    let path = dir.join(format!("mirai_{crate_name}.rs"));
+    fs::write(&path, render_tests(crate_name, &skeletons))?;
+    Ok(path)
+}
+
+fn render_tests(crate_name: &str, skeletons: &[TestSkeleton]) -> String {
+    let mut tests = format!(
+        "// Tests generated by MIRAI for the possible failures that it found in crate {crate_name}.\n\
+         // Parameters that the failure does not depend on are given zero, false or empty values,\n\
+         // and values of other types are left as todo!() placeholders.\n"
+    );
+    let mut test_counts: HashMap<&str, usize> = HashMap::new();
+    for skeleton in skeletons {
+        let count = test_counts.entry(&skeleton.name).or_default();
+        *count += 1;
+        let name = if *count == 1 {
+            skeleton.name.clone()
+        } else {
+            format!("{}_{count}", skeleton.name)
+        };
+        let expected = skeleton
+            .message
+            .strip_prefix("possible ")
+            .unwrap_or(&skeleton.message);
+        let is_annotation = ANNOTATION_MESSAGES.iter().any(|m| expected.starts_with(m));
+        let has_placeholders = skeleton.arguments.iter().any(|a| a.contains("todo!("));
+        tests.push_str("\n#[test]\n");
+        // Tests that would not fail as expected when run as generated are ignored.
+        if is_annotation {
+            tests.push_str("#[ignore = \"the condition is checked by a MIRAI annotation\"]\n");
+            tests.push_str("#[should_panic]\n");
+        } else {
+            if has_placeholders {
+                tests.push_str("#[ignore = \"the todo!() placeholders need values\"]\n");
+            }
+            tests.push_str(&format!("#[should_panic(expected = {expected:?})]\n"));
+        }
+        tests.push_str(&format!("fn {name}() {{\n"));
+        tests.push_str(&format!(
+            "    // {}: {}\n",
+            skeleton.location, skeleton.message
+        ));
+        if is_annotation {
+            tests.push_str(
+                "    // This condition is checked by a MIRAI annotation, which only panics at run time\n\
+                 \x20   // if it is a checked annotation, such as checked_verify! or checked_precondition!.\n\
+                 \x20   // Remove #[ignore] if it is.\n",
+            );
+        }
+        let call = format!("{}({})", skeleton.function, skeleton.arguments.join(", "));
+        if skeleton.is_unsafe {
+            tests.push_str(&format!("    let _ = unsafe {{ {call} }};\n}}\n"));
+        } else {
+            tests.push_str(&format!("    let _ = {call};\n}}\n"));
+        }
+    }
+    tests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tests_are_written_for_each_finding() {
+        let dir = tempfile::tempdir().unwrap();
+        let skeleton = TestSkeleton {
+            name: "add_line_7".to_string(),
+            location: "src/lib.rs:7:5".to_string(),
+            message: "possible attempt to add with overflow".to_string(),
+            function: "foo::add".to_string(),
+            arguments: vec!["255".to_string(), "&foo::Point { x: 1, y: 0 }".to_string()],
+            is_unsafe: false,
+        };
+        let verify = TestSkeleton {
+            location: "src/lib.rs:7:9".to_string(),
+            message: "possible false verification condition".to_string(),
+            ..skeleton.clone()
+        };
+        let skeletons = vec![verify, skeleton.clone(), skeleton];
+        let path = write_test_skeletons(dir.path(), "foo", skeletons).unwrap();
+        assert!(path.ends_with("mirai_foo.rs"));
+        let tests = fs::read_to_string(path).unwrap();
+        assert_eq!(tests.matches("#[test]").count(), 2);
+        assert!(tests.contains(
+            "#[should_panic(expected = \"attempt to add with overflow\")]\n\
+             fn add_line_7() {\n    \
+             // src/lib.rs:7:5: possible attempt to add with overflow\n    \
+             let _ = foo::add(255, &foo::Point { x: 1, y: 0 });\n}\n"
+        ));
+        assert!(tests.contains(
+            "#[ignore = \"the condition is checked by a MIRAI annotation\"]\n\
+             #[should_panic]\n\
+             fn add_line_7_2() {\n"
+        ));
+    }
+
+    #[test]
+    fn tests_with_placeholders_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let skeleton = TestSkeleton {
+            name: "get_line_3".to_string(),
+            location: "src/lib.rs:3:5".to_string(),
+            message: "possible index out of bounds".to_string(),
+            function: "foo::get".to_string(),
+            arguments: vec![
+                "&todo!(\"a value of type [u8]\")".to_string(),
+                "4".to_string(),
+            ],
+            is_unsafe: false,
+        };
+        let path = write_test_skeletons(dir.path(), "foo", vec![skeleton]).unwrap();
+        let tests = fs::read_to_string(path).unwrap();
+        assert!(tests.contains(
+            "#[test]\n\
+             #[ignore = \"the todo!() placeholders need values\"]\n\
+             #[should_panic(expected = \"index out of bounds\")]\n\
+             fn get_line_3() {\n"
+        ));
+    }
+}
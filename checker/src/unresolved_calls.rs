@@ -0,0 +1,72 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The reason why a call could not be analyzed with a summary of its callee.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnresolvedReason {
+    /// The called value could not be resolved to a particular function.
+    UnknownCallee,
+    /// The callee is a trait method that could not be resolved to an implementation.
+    Virtual,
+    /// The callee has no MIR body and the summary stores have no summary for it,
+    /// so a default summary was used.
+    MissingSummary,
+}
+
+/// A call site at which the analysis had to make do without a summary of the callee.
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct UnresolvedCall {
+    /// The source file of the call.
+    pub file: String,
+    /// The 1-based line of the call.
+    pub line: usize,
+    /// The 1-based column of the call.
+    pub column: usize,
+    /// The summary key of the function that makes the call.
+    pub caller: String,
+    /// The qualified name of the callee or, if the callee is not known, the type of the
+    /// called value.
+    pub callee: String,
+    /// The summary key under which a foreign contract for the callee would be stored.
+    pub summary_key: Option<String>,
+    pub reason: UnresolvedReason,
+    /// True if the summary stores have a summary for the callee, for example a foreign
+    /// contract for other argument types, that did not apply to this call.
+    pub has_foreign_contract: bool,
+}
+
+/// The unresolved calls found by MIRAI, grouped by the crate in which they were found.
+/// Each crate is analyzed by a separate MIRAI invocation, so the report for a whole workspace
+/// is built up by updating the entry of one crate at a time.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UnresolvedCallsReport {
+    crates: BTreeMap<String, Vec<UnresolvedCall>>,
+}
+
+impl UnresolvedCallsReport {
+    /// Parses a report from its JSON representation.
+    pub fn from_json(json: &str) -> Result<UnresolvedCallsReport, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Renders the report as JSON.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Replaces the unresolved calls of the given crate.
+    pub fn set_calls(&mut self, crate_name: String, mut calls: Vec<UnresolvedCall>) {
+        // Sorted, and without duplicates from functions that were analyzed more than once,
+        // so that the file does not change if the calls do not.
+        calls.sort();
+        calls.dedup();
+        self.crates.insert(crate_name, calls);
+    }
+}
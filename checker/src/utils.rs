@@ -3,9 +3,13 @@
 // This source code is licensed under the MIT license found in the
 // LICENSE file in the root directory of this source tree.
 
-use std::io::Write;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::rc::Rc;
 
+use fs2::FileExt;
+
 use log::debug;
 use log_derive::{logfn, logfn_inputs};
 
@@ -538,3 +542,30 @@ pub fn pretty_print_mir(tcx: TyCtxt<'_>, def_id: DefId) {
         let _ = stdout.flush();
     }
 }
+
+/// Replaces the content of the file at the given path with the result of applying `update` to
+/// its current content, or to None if the file is empty or does not exist yet. An exclusive lock
+/// is held on the file while doing so, since the MIRAI invocations that analyze the crates of a
+/// workspace can run in parallel and each of them updates its own entry in the same file.
+pub fn update_locked_file(
+    path: &Path,
+    update: impl FnOnce(Option<&str>) -> Result<String, String>,
+) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    // The lock is released when the file is closed.
+    file.lock_exclusive().map_err(|e| e.to_string())?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    let new_content = update(Some(content.as_str()).filter(|c| !c.is_empty()))?;
+    file.set_len(0).map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    file.write_all(new_content.as_bytes())
+        .map_err(|e| e.to_string())
+}
@@ -27,6 +27,9 @@ use crate::tag_domain::Tag;
 pub type Z3ExpressionType = z3_sys::Z3_ast;
 
 lazy_static! {
+    /// Every solver has a context of its own, and Z3 can be used from several threads as long
+    /// as no context is shared between them. Creating and deleting a context, however, touch
+    /// the global state of Z3, such as its memory manager, so these are serialized.
     static ref Z3_MUTEX: Mutex<()> = Mutex::new(());
 }
 
@@ -58,12 +61,26 @@ impl Debug for Z3Solver {
 impl Z3Solver {
     #[logfn_inputs(TRACE)]
     pub fn new() -> Z3Solver {
+        Z3Solver::with_limits(100, 0)
+    }
+
+    /// Creates a solver that gives up on a query after time_out_ms milliseconds, or after
+    /// using up rlimit resource units. Either limit is left unset if it is 0.
+    #[logfn_inputs(TRACE)]
+    pub fn with_limits(time_out_ms: u64, rlimit: u64) -> Z3Solver {
         unsafe {
             let _guard = Z3_MUTEX.lock().unwrap();
             let z3_sys_cfg = z3_sys::Z3_mk_config();
-            let time_out = CString::new("timeout").unwrap().into_raw();
-            let ms = CString::new("100").unwrap().into_raw();
-            z3_sys::Z3_set_param_value(z3_sys_cfg, time_out, ms);
+            if time_out_ms > 0 {
+                let time_out = CString::new("timeout").unwrap().into_raw();
+                let ms = CString::new(time_out_ms.to_string()).unwrap().into_raw();
+                z3_sys::Z3_set_param_value(z3_sys_cfg, time_out, ms);
+            }
+            if rlimit > 0 {
+                let resource_limit = CString::new("rlimit").unwrap().into_raw();
+                let units = CString::new(rlimit.to_string()).unwrap().into_raw();
+                z3_sys::Z3_set_param_value(z3_sys_cfg, resource_limit, units);
+            }
 
             let z3_context = z3_sys::Z3_mk_context(z3_sys_cfg);
             let z3_solver = z3_sys::Z3_mk_solver(z3_context);
@@ -124,13 +141,11 @@ impl Default for Z3Solver {
 impl SmtSolver<Z3ExpressionType> for Z3Solver {
     #[logfn_inputs(TRACE)]
     fn as_debug_string(&self, expression: &Z3ExpressionType) -> String {
-        let _guard = Z3_MUTEX.lock().unwrap();
         self.as_debug_string_helper(*expression)
     }
 
     #[logfn_inputs(TRACE)]
     fn assert(&self, expression: &Z3ExpressionType) {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             z3_sys::Z3_solver_assert(self.z3_context, self.z3_solver, *expression);
         }
@@ -138,7 +153,6 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
 
     #[logfn_inputs(TRACE)]
     fn backtrack(&self) {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             z3_sys::Z3_solver_pop(self.z3_context, self.z3_solver, 1);
         }
@@ -146,13 +160,11 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
 
     #[logfn_inputs(TRACE)]
     fn get_as_smt_predicate(&self, mirai_expression: &Expression) -> Z3ExpressionType {
-        let _guard = Z3_MUTEX.lock().unwrap();
         self.get_as_bool_z3_ast(mirai_expression)
     }
 
     #[logfn_inputs(TRACE)]
     fn get_model_as_string(&self) -> String {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             let model = z3_sys::Z3_solver_get_model(self.z3_context, self.z3_solver);
             let debug_str_bytes = z3_sys::Z3_model_to_string(self.z3_context, model);
@@ -161,9 +173,55 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
         }
     }
 
+    #[logfn_inputs(TRACE)]
+    fn get_model_value_for(&self, path: &Rc<Path>, var_type: ExpressionType) -> Option<String> {
+        unsafe {
+            let model = z3_sys::Z3_solver_get_model(self.z3_context, self.z3_solver);
+            let path_symbol = self.get_symbol_for(path);
+            if var_type == ExpressionType::Bool {
+                let ast = z3_sys::Z3_mk_const(self.z3_context, path_symbol, self.bool_sort);
+                let mut value = ast;
+                if !z3_sys::Z3_model_eval(self.z3_context, model, ast, false, &mut value) {
+                    return None;
+                }
+                return match z3_sys::Z3_get_bool_value(self.z3_context, value) {
+                    z3_sys::Z3_L_TRUE => Some("true".to_string()),
+                    z3_sys::Z3_L_FALSE => Some("false".to_string()),
+                    _ => None,
+                };
+            }
+            if !var_type.is_integer() && var_type != ExpressionType::Char {
+                return None;
+            }
+            // A variable that only appears in bit vector expressions has a bit vector sort.
+            let num_bits = u32::from(var_type.bit_length());
+            let bv_sort = z3_sys::Z3_mk_bv_sort(self.z3_context, num_bits);
+            for sort in [self.int_sort, bv_sort] {
+                let ast = z3_sys::Z3_mk_const(self.z3_context, path_symbol, sort);
+                let mut value = ast;
+                // Without model completion, a variable that is not in the model evaluates to
+                // itself, which is not a numeral.
+                if !z3_sys::Z3_model_eval(self.z3_context, model, ast, false, &mut value)
+                    || !z3_sys::Z3_is_numeral_ast(self.z3_context, value)
+                {
+                    continue;
+                }
+                let numeral_bytes = z3_sys::Z3_get_numeral_string(self.z3_context, value);
+                let numeral = CStr::from_ptr(numeral_bytes).to_str().ok()?;
+                if sort == bv_sort && var_type.is_signed_integer() {
+                    // The numeral of a bit vector is unsigned.
+                    let unsigned = numeral.parse::<u128>().ok()?;
+                    let shift = 128 - num_bits;
+                    return Some((((unsigned << shift) as i128) >> shift).to_string());
+                }
+                return Some(numeral.to_string());
+            }
+            None
+        }
+    }
+
     #[logfn_inputs(TRACE)]
     fn get_solver_state_as_string(&self) -> String {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             let debug_str_bytes = z3_sys::Z3_solver_to_string(self.z3_context, self.z3_solver);
             let debug_str = CStr::from_ptr(debug_str_bytes);
@@ -178,7 +236,6 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
 
     #[logfn_inputs(TRACE)]
     fn set_backtrack_position(&self) {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             z3_sys::Z3_solver_push(self.z3_context, self.z3_solver);
         }
@@ -186,7 +243,6 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
 
     #[logfn_inputs(TRACE)]
     fn solve(&self) -> SmtResult {
-        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             match z3_sys::Z3_solver_check(self.z3_context, self.z3_solver) {
                 z3_sys::Z3_L_TRUE => SmtResult::Satisfiable,
@@ -195,6 +251,24 @@ impl SmtSolver<Z3ExpressionType> for Z3Solver {
             }
         }
     }
+
+    #[logfn_inputs(TRACE)]
+    fn solve_script(&self, script: &str) -> SmtResult {
+        let Ok(script) = CString::new(script) else {
+            return SmtResult::Undefined;
+        };
+        unsafe {
+            // A solver of its own, so that the current context is left alone. It shares the
+            // time-out of the context.
+            let z3_solver = z3_sys::Z3_mk_solver(self.z3_context);
+            z3_sys::Z3_solver_from_string(self.z3_context, z3_solver, script.as_ptr());
+            match z3_sys::Z3_solver_check(self.z3_context, z3_solver) {
+                z3_sys::Z3_L_TRUE => SmtResult::Satisfiable,
+                z3_sys::Z3_L_FALSE => SmtResult::Unsatisfiable,
+                _ => SmtResult::Undefined,
+            }
+        }
+    }
 }
 
 impl Z3Solver {
@@ -2175,6 +2249,7 @@ impl Z3Solver {
 
 impl Drop for Z3Solver {
     fn drop(&mut self) {
+        let _guard = Z3_MUTEX.lock().unwrap();
         unsafe {
             z3_sys::Z3_del_context(self.z3_context);
         };
@@ -5,6 +5,7 @@
 //
 
 // Linear call graph with function pointer calls, single type, no dominance, no loops.
+// The outputs say how the callee of each call was resolved.
 
 fn fn1(x: u32, fn2: &fn(u32) -> u32) -> u32 {
     fn2(x)
@@ -26,7 +27,8 @@ pub fn main() {
     "included_crates": [],
     "datalog_config": {
         "datalog_backend": "DifferentialDatalog"
-    }
+    },
+    "include_call_resolution": true
 }
 */
 
@@ -36,10 +38,10 @@ digraph {
     1 [ label = "\"fnptr::fn1\"" ]
     2 [ label = "\"fnptr::fn2\"" ]
     3 [ label = "\"fnptr::fn3\"" ]
-    0 -> 1 [ ]
-    0 -> 1 [ ]
-    1 -> 2 [ ]
-    2 -> 3 [ ]
+    0 -> 1 [ label = "\"static\"" ]
+    0 -> 1 [ label = "\"static\"" ]
+    1 -> 2 [ label = "\"function_pointer\"" ]
+    2 -> 3 [ label = "\"static\"" ]
 }
 */
 
@@ -115,5 +117,10 @@ commit;
       3,
       0
     ]
+  ],
+  "resolutions": [
+    "function_pointer",
+    "static",
+    "static"
   ]
 }*/
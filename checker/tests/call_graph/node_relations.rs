@@ -0,0 +1,130 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Linear call graph with static calls, single type, no dominance, no loops.
+// One of the functions is unsafe. The Datalog output includes the node and call site relations.
+
+fn fn1(x: u32) -> u32 {
+    unsafe { fn2(x) }
+}
+unsafe fn fn2(x: u32) -> u32 {
+    fn3(x)
+}
+fn fn3(x: u32) -> u32 {
+    x
+}
+pub fn main() {
+    let x = 1;
+    fn1(x);
+}
+
+/* CONFIG
+{
+    "reductions": [],
+    "included_crates": [],
+    "datalog_config": {
+        "datalog_backend": "DifferentialDatalog",
+        "include_node_and_call_site_relations": true
+    }
+}
+*/
+
+/* EXPECTED:DOT
+digraph {
+    0 [ label = "\"node_relations::main\"" ]
+    1 [ label = "\"node_relations::fn1\"" ]
+    2 [ label = "\"node_relations::fn2\"" ]
+    3 [ label = "\"node_relations::fn3\"" ]
+    0 -> 1 [ ]
+    1 -> 2 [ ]
+    2 -> 3 [ ]
+}
+*/
+
+/* EXPECTED:DDLOG
+start;
+insert CallSite(0,"tests/call_graph/node_relations.rs",21,5);
+insert CallSite(1,"tests/call_graph/node_relations.rs",11,14);
+insert CallSite(2,"tests/call_graph/node_relations.rs",14,5);
+insert Edge(0,0,1);
+insert Edge(1,1,2);
+insert Edge(2,2,3);
+insert EdgeType(0,0);
+insert EdgeType(1,0);
+insert EdgeType(2,0);
+insert IsCrateRoot(0);
+insert IsUnsafe(2);
+insert NodeCrate(0,"node_relations");
+insert NodeCrate(1,"node_relations");
+insert NodeCrate(2,"node_relations");
+insert NodeCrate(3,"node_relations");
+insert NodeName(0,"node_relations::main");
+insert NodeName(1,"node_relations::fn1");
+insert NodeName(2,"node_relations::fn2");
+insert NodeName(3,"node_relations::fn3");
+commit;
+*/
+
+/* EXPECTED:TYPEMAP
+{
+  "0": "u32"
+}
+*/
+
+/* EXPECTED:CALL_SITES{
+  "files": [
+    "tests/call_graph/node_relations.rs"
+  ],
+  "callables": [
+    {
+      "name": "/node_relations/fn1(u32)->u32",
+      "file_index": 0,
+      "first_line": 10,
+      "local": true
+    },
+    {
+      "name": "/node_relations/fn2(u32)->u32",
+      "file_index": 0,
+      "first_line": 13,
+      "local": true
+    },
+    {
+      "name": "/node_relations/fn3(u32)->u32",
+      "file_index": 0,
+      "first_line": 16,
+      "local": true
+    },
+    {
+      "name": "/node_relations/main()->()",
+      "file_index": 0,
+      "first_line": 19,
+      "local": true
+    }
+  ],
+  "calls": [
+    [
+      0,
+      11,
+      14,
+      0,
+      1
+    ],
+    [
+      0,
+      14,
+      5,
+      1,
+      2
+    ],
+    [
+      0,
+      21,
+      5,
+      3,
+      0
+    ]
+  ]
+}*/
@@ -0,0 +1,128 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Linear call graph with static calls, single type, no dominance, no loops.
+// One of the functions is unsafe. The Soufflé output includes the node and call site relations.
+
+fn fn1(x: u32) -> u32 {
+    unsafe { fn2(x) }
+}
+unsafe fn fn2(x: u32) -> u32 {
+    fn3(x)
+}
+fn fn3(x: u32) -> u32 {
+    x
+}
+pub fn main() {
+    let x = 1;
+    fn1(x);
+}
+
+/* CONFIG
+{
+    "reductions": [],
+    "included_crates": [],
+    "datalog_config": {
+        "datalog_backend": "Souffle",
+        "include_node_and_call_site_relations": true
+    }
+}
+*/
+
+/* EXPECTED:DOT
+digraph {
+    0 [ label = "\"node_relations_souffle::main\"" ]
+    1 [ label = "\"node_relations_souffle::fn1\"" ]
+    2 [ label = "\"node_relations_souffle::fn2\"" ]
+    3 [ label = "\"node_relations_souffle::fn3\"" ]
+    0 -> 1 [ ]
+    1 -> 2 [ ]
+    2 -> 3 [ ]
+}
+*/
+
+/* EXPECTED:SOUFFLE
+0,0,1
+1,1,2
+2,2,3
+0,0
+1,0
+2,0
+0,"node_relations_souffle::main"
+1,"node_relations_souffle::fn1"
+2,"node_relations_souffle::fn2"
+3,"node_relations_souffle::fn3"
+0,"node_relations_souffle"
+1,"node_relations_souffle"
+2,"node_relations_souffle"
+3,"node_relations_souffle"
+0,"tests/call_graph/node_relations_souffle.rs",21,5
+1,"tests/call_graph/node_relations_souffle.rs",11,14
+2,"tests/call_graph/node_relations_souffle.rs",14,5
+0
+2
+*/
+
+/* EXPECTED:TYPEMAP
+{
+  "0": "u32"
+}
+*/
+
+/* EXPECTED:CALL_SITES{
+  "files": [
+    "tests/call_graph/node_relations_souffle.rs"
+  ],
+  "callables": [
+    {
+      "name": "/node_relations_souffle/fn1(u32)->u32",
+      "file_index": 0,
+      "first_line": 10,
+      "local": true
+    },
+    {
+      "name": "/node_relations_souffle/fn2(u32)->u32",
+      "file_index": 0,
+      "first_line": 13,
+      "local": true
+    },
+    {
+      "name": "/node_relations_souffle/fn3(u32)->u32",
+      "file_index": 0,
+      "first_line": 16,
+      "local": true
+    },
+    {
+      "name": "/node_relations_souffle/main()->()",
+      "file_index": 0,
+      "first_line": 19,
+      "local": true
+    }
+  ],
+  "calls": [
+    [
+      0,
+      11,
+      14,
+      0,
+      1
+    ],
+    [
+      0,
+      14,
+      5,
+      1,
+      2
+    ],
+    [
+      0,
+      21,
+      5,
+      3,
+      0
+    ]
+  ]
+}*/
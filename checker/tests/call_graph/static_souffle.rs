@@ -46,7 +46,8 @@ digraph {
 /* EXPECTED:SOUFFLE
 0,0,1
 1,1,2
-2,2,30,0
+2,2,3
+0,0
 1,0
 2,0
 */
@@ -36,7 +36,8 @@ pub fn main() {
     "included_crates": [],
     "datalog_config": {
         "datalog_backend": "DifferentialDatalog"
-    }
+    },
+    "include_call_resolution": true
 }
 */
 
@@ -45,7 +46,7 @@ digraph {
     0 [ label = "\"trait::{impl#0}::bar\"" ]
     1 [ label = "\"trait::{impl#1}::bar\"" ]
     2 [ label = "\"trait::main\"" ]
-    2 -> 0 [ ]
+    2 -> 0 [ label = "\"trait_dispatch\"" ]
 }
 */
 
@@ -88,5 +89,8 @@ commit;
       0,
       1
     ]
+  ],
+  "resolutions": [
+    "trait_dispatch"
   ]
 }*/
@@ -0,0 +1,106 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Linear call graph with static calls, with type relations inferred from struct fields.
+
+struct Foo {
+    x: u32,
+}
+struct Bar {
+    foo: Foo,
+}
+fn fn1(b: Bar) -> u32 {
+    fn2(b.foo)
+}
+fn fn2(f: Foo) -> u32 {
+    f.x
+}
+pub fn main() {
+    let b = Bar { foo: Foo { x: 1 } };
+    fn1(b);
+}
+
+/* CONFIG
+{
+    "reductions": [],
+    "included_crates": [],
+    "datalog_config": {
+        "datalog_backend": "DifferentialDatalog",
+        "infer_type_relations": true
+    }
+}
+*/
+
+/* EXPECTED:DOT
+digraph {
+    0 [ label = "\"type_inference::main\"" ]
+    1 [ label = "\"type_inference::fn1\"" ]
+    2 [ label = "\"type_inference::fn2\"" ]
+    0 -> 1 [ ]
+    1 -> 2 [ ]
+}
+*/
+
+/* EXPECTED:DDLOG
+start;
+insert Edge(0,0,1);
+insert Edge(1,1,2);
+insert EdgeType(0,0);
+insert EdgeType(1,1);
+insert Member(0,1);
+insert Member(1,2);
+commit;
+*/
+
+/* EXPECTED:TYPEMAP
+{
+  "0": "Bar",
+  "1": "Foo",
+  "2": "u32"
+}
+*/
+
+/* EXPECTED:CALL_SITES{
+  "files": [
+    "tests/call_graph/type_inference.rs"
+  ],
+  "callables": [
+    {
+      "name": "/type_inference/fn1(Bar)->u32",
+      "file_index": 0,
+      "first_line": 15,
+      "local": true
+    },
+    {
+      "name": "/type_inference/fn2(Foo)->u32",
+      "file_index": 0,
+      "first_line": 18,
+      "local": true
+    },
+    {
+      "name": "/type_inference/main()->()",
+      "file_index": 0,
+      "first_line": 21,
+      "local": true
+    }
+  ],
+  "calls": [
+    [
+      0,
+      16,
+      5,
+      0,
+      1
+    ],
+    [
+      0,
+      23,
+      5,
+      2,
+      0
+    ]
+  ]
+}*/
@@ -83,6 +83,102 @@ fn run_call_graph_tests() {
     assert_eq!(result, 0);
 }
 
+// Checks the bundled Datalog rules in the datalog directory, since neither Soufflé nor
+// Differential Datalog is needed to run the tests. Only the declarations, the arities of the
+// relations and the ends of the rules are checked, not the full syntax. The declared input
+// relations must be the relations output by the call graph generator.
+#[test]
+fn bundled_datalog_rules_are_well_formed() {
+    let mut datalog_path = PathBuf::from_str("datalog").unwrap();
+    if !datalog_path.exists() {
+        datalog_path = PathBuf::from_str("checker/datalog").unwrap();
+    }
+    let input_relations: HashMap<String, usize> = [
+        ("Edge", 3),
+        ("EdgeType", 2),
+        ("Dom", 2),
+        ("EqType", 2),
+        ("Member", 2),
+        ("NodeName", 2),
+        ("NodeCrate", 2),
+        ("CallSite", 4),
+        ("IsCrateRoot", 1),
+        ("IsUnsafe", 1),
+    ]
+    .into_iter()
+    .map(|(name, arity)| (name.to_string(), arity))
+    .collect();
+
+    let souffle_path = datalog_path.join("souffle");
+    let declarations = read_to_string(souffle_path.join("mirai.dl")).unwrap();
+    let souffle_input = Regex::new(r"(?m)^\.input\s+(\w+)\((.*)\)").unwrap();
+    let inputs: Vec<_> = souffle_input.captures_iter(&declarations).collect();
+    assert_eq!(inputs.len(), input_relations.len());
+    for input in inputs {
+        // Soufflé separates the columns of fact files with tabs by default.
+        assert!(input[2].contains(r#"delimiter=",""#), "{}", &input[0]);
+    }
+    for rules_file in ["crate_calls.dl", "reachable.dl", "reaches_unsafe.dl"] {
+        let rules = read_to_string(souffle_path.join(rules_file)).unwrap();
+        assert!(rules.contains("#include \"mirai.dl\""), "{rules_file}");
+        let program = format!("{declarations}\n{rules}");
+        let directive = Regex::new(r"(?m)^\.(?:input|output)\s+(\w+)").unwrap();
+        for captures in directive.captures_iter(&program) {
+            let decl = format!(".decl {}(", &captures[1]);
+            assert!(program.contains(&decl), "{}", &captures[0]);
+        }
+        check_datalog_program(&program, r"(?m)^\.decl\s+(\w+)\((.*)\)", &input_relations);
+    }
+
+    let ddlog_path = datalog_path.join("ddlog");
+    for rules_file in ["crate_calls.dl", "reachable.dl", "reaches_unsafe.dl"] {
+        let program = read_to_string(ddlog_path.join(rules_file)).unwrap();
+        check_datalog_program(
+            &program,
+            r"(?m)^(?:input |output )?relation\s+(\w+)\((.*)\)",
+            &input_relations,
+        );
+    }
+}
+
+// Checks that the relations of the given program that are declared by lines that match the
+// given pattern include the given input relations, that every relation that is used is declared
+// with the arity that it is used with, and that every rule ends with a period.
+fn check_datalog_program(
+    program: &str,
+    declaration_pattern: &str,
+    input_relations: &HashMap<String, usize>,
+) {
+    let declaration = Regex::new(declaration_pattern).unwrap();
+    let mut arities = HashMap::new();
+    for captures in declaration.captures_iter(program) {
+        let arity = captures[2].matches(':').count();
+        let previous = arities.insert(captures[1].to_string(), arity);
+        assert!(previous.is_none(), "{} is declared twice", &captures[1]);
+    }
+    for (name, arity) in input_relations {
+        assert_eq!(arities.get(name), Some(arity), "{name}");
+    }
+    let atom = Regex::new(r"\b([A-Z]\w*)\(([^()]*)\)").unwrap();
+    let mut rule = String::new();
+    for line in program.lines() {
+        let line = line.split("//").next().unwrap().trim();
+        if line.is_empty() || line.starts_with(['.', '#']) || declaration.is_match(line) {
+            continue;
+        }
+        for captures in atom.captures_iter(line) {
+            let arity = captures[2].split(',').count();
+            assert_eq!(arities.get(&captures[1]), Some(&arity), "{line}");
+        }
+        rule.push_str(line);
+        if line.ends_with('.') {
+            assert!(rule.contains(":-"), "{rule}");
+            rule.clear();
+        }
+    }
+    assert!(rule.is_empty(), "unterminated rule: {rule}");
+}
+
 fn find_extern_library(base_name: &str) -> String {
     let mut deps_path = PathBuf::from_str("../target/debug").unwrap();
     if !deps_path.exists() {
@@ -165,6 +261,10 @@ fn build_options(early_error_handler: &EarlyDiagCtxt) -> Options {
 struct DatalogTestConfig {
     datalog_backend: DatalogBackend,
     type_relations_path: Option<Box<str>>,
+    #[serde(default)]
+    infer_type_relations: bool,
+    #[serde(default)]
+    include_node_and_call_site_relations: bool,
 }
 
 // Partial call graph config to be read from the
@@ -174,6 +274,10 @@ struct CallGraphTestConfig {
     reductions: Vec<CallGraphReduction>,
     included_crates: Vec<Box<str>>,
     datalog_config: DatalogTestConfig,
+    #[serde(default)]
+    include_call_resolution: bool,
+    #[serde(default)]
+    dot_collapse_sccs: bool,
 }
 
 // Write a call graph configuration file for the current test case
@@ -194,18 +298,30 @@ fn generate_call_graph_config(file_name: &str, temp_dir_path: &str) -> (CallGrap
         }
         DatalogBackend::Souffle => temp_dir_path.to_owned().into_boxed_str(),
     };
-    let call_graph_config = CallGraphConfig::new(
+    let mut datalog_config = DatalogConfig::new(
+        datalog_path,
+        format!("{temp_dir_path}/types.json").into_boxed_str(),
+        call_graph_test_config.datalog_config.type_relations_path,
+        call_graph_test_config.datalog_config.datalog_backend,
+        call_graph_test_config.datalog_config.infer_type_relations,
+    );
+    datalog_config.set_include_node_and_call_site_relations(
+        call_graph_test_config
+            .datalog_config
+            .include_node_and_call_site_relations,
+    );
+    let mut call_graph_config = CallGraphConfig::new(
         Some(format!("{temp_dir_path}/call_sites.json").into_boxed_str()),
         Some(format!("{temp_dir_path}/graph.dot").into_boxed_str()),
         call_graph_test_config.reductions,
         call_graph_test_config.included_crates,
-        Some(DatalogConfig::new(
-            datalog_path,
-            format!("{temp_dir_path}/types.json").into_boxed_str(),
-            call_graph_test_config.datalog_config.type_relations_path,
-            call_graph_test_config.datalog_config.datalog_backend,
-        )),
+        Some(datalog_config),
     );
+    call_graph_config.set_include_call_resolution(call_graph_test_config.include_call_resolution);
+    call_graph_config.set_dot_collapse_sccs(call_graph_test_config.dot_collapse_sccs);
+    if get_expected_output(&test_case_data, "SCC").is_some() {
+        call_graph_config.set_scc_path(Some(format!("{temp_dir_path}/scc.json").into_boxed_str()));
+    }
     let call_graph_config_path = format!("{temp_dir_path}/call_graph_config.json");
     let call_graph_config_str =
         serde_json::to_string(&call_graph_config).expect("Failed to serialize config");
@@ -350,15 +466,76 @@ enum CallGraphOutputType {
     Ddlog,
     TypeMap,
     Souffle,
+    Scc,
 }
 
+// Returns the contents of all the Soufflé fact files, one file after the other.
+// The files do not end with a newline, so one is put between them.
 fn get_souffle_output(output_path: &Path) -> Result<String, std::io::Error> {
+    let mut facts = vec![];
+    for relation in [
+        "Dom",
+        "Edge",
+        "EdgeType",
+        "EqType",
+        "Member",
+        "NodeName",
+        "NodeCrate",
+        "CallSite",
+        "IsCrateRoot",
+        "IsUnsafe",
+    ] {
+        facts.push(fs::read_to_string(
+            output_path.join(format!("{relation}.facts")),
+        )?);
+    }
+    Ok(facts.join("\n"))
+}
+
+// Returns the expected output in the /* EXPECTED:<kind> */ comment of the test case, if any.
+fn get_expected_output(test_case_data: &str, kind: &str) -> Option<String> {
+    let expected_regex = Regex::new(&format!(r"(/\* EXPECTED:{kind})([\S\s]*?)(\*/)")).unwrap();
+    expected_regex
+        .captures(test_case_data)
+        .map(|captures| captures[2].to_owned())
+}
+
+// Check an output file of a run-pass test case against the expected output from the test case.
+fn check_output(
+    file_name: &str,
+    kind: &str,
+    expected: &str,
+    actual: Result<String, std::io::Error>,
+) -> usize {
+    match actual {
+        Ok(actual) if compare_lines(expected, &actual) => 0,
+        Ok(actual) => {
+            println!("{file_name} failed to match {kind} output");
+            println!("Expected:\n{expected}");
+            println!("Actual:\n{actual}");
+            1
+        }
+        Err(e) => {
+            println!("{file_name} failed to read {kind} output: {e}");
+            1
+        }
+    }
+}
+
+// Renders the unresolved calls report as a line of the form crate:line reason for every call,
+// which leaves out the details that depend on how the callees are printed.
+fn get_unresolved_calls_output(report_path: &Path) -> Result<String, std::io::Error> {
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(report_path)?)?;
     let mut out = String::new();
-    out.push_str(fs::read_to_string(output_path.join("Dom.facts"))?.as_str());
-    out.push_str(fs::read_to_string(output_path.join("Edge.facts"))?.as_str());
-    out.push_str(fs::read_to_string(output_path.join("EdgeType.facts"))?.as_str());
-    out.push_str(fs::read_to_string(output_path.join("EqType.facts"))?.as_str());
-    out.push_str(fs::read_to_string(output_path.join("Member.facts"))?.as_str());
+    for (crate_name, calls) in report["crates"].as_object().into_iter().flatten() {
+        for call in calls.as_array().into_iter().flatten() {
+            out.push_str(&format!(
+                "{crate_name}:{} {}\n",
+                call["line"],
+                call["reason"].as_str().unwrap_or_default()
+            ));
+        }
+    }
     Ok(out)
 }
 
@@ -384,6 +561,7 @@ fn check_call_graph_output(
         CallGraphOutputType::Souffle => {
             Regex::new(r"(/\* EXPECTED:SOUFFLE)([\S\s]*?)(\*/)").unwrap()
         }
+        CallGraphOutputType::Scc => Regex::new(r"(/\* EXPECTED:SCC)([\S\s]*?)(\*/)").unwrap(),
     };
     let expected: String = if let Some(captures) = expected_regex.captures(&test_case_data) {
         assume!(captures.len() == 4);
@@ -405,6 +583,7 @@ fn check_call_graph_output(
         CallGraphOutputType::Souffle => {
             get_souffle_output(Path::new(call_graph_config.get_ddlog_path().unwrap()))
         }
+        CallGraphOutputType::Scc => fs::read_to_string(call_graph_config.get_scc_path().unwrap()),
     };
     if let Ok(actual) = actual {
         if compare_lines(&expected, &actual) {
@@ -426,19 +605,53 @@ fn check_call_graph_output(
     }
 }
 
-// Default test driver
+// Default test driver; also checks the output files that have expectations in the test case.
 fn start_driver(config: DriverConfig) -> usize {
     let early_error_handler = EarlyDiagCtxt::new(config::ErrorOutputType::default());
     let sys_root = utils::find_sysroot();
-    let options = build_options(&early_error_handler);
-    self::invoke_driver(
+    let mut options = build_options(&early_error_handler);
+    let test_case_data =
+        fs::read_to_string(Path::new(&config.file_name)).expect("Failed to read test case");
+    let expected_unresolved_calls = get_expected_output(&test_case_data, "UNRESOLVED_CALLS");
+    let unresolved_calls_path = format!("{}/unresolved_calls.json", config.temp_dir_path);
+    if expected_unresolved_calls.is_some() {
+        options.unresolved_calls = Some(unresolved_calls_path.clone());
+    }
+    let expected_test_skeletons = get_expected_output(&test_case_data, "TEST_SKELETONS");
+    let test_skeletons_path = format!("{}/tests", config.temp_dir_path);
+    if expected_test_skeletons.is_some() {
+        options.test_skeletons = Some(test_skeletons_path.clone());
+    }
+    let result = self::invoke_driver(
         &early_error_handler,
-        config.file_name,
-        config.temp_dir_path,
+        config.file_name.clone(),
+        config.temp_dir_path.clone(),
         sys_root,
         config.extern_deps,
         options,
-    )
+    );
+    if result != 0 {
+        return result;
+    }
+    let mut result = 0;
+    if let Some(expected) = expected_unresolved_calls {
+        result += check_output(
+            &config.file_name,
+            "UNRESOLVED_CALLS",
+            &expected,
+            get_unresolved_calls_output(Path::new(&unresolved_calls_path)),
+        );
+    }
+    if let Some(expected) = expected_test_skeletons {
+        // The crate of a test case is called mirai.
+        result += check_output(
+            &config.file_name,
+            "TEST_SKELETONS",
+            &expected,
+            fs::read_to_string(Path::new(&test_skeletons_path).join("mirai_mirai.rs")),
+        );
+    }
+    result
 }
 
 // Test driver for call graph generation;
@@ -482,7 +695,15 @@ fn start_driver_call_graph(config: DriverConfig) -> usize {
                 &call_graph_config,
                 CallGraphOutputType::Souffle,
             ),
-        })
+        }) + if call_graph_config.get_scc_path().is_some() {
+            check_call_graph_output(
+                &config.file_name,
+                &call_graph_config,
+                CallGraphOutputType::Scc,
+            )
+        } else {
+            0
+        }
     } else {
         result
     }
@@ -0,0 +1,32 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that diagnostics get a note with parameter values for which they fail.
+
+// MIRAI_FLAGS --counterexamples
+
+use mirai_annotations::*;
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn check_point(p: &Point) {
+    verify!(p.x != 3 || p.y != -4); //~ possible false verification condition
+                                    //~ counterexample: p.x = 3, p.y = -4
+}
+
+fn callee(i: u8) {
+    precondition!(i != 7); //~ related location
+}
+
+pub fn caller(n: u8) {
+    callee(n); //~ possible unsatisfied precondition
+               //~ counterexample: n = 7
+}
+
+pub fn main() {}
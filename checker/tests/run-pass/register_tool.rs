@@ -0,0 +1,21 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that MIRAI only registers itself as a tool when the crate does not already do so.
+// The crate enables the feature itself, but only registers the tool when it is not analyzed,
+// so MIRAI must register the tool without enabling the feature a second time.
+// Mentions of the attributes in comments, such as #![register_tool(mirai)], do not count.
+
+#![cfg_attr(mirai, feature(register_tool))]
+#![cfg_attr(not(mirai), register_tool(mirai))]
+
+#[cfg_attr(mirai, mirai::allow(overflow))]
+pub fn suppressed_by_function_attribute(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    a + 1
+}
+
+pub fn main() {}
@@ -0,0 +1,30 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that the rule of a diagnostic is that of the check that failed,
+// whatever the message of the check says.
+
+use mirai_annotations::*;
+
+pub fn verify_with_message(x: u8) {
+    checked_verify!(x != 3, "x would add with overflow"); //~ [MIRAI0004] possible false verification condition: x would add with overflow
+}
+
+fn callee(i: usize) {
+    precondition!(i != 7, "index out of bounds"); //~ related location
+}
+
+pub fn caller(n: usize) {
+    callee(n); //~ [MIRAI0005] possible unsatisfied precondition: index out of bounds
+}
+
+pub fn verify_unreachable_with_message(x: u8) {
+    if x == 3 {
+        verify_unreachable!("x is never 3"); //~ [MIRAI0009] x is never 3
+    }
+}
+
+pub fn main() {}
@@ -0,0 +1,44 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// Tests that diagnostics can be suppressed with attributes and comments
+
+#[cfg_attr(mirai, mirai::allow(overflow))]
+pub fn suppressed_by_function_attribute(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    a + 1
+}
+
+pub fn suppressed_by_statement_attribute(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    #[cfg_attr(mirai, mirai::allow(MIRAI0001))]
+    let b = a + 1;
+    b
+}
+
+pub fn suppressed_by_comment(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    // mirai: allow(overflow)
+    a + 1
+}
+
+pub fn suppressed_by_trailing_comment(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    a + 1 // mirai: allow(overflow)
+}
+
+pub fn not_suppressed(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    // mirai: allow(division_by_zero) //~ this suppression does not apply to any diagnostic
+    a + 1 //~ possible attempt to add with overflow
+}
+
+pub fn unknown_rule(cond: bool) -> u8 {
+    let a: u8 = if cond { 0xFF } else { 1 };
+    a + 1 // mirai: allow(overflow, no_such_rule) //~ unknown rule in suppression: no_such_rule
+}
+
+pub fn main() {}
@@ -0,0 +1,49 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks the tests that are written for possible failures by --test_skeletons.
+
+use mirai_annotations::*;
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn check_point(p: &Point) {
+    verify!(p.x != 3 || p.y != -4); //~ possible false verification condition
+}
+
+pub fn divide(_bytes: &[u8], d: u8) -> u8 {
+    100 / d //~ possible attempt to divide by zero
+}
+
+pub fn main() {}
+
+/* EXPECTED:TEST_SKELETONS
+// Tests generated by MIRAI for the possible failures that it found in crate mirai.
+// Parameters that the failure does not depend on are given zero, false or empty values,
+// and values of other types are left as todo!() placeholders.
+
+#[test]
+#[ignore = "the condition is checked by a MIRAI annotation"]
+#[should_panic]
+fn check_point_line_17() {
+    // tests/run-pass/test_skeletons.rs:17:5: possible false verification condition
+    // This condition is checked by a MIRAI annotation, which only panics at run time
+    // if it is a checked annotation, such as checked_verify! or checked_precondition!.
+    // Remove #[ignore] if it is.
+    let _ = mirai::check_point(&mirai::Point { x: 3, y: -4 });
+}
+
+#[test]
+#[ignore = "the todo!() placeholders need values"]
+#[should_panic(expected = "attempt to divide by zero")]
+fn divide_line_21() {
+    // tests/run-pass/test_skeletons.rs:21:5: possible attempt to divide by zero
+    let _ = mirai::divide(&todo!("a value of type [u8]"), 0);
+}
+*/
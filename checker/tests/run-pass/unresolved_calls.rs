@@ -0,0 +1,42 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+
+// A test that checks that calls without a summary of their callee are recorded in the
+// unresolved calls report, with the reason why there is no summary.
+
+// MIRAI_FLAGS --diag=default
+
+pub struct Handler {
+    pub f: fn(u32) -> u32,
+}
+
+pub trait Shape {
+    fn area(&self) -> u32;
+}
+
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+pub fn unknown_callee(h: &Handler) -> u32 {
+    (h.f)(1)
+}
+
+pub fn virtual_call(s: &dyn Shape) -> u32 {
+    s.area()
+}
+
+pub fn missing_summary(x: i32) -> i32 {
+    unsafe { abs(x) }
+}
+
+pub fn main() {}
+
+/* EXPECTED:UNRESOLVED_CALLS
+mirai:25 unknown_callee
+mirai:29 virtual
+mirai:33 missing_summary
+*/